@@ -5,48 +5,510 @@
  * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
  * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
  *
+ * vss command line: vss [options] <command> [args]
+ *   exit code: 0 success, 1 check failed (errors, differences, no match, not formatted), 2 usage/parsing error
  */
 
 extern crate vssparser;
 
 use std::env;
+use std::fs;
+use std::process::exit;
 use vssparser::prelude::*;
 
-fn main() -> Result<(), AfbError> {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        return Err(AfbError::new(
-            "invalid-args-count",
-            "filename missing".to_string(),
+const USAGE: &str = "usage: vss [options] <command> [args]
+commands:
+  dump <spec>                    print every parsed node (stdout only)
+  check <spec> [--strict]        print diagnostics, exit 1 on error (--strict: on warning too)
+  lint <spec> [--config yaml]    naming rules, exit 1 on error (--strict: on warning too)
+  export <spec> [--format F]     json|yaml|csv|proto (default json) [--locale L]
+  query <spec> <pattern>         print matching vpaths, exit 1 when nothing matches
+                                 [--type branch|sensor|actuator|attribute]
+  tree <spec> [root]             print signal tree
+  diff <old> <new>               print changes, exit 1 when specs differ
+  blame <spec> <vpath>           definitions, include chain and origin of every field
+  resolve <spec> <vpath>         current vpath, former vpaths (fka) print a notice
+  stats <spec> [--format F]      text|json node counts, units, depth, instances, per file
+  fmt [--check] <vspec>...       format files in place, --check exit 1 when not formatted
+  doc <spec> [--format F]        markdown|html (default markdown) [--locale L]
+  graph <spec> [--format F]      dot|mermaid (default dot) [--root vpath] [--depth N] [--no-color]
+  catalog <spec> -o <file>       write binary catalog
+options:
+  -I, --include <dir>            include search directory (repeatable)
+  --overlay <vspec>              overlay applied on top of spec (repeatable)
+  --units <yaml>                 units file replacing built-in table
+  --quantities <yaml>            quantities file (with --units)
+  --duplicates <policy>          error|first-wins|last-wins vpath defined twice (default error)
+  --vss-version <3.x|4.x>        target specification, newer features are warnings (default 4.x)
+  --expand                       expand instances
+  -o, --output <file>            write output to file instead of stdout";
+
+#[derive(Default)]
+struct Options {
+    command: String,
+    args: Vec<String>,
+    includes: Vec<String>,
+    overlays: Vec<String>,
+    units: Option<String>,
+    quantities: Option<String>,
+    duplicates: Option<VssDuplicates>,
+    version: Option<VssVersion>,
+    expand: bool,
+    output: Option<String>,
+    format: Option<String>,
+    locale: Option<String>,
+    config: Option<String>,
+    vtype: Option<String>,
+    root: Option<String>,
+    depth: Option<String>,
+    strict: bool,
+    check: bool,
+    no_color: bool,
+}
+
+fn usage_error(info: String) -> AfbError {
+    AfbError::new("vss-usage", info)
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, AfbError> {
+    let mut options = Options::default();
+    let mut positionals: Vec<String> = Vec::new();
+    while let Some(arg) = args.next() {
+        let mut value = |label: &str| match args.next() {
+            Some(value) => Ok(value),
+            None => Err(usage_error(format!("option {} requires a value", label))),
+        };
+        match arg.as_str() {
+            "-I" | "--include" => options.includes.push(value(&arg)?),
+            "--overlay" => options.overlays.push(value(&arg)?),
+            "--units" => options.units = Some(value(&arg)?),
+            "--quantities" => options.quantities = Some(value(&arg)?),
+            "--duplicates" => options.duplicates = Some(value(&arg)?.parse()?),
+            "--vss-version" => options.version = Some(value(&arg)?.parse()?),
+            "-o" | "--output" => options.output = Some(value(&arg)?),
+            "-f" | "--format" => options.format = Some(value(&arg)?),
+            "--locale" => options.locale = Some(value(&arg)?),
+            "--config" => options.config = Some(value(&arg)?),
+            "--type" => options.vtype = Some(value(&arg)?),
+            "--root" => options.root = Some(value(&arg)?),
+            "--depth" => options.depth = Some(value(&arg)?),
+            "--expand" => options.expand = true,
+            "--strict" => options.strict = true,
+            "--check" => options.check = true,
+            "--no-color" => options.no_color = true,
+            _ if arg.starts_with('-') && arg.len() > 1 => {
+                return Err(usage_error(format!("unknown option:{}", arg)))
+            }
+            _ => positionals.push(arg),
+        }
+    }
+    if positionals.is_empty() {
+        return Err(usage_error("missing command".to_string()));
+    }
+    options.command = positionals.remove(0);
+    options.args = positionals;
+    Ok(options)
+}
+
+impl Options {
+    fn arg(&self, idx: usize, label: &str) -> Result<&str, AfbError> {
+        match self.args.get(idx) {
+            Some(value) => Ok(value.as_str()),
+            None => Err(usage_error(format!(
+                "command {} requires <{}>",
+                self.command, label
+            ))),
+        }
+    }
+
+    fn load(&self, filename: &str) -> Result<(Locator, VssSpec), AfbError> {
+        self.load_with(filename, self.expand)
+    }
+
+    fn load_with(&self, filename: &str, expand: bool) -> Result<(Locator, VssSpec), AfbError> {
+        let mut vss = VssHandle::new(filename.to_string(), None, None);
+        if let Some(policy) = self.duplicates {
+            vss.data.duplicates = policy;
+        }
+        if let Some(version) = self.version {
+            vss.data.version = version;
+        }
+        for dirname in &self.includes {
+            vss.add_include_dir(dirname);
+        }
+        for overlay in &self.overlays {
+            vss.add_overlay(overlay.to_string());
+        }
+        vss_from_file(&mut vss)?;
+        let mut locator = Locator::new(vss)?;
+        if let Some(units) = &self.units {
+            locator.units = VssUnitPool::from_file(units, self.quantities.as_deref())?;
+        }
+        let mut spec = vss_parse_rules(&locator)?;
+        if expand {
+            spec = vss_expand_instances(&spec);
+        }
+        Ok((locator, spec))
+    }
+
+    fn write(&self, output: &str) -> Result<(), AfbError> {
+        match &self.output {
+            None => {
+                print!("{}", output);
+                Ok(())
+            }
+            Some(filename) => match fs::write(filename, output) {
+                Ok(()) => Ok(()),
+                Err(error) => Err(AfbError::new(
+                    "vss-output-write",
+                    format!("{} ({})", filename, error),
+                )),
+            },
+        }
+    }
+}
+
+fn cmd_dump(options: &Options) -> Result<i32, AfbError> {
+    let (locator, vss) = options.load(options.arg(0, "spec")?)?;
+    println!("\n== Branches ===");
+    for branch in vss.branches {
+        branch.println(&locator);
+    }
+    println!("\n== Sensors ===");
+    for sensor in vss.sensors {
+        sensor.println(&locator);
+    }
+    println!("\n== Actuators ===");
+    for actuator in vss.actuators {
+        actuator.println(&locator);
+    }
+    println!("\n== Attributes ===");
+    for attribute in vss.attributes {
+        attribute.println(&locator);
+    }
+    if !vss.diagnostics.is_empty() {
+        println!("\n== Diagnostics ===");
+        for diagnostic in vss.diagnostics {
+            diagnostic.println(&locator);
+        }
+    }
+    Ok(0)
+}
+
+// one line per diagnostic followed by a summary, status 1 on error (or warning when strict)
+fn diagnostics_output(
+    options: &Options,
+    locator: &Locator,
+    diagnostics: &[VssDiagnostic],
+) -> Result<i32, AfbError> {
+    let mut output = String::new();
+    let mut errors = 0;
+    for diagnostic in diagnostics {
+        if diagnostic.level == VssLevel::Error {
+            errors += 1;
+        }
+        let filename = locator.filename(diagnostic.location);
+        output.push_str(&format!(
+            "{}: {}{}:{} [{}] {} {}\n",
+            diagnostic.level.to_str(),
+            filename.dirname,
+            filename.basename,
+            diagnostic.location.line,
+            diagnostic.uid,
+            diagnostic.vpath,
+            diagnostic.info
         ));
     }
+    let warnings = diagnostics.len() - errors;
+    output.push_str(&format!("{} error(s), {} warning(s)\n", errors, warnings));
+    options.write(&output)?;
+    if errors > 0 || (options.strict && warnings > 0) {
+        Ok(1)
+    } else {
+        Ok(0)
+    }
+}
+
+fn cmd_check(options: &Options) -> Result<i32, AfbError> {
+    let (locator, spec) = options.load(options.arg(0, "spec")?)?;
+    diagnostics_output(options, &locator, &spec.diagnostics)
+}
 
-    // recursively parse VSS files (Fulup:TBD group in a single api call)
-    let vss = VssHandle::new (args[1].to_string(), None, None);
-    vss_from_file(&vss)?;
-    let locator= Locator::new(vss)?;
+fn cmd_lint(options: &Options) -> Result<i32, AfbError> {
+    let config = match &options.config {
+        Some(filename) => VssLintConfig::from_file(filename)?,
+        None => VssLintConfig::default(),
+    };
+    let (locator, spec) = options.load(options.arg(0, "spec")?)?;
+    diagnostics_output(options, &locator, &vss_lint(&spec, &config))
+}
+
+fn cmd_export(options: &Options) -> Result<i32, AfbError> {
+    let format = match &options.format {
+        Some(value) => value.parse::<VssExportFormat>()?,
+        None => VssExportFormat::Json,
+    };
+    let (_, spec) = options.load(options.arg(0, "spec")?)?;
+    let locale = options.locale.as_deref();
+    options.write(&vss_export_build(&spec, format, locale))?;
+    Ok(0)
+}
 
-    // let vss_data = vss.data.try_borrow().unwrap();
-    // for vss in &vss_data.lines {
-    //     println!("{}{}:{} =>{}", vss.filename.dirname, vss.filename.basename, vss.line, vss.text);
-    // }
+fn cmd_query(options: &Options) -> Result<i32, AfbError> {
+    let (_, spec) = options.load(options.arg(0, "spec")?)?;
+    let pattern = options.arg(1, "pattern")?;
+    let mut filter = VssFilter::new();
+    if let Some(value) = &options.vtype {
+        filter.vtype = match value.to_lowercase().as_str() {
+            "branch" => Some(VssObjectType::Branch),
+            "sensor" => Some(VssObjectType::Sensor),
+            "actuator" => Some(VssObjectType::Actuator),
+            "attribute" => Some(VssObjectType::Attribute),
+            _ => return Err(usage_error(format!("unknown node type:{}", value))),
+        };
+    }
+    let index = VssIndex::new(&spec);
+    let nodes = index.filter(pattern, &filter);
+    let mut output = String::new();
+    for node in &nodes {
+        output.push_str(&format!("{}\n", node.vpath()));
+    }
+    options.write(&output)?;
+    if nodes.is_empty() {
+        Ok(1)
+    } else {
+        Ok(0)
+    }
+}
 
-    match vss_parse_rules(&locator) {
-        Err(error) => Err(error),
-        Ok(vss) => {
-            println!("\n== Branches ===");
-            for branch in vss.branches {
-                branch.println(&locator);
+fn tree_label(node: &VssNode) -> String {
+    let name = node.vpath().rsplit('.').next().unwrap_or("");
+    let mut details = vec![node.vtype().to_str().to_string()];
+    if let Some(datatype) = node.datatype() {
+        match node.arraysize() {
+            Some(_) => details.push(format!("{}[]", datatype.to_str())),
+            None => details.push(datatype.to_str().to_string()),
+        }
+    }
+    if let Some(unit) = node.unit() {
+        if *unit != VssUnit::None {
+            details.push(unit.to_str().to_string());
+        }
+    }
+    format!("{} ({})", name, details.join(" "))
+}
+
+fn cmd_tree(options: &Options) -> Result<i32, AfbError> {
+    let (_, spec) = options.load(options.arg(0, "spec")?)?;
+    let index = VssIndex::new(&spec);
+    let root = options.args.get(1).or(options.root.as_ref());
+
+    // depth follows the stack of enclosing nodes
+    let mut output = String::new();
+    let mut stack: Vec<&str> = Vec::new();
+    for node in index.iter() {
+        let vpath = node.vpath();
+        if let Some(root) = root {
+            if vpath != root && !vpath.starts_with(&format!("{}.", root)) {
+                continue;
+            }
+        }
+        while let Some(parent) = stack.last() {
+            if vpath.starts_with(parent) && vpath[parent.len()..].starts_with('.') {
+                break;
             }
-            println!("\n== Sensors ===");
-            for sensor in vss.sensors {
-                sensor.println(&locator);
+            stack.pop();
+        }
+        output.push_str(&format!(
+            "{:indent$}{}\n",
+            "",
+            tree_label(&node),
+            indent = stack.len() * 2
+        ));
+        stack.push(vpath);
+    }
+    options.write(&output)?;
+    Ok(0)
+}
+
+// overlay and include history of one node, instances are never expanded
+fn cmd_blame(options: &Options) -> Result<i32, AfbError> {
+    let (_, spec) = options.load_with(options.arg(0, "spec")?, false)?;
+    let vpath = options.arg(1, "vpath")?;
+    match VssBlame::new(&spec, vpath) {
+        Some(blame) => {
+            options.write(&blame.to_text(&spec))?;
+            Ok(0)
+        }
+        None => {
+            eprintln!("vpath:{} not found", vpath);
+            Ok(1)
+        }
+    }
+}
+
+// former vpaths (fka) resolve to the current node with a deprecation notice on stderr
+fn cmd_resolve(options: &Options) -> Result<i32, AfbError> {
+    let (_, spec) = options.load(options.arg(0, "spec")?)?;
+    let vpath = options.arg(1, "vpath")?;
+    let index = VssIndex::new(&spec);
+    match index.resolve(vpath) {
+        Some(resolved) => {
+            if let Some(notice) = resolved.notice() {
+                eprintln!("warning: {}", notice);
+            }
+            options.write(&format!("{}\n", resolved.node.vpath()))?;
+            Ok(0)
+        }
+        None => {
+            eprintln!("vpath:{} not found", vpath);
+            Ok(1)
+        }
+    }
+}
+
+fn cmd_diff(options: &Options) -> Result<i32, AfbError> {
+    let (_, old) = options.load(options.arg(0, "old")?)?;
+    let (_, new) = options.load(options.arg(1, "new")?)?;
+    let changes = vss_diff(&old, &new);
+    let mut output = String::new();
+    for change in &changes {
+        output.push_str(&format!("{}\n", change));
+    }
+    options.write(&output)?;
+    if changes.is_empty() {
+        Ok(0)
+    } else {
+        Ok(1)
+    }
+}
+
+fn cmd_stats(options: &Options) -> Result<i32, AfbError> {
+    let (locator, spec) = options.load(options.arg(0, "spec")?)?;
+    let stats = VssStats::new(&spec, &locator.units);
+    let output = match options.format.as_deref() {
+        None | Some("text") => stats.to_text(),
+        Some("json") => stats.to_json(),
+        Some(value) => {
+            return Err(usage_error(format!(
+                "unsupported stats format:{} (text|json)",
+                value
+            )))
+        }
+    };
+    options.write(&output)?;
+    Ok(0)
+}
+
+fn cmd_fmt(options: &Options) -> Result<i32, AfbError> {
+    if options.args.is_empty() {
+        return Err(usage_error("command fmt requires <vspec>...".to_string()));
+    }
+    let format = VssFormatOptions::default();
+    let mut status = 0;
+    for filename in &options.args {
+        match vss_format_file(filename, &format, options.check)? {
+            true => {}
+            false if options.check => {
+                println!("{}: not formatted", filename);
+                status = 1;
             }
-            println!("\n== Attributes ===");
-            for attribute in vss.attributes {
-                attribute.println(&locator);
+            false => println!("{}: formatted", filename),
+        }
+    }
+    Ok(status)
+}
+
+fn cmd_doc(options: &Options) -> Result<i32, AfbError> {
+    let format = match &options.format {
+        Some(value) => value.parse::<VssDocFormat>()?,
+        None => VssDocFormat::Markdown,
+    };
+    let (locator, spec) = options.load(options.arg(0, "spec")?)?;
+    let locale = options.locale.as_deref();
+    options.write(&vss_doc_build(&spec, &locator.units, format, locale))?;
+    Ok(0)
+}
+
+fn cmd_graph(options: &Options) -> Result<i32, AfbError> {
+    let format = match &options.format {
+        Some(value) => value.parse::<VssGraphFormat>()?,
+        None => VssGraphFormat::Dot,
+    };
+    let mut graph = VssGraphOptions {
+        root: options.root.clone(),
+        colors: !options.no_color,
+        ..Default::default()
+    };
+    if let Some(value) = &options.depth {
+        match value.parse::<usize>() {
+            Ok(depth) => graph.depth = Some(depth),
+            Err(_) => {
+                return Err(usage_error(format!(
+                    "depth:{} should be a positive integer",
+                    value
+                )))
             }
-            Ok(())
         }
     }
+
+    // --expand is done by the graph itself, instance levels are drawn as dashed nodes
+    let (_, spec) = options.load_with(options.arg(0, "spec")?, false)?;
+    graph.collapse_instances = !options.expand;
+    options.write(&vss_graph_build(&spec, format, &graph)?)?;
+    Ok(0)
+}
+
+fn cmd_catalog(options: &Options) -> Result<i32, AfbError> {
+    let (_, spec) = options.load(options.arg(0, "spec")?)?;
+    match &options.output {
+        Some(filename) => vss_catalog_to_file(&spec, filename)?,
+        None => {
+            return Err(usage_error(
+                "command catalog requires -o <file>".to_string(),
+            ))
+        }
+    }
+    Ok(0)
+}
+
+fn run(options: &Options) -> Result<i32, AfbError> {
+    match options.command.as_str() {
+        "dump" => cmd_dump(options),
+        "check" => cmd_check(options),
+        "lint" => cmd_lint(options),
+        "export" => cmd_export(options),
+        "query" => cmd_query(options),
+        "tree" => cmd_tree(options),
+        "diff" => cmd_diff(options),
+        "blame" => cmd_blame(options),
+        "resolve" => cmd_resolve(options),
+        "stats" => cmd_stats(options),
+        "fmt" => cmd_fmt(options),
+        "doc" => cmd_doc(options),
+        "graph" => cmd_graph(options),
+        "catalog" => cmd_catalog(options),
+        "help" => {
+            println!("{}", USAGE);
+            Ok(0)
+        }
+        _ => Err(usage_error(format!("unknown command:{}", options.command))),
+    }
+}
+
+fn main() {
+    let status = match parse_args(env::args().skip(1)) {
+        Err(error) => {
+            eprintln!("{}\n{}", error.to_str(), USAGE);
+            2
+        }
+        Ok(options) => match run(&options) {
+            Ok(status) => status,
+            Err(error) => {
+                eprintln!("{}", error.to_str());
+                2
+            }
+        },
+    };
+    exit(status);
 }
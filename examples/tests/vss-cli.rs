@@ -0,0 +1,113 @@
+/*
+ * Copyright (C) 2015-2023 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
+ * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
+ *
+ * vss command exit codes: 0 success, 1 check/diff/fmt --check failure, 2 usage or parsing error
+ */
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+fn vss(args: &[&str]) -> i32 {
+    let output = Command::new(env!("CARGO_BIN_EXE_vss"))
+        .args(args)
+        .output()
+        .expect("fail to run vss");
+    output.status.code().expect("vss killed by a signal")
+}
+
+fn fixture(vspec: &str) -> String {
+    format!(
+        "{}/../vssparser/tests/spec/{}",
+        env!("CARGO_MANIFEST_DIR"),
+        vspec
+    )
+}
+
+fn tmpdir(name: &str) -> PathBuf {
+    let dirname = std::env::temp_dir().join(format!("vss-cli-{}-{}", name, std::process::id()));
+    fs::create_dir_all(&dirname).unwrap();
+    dirname
+}
+
+#[test]
+fn fmt_check_exit_code() {
+    let dirname = tmpdir("fmt");
+    let formatted = dirname.join("formatted.vspec");
+    let unformatted = dirname.join("unformatted.vspec");
+    let source = "Vehicle:\n    description: Vehicle.\n    type: branch\n";
+    fs::write(
+        &formatted,
+        "Vehicle:\n  type: branch\n  description: Vehicle.\n",
+    )
+    .unwrap();
+    fs::write(&unformatted, source).unwrap();
+    let formatted = formatted.to_str().unwrap();
+    let unformatted = unformatted.to_str().unwrap();
+
+    assert_eq!(vss(&["fmt", "--check", formatted]), 0);
+    assert_eq!(vss(&["fmt", "--check", formatted, unformatted]), 1);
+    assert_eq!(fs::read_to_string(unformatted).unwrap(), source);
+    assert_eq!(vss(&["fmt", "--check"]), 2);
+    assert_eq!(vss(&["fmt", "--check", "missing.vspec"]), 2);
+
+    // rewrite then check again
+    assert_eq!(vss(&["fmt", unformatted]), 0);
+    assert_eq!(vss(&["fmt", "--check", unformatted]), 0);
+    fs::remove_dir_all(&dirname).unwrap();
+}
+
+#[test]
+fn check_exit_code() {
+    assert_eq!(vss(&["check", &fixture("vehicle.vspec")]), 0);
+    assert_eq!(vss(&["check", &fixture("diagnostic.vspec")]), 1);
+    assert_eq!(vss(&["check", &fixture("yaml/tab.vspec")]), 2);
+    assert_eq!(vss(&["check", &fixture("missing.vspec")]), 2);
+    assert_eq!(vss(&["check"]), 2);
+
+    // warnings only fail in strict mode
+    let dirname = tmpdir("check");
+    let warning = dirname.join("warning.vspec");
+    fs::write(
+        &warning,
+        "Vehicle:\n  type: branch\n  description: Vehicle.\n\
+         Vehicle.Inside:\n  datatype: float\n  type: sensor\n  unit: celsius\n  description: Inside.\n\
+         Vehicle.Outside:\n  datatype: float\n  type: sensor\n  unit: fahrenheit\n  description: Outside.\n",
+    )
+    .unwrap();
+    let warning = warning.to_str().unwrap();
+    assert_eq!(vss(&["check", warning]), 0);
+    assert_eq!(vss(&["check", "--strict", warning]), 1);
+    fs::remove_dir_all(&dirname).unwrap();
+}
+
+#[test]
+fn diff_exit_code() {
+    let vehicle = fixture("vehicle.vspec");
+    assert_eq!(vss(&["diff", &vehicle, &vehicle]), 0);
+    assert_eq!(vss(&["diff", &fixture("graph/graph.vspec"), &vehicle]), 1);
+    assert_eq!(vss(&["diff", &vehicle, &fixture("yaml/tab.vspec")]), 2);
+    assert_eq!(vss(&["diff", &vehicle]), 2);
+}
+
+#[test]
+fn export_locale() {
+    let dirname = tmpdir("export");
+    let output = dirname.join("vehicle.json");
+    let output = output.to_str().unwrap();
+    let vehicle = fixture("vehicle.vspec");
+    assert_eq!(
+        vss(&["export", &vehicle, "--locale", "fr", "-o", output]),
+        0
+    );
+    assert!(fs::read_to_string(output)
+        .unwrap()
+        .contains("Données générales du véhicule."));
+    assert_eq!(vss(&["export", &vehicle, "-o", output]), 0);
+    assert!(!fs::read_to_string(output).unwrap().contains("Données"));
+    fs::remove_dir_all(&dirname).unwrap();
+}
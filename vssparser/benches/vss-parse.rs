@@ -0,0 +1,97 @@
+/*
+ * Copyright (C) 2015-2023 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
+ * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
+ *
+ * synthetic catalog: 100 included files x 500 signals (50k sensors/actuators)
+ * run: cargo bench -p lib_vssparser
+ */
+
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use vssparser::prelude::*;
+
+const GROUPS: usize = 100;
+const SIGNALS: usize = 500;
+
+// generate spec once within tmp directory, returns root vspec filename
+fn synthetic_spec() -> String {
+    let dirname = std::env::temp_dir().join(format!("vss-bench-{}x{}", GROUPS, SIGNALS));
+    let rootname = dirname.join("root.vspec");
+    if rootname.exists() {
+        return rootname.to_string_lossy().to_string();
+    }
+    fs::create_dir_all(dirname.join("sub")).unwrap();
+
+    let mut root =
+        String::from("# synthetic spec\nVehicle:\n  type: branch\n  description: root\n\n");
+    for group in 0..GROUPS {
+        writeln!(
+            root,
+            "Vehicle.Group{group}:\n  type: branch\n  description: group {group}\n\n#include sub/Group{group}.vspec Vehicle.Group{group}\n"
+        )
+        .unwrap();
+
+        let mut buffer = String::new();
+        for signal in 0..SIGNALS {
+            let vtype = if signal % 5 == 0 {
+                "actuator"
+            } else {
+                "sensor"
+            };
+            writeln!(
+                buffer,
+                "# signal {signal}\nSignal{signal}:\n  datatype: float\n  type: {vtype}\n  unit: km/h\n  min: 0\n  max: 250\n  description: synthetic signal {signal}\n    spread on two lines\n"
+            )
+            .unwrap();
+        }
+        let filename: PathBuf = dirname.join(format!("sub/Group{group}.vspec"));
+        fs::write(filename, buffer).unwrap();
+    }
+    // root is written last, an interrupted generation is never reused
+    fs::write(&rootname, root).unwrap();
+    rootname.to_string_lossy().to_string()
+}
+
+fn load(filename: &str) -> Locator {
+    let mut vss = VssHandle::new(filename.to_string(), None, None);
+    vss_from_file(&mut vss).unwrap();
+    Locator::new(vss).unwrap()
+}
+
+fn vss_bench(criterion: &mut Criterion) {
+    let filename = synthetic_spec();
+    let locator = load(filename.as_str());
+    let spec = vss_parse_rules(&locator).unwrap();
+    assert_eq!(spec.sensors.len() + spec.actuators.len(), GROUPS * SIGNALS);
+
+    let mut group = criterion.benchmark_group("vss-50k");
+    group.sample_size(10);
+    group.measurement_time(Duration::from_secs(10));
+    group.bench_function("load", |bench| bench.iter(|| load(filename.as_str())));
+    group.bench_function("parse", |bench| {
+        bench.iter(|| vss_parse_rules(&locator).unwrap())
+    });
+    group.bench_function("index", |bench| bench.iter(|| VssIndex::new(&spec).len()));
+    group.bench_function("query", |bench| {
+        let index = VssIndex::new(&spec);
+        bench.iter(|| index.query("Vehicle.Group42.**").len())
+    });
+    group.bench_function("catalog-build", |bench| {
+        bench.iter(|| vss_catalog_build(&spec).len())
+    });
+    group.bench_function("catalog-open", |bench| {
+        let bytes = vss_catalog_build(&spec);
+        bench.iter(|| VssCatalog::new(&bytes).unwrap().len())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, vss_bench);
+criterion_main!(benches);
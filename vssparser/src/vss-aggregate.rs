@@ -0,0 +1,143 @@
+/*
+ * Copyright (C) 2015-2023 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
+ * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
+ *
+ * aggregate branches: leaves below a branch flagged 'aggregate: true' are one atomic group
+ *   - the group is exposed as a record type, fields are leaves named by their vpath relative
+ *     to the aggregate branch (Position, Backrest.Recline)
+ *   - a record carries one value per field and is checked (read or write) as a whole
+ */
+
+use std::collections::BTreeMap;
+
+use crate::index::*;
+use crate::types::*;
+use crate::validate::*;
+
+// composite value of an aggregate branch, values are keyed by field name
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VssRecord {
+    pub vpath: String,
+    pub values: BTreeMap<String, VssDataValue>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct VssAggregateField<'a> {
+    pub name: &'a str,
+    pub node: VssNode<'a>,
+}
+
+#[derive(Debug, Clone)]
+pub struct VssAggregate<'a> {
+    pub branch: &'a VssBranch,
+    // in vpath order
+    pub fields: Vec<VssAggregateField<'a>>,
+}
+
+impl<'a> VssAggregate<'a> {
+    // None when vpath is not an aggregate branch
+    pub fn new(index: &VssIndex<'a>, vpath: &str) -> Option<Self> {
+        let branch = match index.get(vpath)? {
+            VssNode::Branch(obj) if obj.aggregate => obj,
+            _ => return None,
+        };
+        let skip = branch.vpath.len() + 1;
+        let fields = index
+            .children(vpath)
+            .into_iter()
+            .filter(|node| node.datatype().is_some())
+            .map(|node| VssAggregateField {
+                name: &node.vpath()[skip..],
+                node,
+            })
+            .collect();
+        Some(VssAggregate { branch, fields })
+    }
+
+    pub fn vpath(&self) -> &'a str {
+        self.branch.vpath.as_str()
+    }
+
+    pub fn field(&self, name: &str) -> Option<VssNode<'a>> {
+        self.fields
+            .iter()
+            .find(|field| field.name == name)
+            .map(|field| field.node)
+    }
+
+    // record holding every field as NotAvailable
+    pub fn record(&self) -> VssRecord {
+        VssRecord {
+            vpath: self.vpath().to_string(),
+            values: self
+                .fields
+                .iter()
+                .map(|field| (field.name.to_string(), VssDataValue::NotAvailable))
+                .collect(),
+        }
+    }
+
+    fn check_with(
+        &self,
+        record: &VssRecord,
+        check: fn(&VssNode, &VssDataValue) -> Result<(), AfbError>,
+    ) -> Result<(), AfbError> {
+        if record.vpath != self.vpath() {
+            return Err(AfbError::new(
+                "vss-record-vpath",
+                format!(
+                    "record:{} does not match aggregate:{}",
+                    record.vpath,
+                    self.vpath()
+                ),
+            ));
+        }
+        for name in record.values.keys() {
+            if self.field(name).is_none() {
+                return Err(AfbError::new(
+                    "vss-record-field",
+                    format!("aggregate:{} has no field:{}", self.vpath(), name),
+                ));
+            }
+        }
+        // aggregate is atomic, every field is part of the record
+        for field in &self.fields {
+            match record.values.get(field.name) {
+                Some(value) => check(&field.node, value)?,
+                None => {
+                    return Err(AfbError::new(
+                        "vss-record-missing",
+                        format!(
+                            "aggregate:{} record misses field:{}",
+                            self.vpath(),
+                            field.name
+                        ),
+                    ))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // every field value is checked with vss_check_value
+    pub fn check_record(&self, record: &VssRecord) -> Result<(), AfbError> {
+        self.check_with(record, vss_check_value)
+    }
+
+    // record set by a client, every field has to be writable (vss_check_write)
+    pub fn check_write(&self, record: &VssRecord) -> Result<(), AfbError> {
+        self.check_with(record, vss_check_write)
+    }
+}
+
+// every aggregate branch in vpath order
+pub fn vss_aggregates<'a>(index: &VssIndex<'a>) -> Vec<VssAggregate<'a>> {
+    index
+        .iter()
+        .filter_map(|node| VssAggregate::new(index, node.vpath()))
+        .collect()
+}
@@ -0,0 +1,171 @@
+/*
+ * Copyright (C) 2015-2023 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
+ * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
+ *
+ * node provenance: every definition of a vpath (main spec then overlays) with its include chain,
+ * and for every field the definition that last set or modified it
+ *   - include chain comes from Filename.origin (location and prefix of each #include)
+ *   - definitions replaced by overlays or last-wins duplicates are kept within VssSpec.superseded
+ */
+
+use crate::diff::*;
+use crate::index::*;
+use crate::types::*;
+
+// one #include traversed to reach a file: #include line location, included file and prefix
+// as written on #include line
+#[derive(Debug, Clone, PartialEq)]
+pub struct VssIncludeStep {
+    pub location: VssLocation,
+    pub file: u32,
+    pub prefix: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VssDefinition {
+    pub location: VssLocation,
+    pub vtype: VssObjectType,
+    // overlay root file, None for main spec
+    pub overlay: Option<u32>,
+    // innermost #include first
+    pub chain: Vec<VssIncludeStep>,
+}
+
+// definition is an index within VssBlame.definitions
+#[derive(Debug, Clone, PartialEq)]
+pub struct VssBlameField {
+    pub field: &'static str,
+    pub value: Option<String>,
+    pub definition: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VssBlame {
+    pub vpath: String,
+    pub definitions: Vec<VssDefinition>,
+    pub fields: Vec<VssBlameField>,
+}
+
+// chain of #include from file up to its root file, plus root file index when it is an overlay
+pub fn vss_include_chain(files: &[Filename], file: u32) -> (Vec<VssIncludeStep>, Option<u32>) {
+    let mut chain = Vec::new();
+    let mut current = file;
+    loop {
+        match &files[current as usize].origin {
+            VssFileOrigin::Include { location, prefix } => {
+                chain.push(VssIncludeStep {
+                    location: *location,
+                    file: current,
+                    prefix: prefix.clone(),
+                });
+                current = location.file;
+            }
+            VssFileOrigin::Overlay => return (chain, Some(current)),
+            VssFileOrigin::Root => return (chain, None),
+        }
+    }
+}
+
+fn blame_definition(spec: &VssSpec, node: &VssNode) -> VssDefinition {
+    let location = node.location();
+    let (chain, overlay) = vss_include_chain(&spec.files, location.file);
+    VssDefinition {
+        location,
+        vtype: node.vtype().clone(),
+        overlay,
+        chain,
+    }
+}
+
+fn blame_path(spec: &VssSpec, location: VssLocation) -> String {
+    let filename = spec.filename(location);
+    format!(
+        "{}{}:{}",
+        filename.dirname, filename.basename, location.line
+    )
+}
+
+impl VssBlame {
+    pub fn new(spec: &VssSpec, vpath: &str) -> Option<Self> {
+        let index = VssIndex::new(spec);
+        let current = index.get(vpath)?;
+
+        // superseded definitions in replacement order, then the one in use
+        let mut nodes: Vec<VssNode> = spec
+            .superseded
+            .iter()
+            .map(VssNode::from)
+            .filter(|node| node.vpath() == vpath)
+            .collect();
+        nodes.push(current);
+
+        let mut fields: Vec<VssBlameField> = Vec::new();
+        for (idx, node) in nodes.iter().enumerate() {
+            for (slot, (field, value)) in diff_fields(node).into_iter().enumerate() {
+                match fields.get_mut(slot) {
+                    None => fields.push(VssBlameField {
+                        field,
+                        value,
+                        definition: idx,
+                    }),
+                    Some(blame) if blame.value != value => {
+                        blame.value = value;
+                        blame.definition = idx;
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+        // fields never set by any definition are skipped
+        fields.retain(|blame| blame.value.is_some() || blame.definition > 0);
+
+        Some(VssBlame {
+            vpath: vpath.to_string(),
+            definitions: nodes
+                .iter()
+                .map(|node| blame_definition(spec, node))
+                .collect(),
+            fields,
+        })
+    }
+
+    pub fn to_text(&self, spec: &VssSpec) -> String {
+        let mut out = format!("{}\ndefinitions:\n", self.vpath);
+        for (idx, definition) in self.definitions.iter().enumerate() {
+            out.push_str(&format!(
+                "  [{}] {} {}",
+                idx,
+                blame_path(spec, definition.location),
+                definition.vtype.to_str()
+            ));
+            if let Some(overlay) = definition.overlay {
+                let filename = &spec.files[overlay as usize];
+                out.push_str(&format!(
+                    " (overlay {}{})",
+                    filename.dirname, filename.basename
+                ));
+            }
+            out.push('\n');
+            for step in &definition.chain {
+                out.push_str(&format!(
+                    "      included from {} prefix:{}\n",
+                    blame_path(spec, step.location),
+                    step.prefix.as_deref().unwrap_or("(none)")
+                ));
+            }
+        }
+        out.push_str("fields:\n");
+        for blame in &self.fields {
+            out.push_str(&format!(
+                "  {}: {} [{}]\n",
+                blame.field,
+                blame.value.as_deref().unwrap_or("(none)"),
+                blame.definition
+            ));
+        }
+        out
+    }
+}
@@ -0,0 +1,637 @@
+/*
+ * Copyright (C) 2015-2023 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
+ * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
+ *
+ * binary catalog: resolved tree written on host, read in place on target (file may be mmap'ed)
+ * instances are expanded, target lookups use concrete vpaths (Vehicle.Cabin.Door.Row1.DriverSide.IsOpen)
+ *
+ * layout (little endian, no alignment requirement)
+ * - header (36 bytes): magic, version, header size, node count, node/data offsets, payload size, crc32,
+ *   sources hash (crc32 of source files content, see vss_catalog_sources)
+ * - node table: fixed size records sorted by vpath (binary search lookup, subtree is a contiguous range)
+ * - data: utf8 strings and lists of string references, referenced as (offset,len) from data start
+ */
+
+use std::fs;
+
+use uuid::Uuid;
+
+use crate::expand::*;
+use crate::index::*;
+use crate::types::*;
+use crate::units::*;
+
+pub const CATALOG_MAGIC: [u8; 4] = *b"VSSC";
+pub const CATALOG_VERSION: u16 = 2;
+
+const HEADER_SIZE: usize = 36;
+const NODE_SIZE: usize = 96;
+const NONE: u32 = u32::MAX;
+
+// node record fields offset
+const NODE_VPATH: usize = 0;
+const NODE_DESCRIPTION: usize = 8;
+const NODE_COMMENT: usize = 16;
+const NODE_UNIT: usize = 24;
+const NODE_ALLOWED: usize = 32;
+const NODE_DEFAULT: usize = 40;
+const NODE_MIN: usize = 48;
+const NODE_MAX: usize = 56;
+const NODE_UUID: usize = 64;
+const NODE_PARENT: usize = 80;
+const NODE_ARRAYSIZE: usize = 84;
+const NODE_VTYPE: usize = 88;
+const NODE_DATATYPE: usize = 89;
+const NODE_FLAGS: usize = 90;
+
+const FLAG_MIN: u8 = 0x01;
+const FLAG_MAX: u8 = 0x02;
+const FLAG_AGGREGATE: u8 = 0x04;
+
+// crc32 (ieee 802.3) lookup table
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut idx = 0;
+    while idx < 256 {
+        let mut crc = idx as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                0xEDB8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[idx] = crc;
+        idx += 1;
+    }
+    table
+};
+
+pub fn vss_crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in bytes {
+        crc = CRC_TABLE[((crc ^ *byte as u32) & 0xFF) as usize] ^ (crc >> 8);
+    }
+    !crc
+}
+
+// same node uuid as vss-tools: uuid5(uuid5(NAMESPACE_OID, "vehicle_signal_specification"), vpath)
+pub fn vss_uuid(vpath: &str) -> Uuid {
+    let namespace = Uuid::new_v5(&Uuid::NAMESPACE_OID, b"vehicle_signal_specification");
+    Uuid::new_v5(&namespace, vpath.as_bytes())
+}
+
+fn vtype_code(vtype: &VssObjectType) -> u8 {
+    match vtype {
+        VssObjectType::Branch => 0,
+        VssObjectType::Sensor => 1,
+        VssObjectType::Actuator => 2,
+        VssObjectType::Attribute => 3,
+        VssObjectType::Unset => 255,
+    }
+}
+
+fn vtype_from_code(code: u8) -> VssObjectType {
+    match code {
+        0 => VssObjectType::Branch,
+        1 => VssObjectType::Sensor,
+        2 => VssObjectType::Actuator,
+        3 => VssObjectType::Attribute,
+        _ => VssObjectType::Unset,
+    }
+}
+
+fn datatype_code(datatype: &VssValueType) -> u8 {
+    match datatype {
+        VssValueType::Unset => 0,
+        VssValueType::Uint8 => 1,
+        VssValueType::Int8 => 2,
+        VssValueType::Uint16 => 3,
+        VssValueType::Int16 => 4,
+        VssValueType::Uint32 => 5,
+        VssValueType::Int32 => 6,
+        VssValueType::Uint64 => 7,
+        VssValueType::Int64 => 8,
+        VssValueType::Boolean => 9,
+        VssValueType::Float => 10,
+        VssValueType::Double => 11,
+        VssValueType::String => 12,
+    }
+}
+
+fn datatype_from_code(code: u8) -> VssValueType {
+    match code {
+        1 => VssValueType::Uint8,
+        2 => VssValueType::Int8,
+        3 => VssValueType::Uint16,
+        4 => VssValueType::Int16,
+        5 => VssValueType::Uint32,
+        6 => VssValueType::Int32,
+        7 => VssValueType::Uint64,
+        8 => VssValueType::Int64,
+        9 => VssValueType::Boolean,
+        10 => VssValueType::Float,
+        11 => VssValueType::Double,
+        12 => VssValueType::String,
+        _ => VssValueType::Unset,
+    }
+}
+
+fn catalog_error(info: &str) -> AfbError {
+    AfbError::new("vss-catalog-invalid", info.to_string())
+}
+
+// host side: data section is filled while node records are written
+struct CatalogWriter {
+    nodes: Vec<u8>,
+    data: Vec<u8>,
+}
+
+impl CatalogWriter {
+    fn text(&mut self, value: Option<&str>) -> [u8; 8] {
+        let (offset, len) = match value {
+            None => (NONE, 0),
+            Some(value) => {
+                let offset = self.data.len() as u32;
+                self.data.extend_from_slice(value.as_bytes());
+                (offset, value.len() as u32)
+            }
+        };
+        let mut result = [0u8; 8];
+        result[0..4].copy_from_slice(&offset.to_le_bytes());
+        result[4..8].copy_from_slice(&len.to_le_bytes());
+        result
+    }
+
+    // list is an array of text references within data section
+    fn list(&mut self, values: &[String]) -> [u8; 8] {
+        let refs = values
+            .iter()
+            .map(|value| self.text(Some(value)))
+            .collect::<Vec<[u8; 8]>>();
+        let offset = self.data.len() as u32;
+        for value in refs {
+            self.data.extend_from_slice(&value);
+        }
+        let mut result = [0u8; 8];
+        result[0..4].copy_from_slice(&offset.to_le_bytes());
+        result[4..8].copy_from_slice(&(values.len() as u32).to_le_bytes());
+        result
+    }
+
+    fn node(&mut self, node: &VssNode, parent: u32) {
+        let mut record = [0u8; NODE_SIZE];
+        let (allowed, default, aggregate) = match node {
+            VssNode::Branch(obj) => (&[][..], &[][..], obj.aggregate),
+            VssNode::Sensor(obj) => (&obj.allowed[..], &obj.default[..], false),
+            VssNode::Actuator(obj) => (&obj.allowed[..], &obj.default[..], false),
+            VssNode::Attribute(obj) => (&obj.allowed[..], &obj.default[..], false),
+        };
+        let unit = match node.unit() {
+            Some(unit) if *unit != VssUnit::None => Some(unit.to_str()),
+            _ => None,
+        };
+
+        record[NODE_VPATH..NODE_VPATH + 8].copy_from_slice(&self.text(Some(node.vpath())));
+        record[NODE_DESCRIPTION..NODE_DESCRIPTION + 8]
+            .copy_from_slice(&self.text(node.description().map(|value| value.as_str())));
+        record[NODE_COMMENT..NODE_COMMENT + 8]
+            .copy_from_slice(&self.text(node.comment().map(|value| value.as_str())));
+        record[NODE_UNIT..NODE_UNIT + 8].copy_from_slice(&self.text(unit));
+        record[NODE_ALLOWED..NODE_ALLOWED + 8].copy_from_slice(&self.list(allowed));
+        record[NODE_DEFAULT..NODE_DEFAULT + 8].copy_from_slice(&self.list(default));
+        record[NODE_MIN..NODE_MIN + 8].copy_from_slice(&node.min().unwrap_or(0).to_le_bytes());
+        record[NODE_MAX..NODE_MAX + 8].copy_from_slice(&node.max().unwrap_or(0).to_le_bytes());
+        record[NODE_UUID..NODE_UUID + 16].copy_from_slice(vss_uuid(node.vpath()).as_bytes());
+        record[NODE_PARENT..NODE_PARENT + 4].copy_from_slice(&parent.to_le_bytes());
+        let arraysize = node.arraysize().map_or(NONE, |value| value as u32);
+        record[NODE_ARRAYSIZE..NODE_ARRAYSIZE + 4].copy_from_slice(&arraysize.to_le_bytes());
+        record[NODE_VTYPE] = vtype_code(node.vtype());
+        record[NODE_DATATYPE] = node.datatype().map_or(0, datatype_code);
+
+        let mut flags = 0;
+        if node.min().is_some() {
+            flags |= FLAG_MIN;
+        }
+        if node.max().is_some() {
+            flags |= FLAG_MAX;
+        }
+        if aggregate {
+            flags |= FLAG_AGGREGATE;
+        }
+        record[NODE_FLAGS] = flags;
+        self.nodes.extend_from_slice(&record);
+    }
+}
+
+// hash of every source file (name, vpath prefix and content) in loading order
+pub fn vss_catalog_sources(files: &[Filename]) -> u32 {
+    let mut bytes = Vec::new();
+    for file in files {
+        bytes.extend_from_slice(file.basename.as_bytes());
+        bytes.push(0);
+        bytes.extend_from_slice(file.prefix.as_deref().unwrap_or("").as_bytes());
+        bytes.push(0);
+        bytes.extend_from_slice(&file.checksum.to_le_bytes());
+    }
+    vss_crc32(&bytes)
+}
+
+// serialize the spec with expanded instances, nodes are sorted by vpath and parent links resolved
+pub fn vss_catalog_build(spec: &VssSpec) -> Vec<u8> {
+    let spec = &vss_expand_instances(spec);
+    let index = VssIndex::new(spec);
+    let nodes = index.iter().collect::<Vec<VssNode>>();
+    let mut writer = CatalogWriter {
+        nodes: Vec::with_capacity(nodes.len() * NODE_SIZE),
+        data: Vec::new(),
+    };
+
+    for node in &nodes {
+        let parent = match node.vpath().rsplit_once('.') {
+            None => NONE,
+            Some((parent, _)) => nodes
+                .binary_search_by(|node| node.vpath().cmp(parent))
+                .map_or(NONE, |idx| idx as u32),
+        };
+        writer.node(node, parent);
+    }
+
+    let payload = writer.nodes.len() + writer.data.len();
+    let mut buffer = Vec::with_capacity(HEADER_SIZE + payload);
+    buffer.extend_from_slice(&CATALOG_MAGIC);
+    buffer.extend_from_slice(&CATALOG_VERSION.to_le_bytes());
+    buffer.extend_from_slice(&(HEADER_SIZE as u16).to_le_bytes());
+    buffer.extend_from_slice(&(nodes.len() as u32).to_le_bytes());
+    buffer.extend_from_slice(&(HEADER_SIZE as u32).to_le_bytes());
+    buffer.extend_from_slice(&((HEADER_SIZE + writer.nodes.len()) as u32).to_le_bytes());
+    buffer.extend_from_slice(&(writer.data.len() as u32).to_le_bytes());
+    buffer.extend_from_slice(&(payload as u32).to_le_bytes());
+    buffer.extend_from_slice(&[0u8; 4]); // checksum
+    buffer.extend_from_slice(&vss_catalog_sources(&spec.files).to_le_bytes());
+    buffer.extend_from_slice(&writer.nodes);
+    buffer.extend_from_slice(&writer.data);
+
+    let checksum = vss_crc32(&buffer[HEADER_SIZE..]);
+    buffer[28..32].copy_from_slice(&checksum.to_le_bytes());
+    buffer
+}
+
+pub fn vss_catalog_to_file(spec: &VssSpec, filename: &str) -> Result<(), AfbError> {
+    match fs::write(filename, vss_catalog_build(spec)) {
+        Ok(()) => Ok(()),
+        Err(error) => Err(AfbError::new(
+            "vss-catalog-write",
+            format!("{} ({})", filename, error),
+        )),
+    }
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    let mut value = [0u8; 4];
+    value.copy_from_slice(&bytes[offset..offset + 4]);
+    u32::from_le_bytes(value)
+}
+
+fn read_i64(bytes: &[u8], offset: usize) -> i64 {
+    let mut value = [0u8; 8];
+    value.copy_from_slice(&bytes[offset..offset + 8]);
+    i64::from_le_bytes(value)
+}
+
+// target side: borrowed view on a catalog buffer, no allocation after validation
+#[derive(Clone, Copy)]
+pub struct VssCatalog<'a> {
+    nodes: &'a [u8],
+    data: &'a [u8],
+    count: usize,
+    checksum: u32,
+    sources: u32,
+}
+
+impl<'a> VssCatalog<'a> {
+    // check magic, version, bounds and checksum, then every string reference once
+    pub fn new(bytes: &'a [u8]) -> Result<Self, AfbError> {
+        if bytes.len() < HEADER_SIZE || bytes[0..4] != CATALOG_MAGIC {
+            return Err(catalog_error("not a vss catalog (invalid magic)"));
+        }
+        let version = read_u16(bytes, 4);
+        if version != CATALOG_VERSION {
+            return Err(AfbError::new(
+                "vss-catalog-version",
+                format!(
+                    "catalog version:{} not supported (expect:{})",
+                    version, CATALOG_VERSION
+                ),
+            ));
+        }
+        let header = read_u16(bytes, 6) as usize;
+        let count = read_u32(bytes, 8) as usize;
+        let nodes = read_u32(bytes, 12) as usize;
+        let data = read_u32(bytes, 16) as usize;
+        let data_len = read_u32(bytes, 20) as usize;
+        let payload = read_u32(bytes, 24) as usize;
+        let checksum = read_u32(bytes, 28);
+        let sources = read_u32(bytes, 32);
+
+        // header values are untrusted, sums never wrap (usize may be 32 bits)
+        let table = count
+            .checked_mul(NODE_SIZE)
+            .and_then(|size| nodes.checked_add(size));
+        if header != HEADER_SIZE
+            || header.checked_add(payload) != Some(bytes.len())
+            || nodes != header
+            || table != Some(data)
+            || data.checked_add(data_len) != Some(bytes.len())
+        {
+            return Err(catalog_error("truncated or inconsistent catalog"));
+        }
+        if vss_crc32(&bytes[header..]) != checksum {
+            return Err(AfbError::new(
+                "vss-catalog-checksum",
+                "catalog checksum mismatch (corrupted file)".to_string(),
+            ));
+        }
+
+        let catalog = VssCatalog {
+            nodes: &bytes[nodes..data],
+            data: &bytes[data..],
+            count,
+            checksum,
+            sources,
+        };
+        for idx in 0..count {
+            catalog.check_node(idx)?;
+        }
+        Ok(catalog)
+    }
+
+    // host side: same as new() but also refuse a catalog built from other sources
+    pub fn with_sources(bytes: &'a [u8], files: &[Filename]) -> Result<Self, AfbError> {
+        let catalog = VssCatalog::new(bytes)?;
+        catalog.check_sources(files)?;
+        Ok(catalog)
+    }
+
+    fn check_text(&self, reference: &[u8]) -> Result<(), AfbError> {
+        let offset = read_u32(reference, 0);
+        let len = read_u32(reference, 4) as usize;
+        if offset == NONE {
+            return Ok(());
+        }
+        let offset = offset as usize;
+        let text = offset
+            .checked_add(len)
+            .and_then(|end| self.data.get(offset..end));
+        match text {
+            Some(text) if std::str::from_utf8(text).is_ok() => Ok(()),
+            _ => Err(catalog_error("invalid string reference")),
+        }
+    }
+
+    fn check_node(&self, idx: usize) -> Result<(), AfbError> {
+        let record = &self.nodes[idx * NODE_SIZE..(idx + 1) * NODE_SIZE];
+        for field in [NODE_VPATH, NODE_DESCRIPTION, NODE_COMMENT, NODE_UNIT] {
+            self.check_text(&record[field..field + 8])?;
+        }
+        for field in [NODE_ALLOWED, NODE_DEFAULT] {
+            let offset = read_u32(record, field) as usize;
+            let count = read_u32(record, field + 4) as usize;
+            let list = count
+                .checked_mul(8)
+                .and_then(|size| offset.checked_add(size))
+                .and_then(|end| self.data.get(offset..end));
+            let list = match list {
+                Some(list) => list,
+                None => return Err(catalog_error("invalid list reference")),
+            };
+            for reference in list.chunks(8) {
+                self.check_text(reference)?;
+            }
+        }
+        let parent = read_u32(record, NODE_PARENT);
+        if parent != NONE && parent as usize >= self.count {
+            return Err(catalog_error("invalid parent reference"));
+        }
+        Ok(())
+    }
+
+    pub fn version(&self) -> u16 {
+        CATALOG_VERSION
+    }
+
+    // crc32 of catalog payload
+    pub fn checksum(&self) -> u32 {
+        self.checksum
+    }
+
+    // hash of source files the catalog was built from
+    pub fn sources(&self) -> u32 {
+        self.sources
+    }
+
+    // files come from a fresh load (VssSpec/VssData files), edited or renamed files are stale
+    pub fn check_sources(&self, files: &[Filename]) -> Result<(), AfbError> {
+        let sources = vss_catalog_sources(files);
+        if sources != self.sources {
+            return Err(AfbError::new(
+                "vss-catalog-stale",
+                format!(
+                    "catalog sources:{:08x} do not match spec sources:{:08x}",
+                    self.sources, sources
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    pub fn node(&self, idx: usize) -> Option<VssCatalogNode<'a>> {
+        if idx >= self.count {
+            return None;
+        }
+        Some(VssCatalogNode {
+            catalog: *self,
+            idx,
+        })
+    }
+
+    // nodes in vpath order
+    pub fn iter(&self) -> impl Iterator<Item = VssCatalogNode<'a>> + '_ {
+        (0..self.count).map(|idx| VssCatalogNode {
+            catalog: *self,
+            idx,
+        })
+    }
+
+    // exact vpath lookup (binary search)
+    pub fn get(&self, vpath: &str) -> Option<VssCatalogNode<'a>> {
+        let mut low = 0;
+        let mut high = self.count;
+        while low < high {
+            let middle = (low + high) / 2;
+            let node = VssCatalogNode {
+                catalog: *self,
+                idx: middle,
+            };
+            match node.vpath().cmp(vpath) {
+                std::cmp::Ordering::Equal => return Some(node),
+                std::cmp::Ordering::Less => low = middle + 1,
+                std::cmp::Ordering::Greater => high = middle,
+            }
+        }
+        None
+    }
+
+    fn text(&self, reference: &[u8]) -> Option<&'a str> {
+        let offset = read_u32(reference, 0);
+        if offset == NONE {
+            return None;
+        }
+        let len = read_u32(reference, 4) as usize;
+        let data: &'a [u8] = self.data;
+        // references were checked when catalog was opened
+        std::str::from_utf8(&data[offset as usize..offset as usize + len]).ok()
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct VssCatalogNode<'a> {
+    catalog: VssCatalog<'a>,
+    idx: usize,
+}
+
+impl<'a> VssCatalogNode<'a> {
+    fn record(&self) -> &'a [u8] {
+        let nodes: &'a [u8] = self.catalog.nodes;
+        &nodes[self.idx * NODE_SIZE..(self.idx + 1) * NODE_SIZE]
+    }
+
+    fn field(&self, offset: usize) -> Option<&'a str> {
+        self.catalog.text(&self.record()[offset..offset + 8])
+    }
+
+    fn list(&self, offset: usize) -> impl Iterator<Item = &'a str> + 'a {
+        let record = self.record();
+        let start = read_u32(record, offset) as usize;
+        let count = read_u32(record, offset + 4) as usize;
+        let catalog = self.catalog;
+        let data: &'a [u8] = catalog.data;
+        data[start..start + count * 8]
+            .chunks(8)
+            .filter_map(move |reference| catalog.text(reference))
+    }
+
+    pub fn index(&self) -> usize {
+        self.idx
+    }
+
+    pub fn vpath(&self) -> &'a str {
+        self.field(NODE_VPATH).unwrap_or("")
+    }
+
+    pub fn name(&self) -> &'a str {
+        let vpath = self.vpath();
+        vpath.rsplit('.').next().unwrap_or(vpath)
+    }
+
+    pub fn description(&self) -> Option<&'a str> {
+        self.field(NODE_DESCRIPTION)
+    }
+
+    pub fn comment(&self) -> Option<&'a str> {
+        self.field(NODE_COMMENT)
+    }
+
+    pub fn unit(&self) -> Option<&'a str> {
+        self.field(NODE_UNIT)
+    }
+
+    pub fn allowed(&self) -> impl Iterator<Item = &'a str> + 'a {
+        self.list(NODE_ALLOWED)
+    }
+
+    pub fn default(&self) -> impl Iterator<Item = &'a str> + 'a {
+        self.list(NODE_DEFAULT)
+    }
+
+    pub fn vtype(&self) -> VssObjectType {
+        vtype_from_code(self.record()[NODE_VTYPE])
+    }
+
+    pub fn access(&self) -> VssAccess {
+        self.vtype().access()
+    }
+
+    pub fn datatype(&self) -> Option<VssValueType> {
+        match self.vtype() {
+            VssObjectType::Branch => None,
+            _ => Some(datatype_from_code(self.record()[NODE_DATATYPE])),
+        }
+    }
+
+    pub fn arraysize(&self) -> Option<usize> {
+        match read_u32(self.record(), NODE_ARRAYSIZE) {
+            NONE => None,
+            value => Some(value as usize),
+        }
+    }
+
+    pub fn min(&self) -> Option<i64> {
+        match self.record()[NODE_FLAGS] & FLAG_MIN {
+            0 => None,
+            _ => Some(read_i64(self.record(), NODE_MIN)),
+        }
+    }
+
+    pub fn max(&self) -> Option<i64> {
+        match self.record()[NODE_FLAGS] & FLAG_MAX {
+            0 => None,
+            _ => Some(read_i64(self.record(), NODE_MAX)),
+        }
+    }
+
+    pub fn aggregate(&self) -> bool {
+        self.record()[NODE_FLAGS] & FLAG_AGGREGATE != 0
+    }
+
+    pub fn uuid(&self) -> Uuid {
+        let mut uuid = [0u8; 16];
+        uuid.copy_from_slice(&self.record()[NODE_UUID..NODE_UUID + 16]);
+        Uuid::from_bytes(uuid)
+    }
+
+    pub fn parent(&self) -> Option<VssCatalogNode<'a>> {
+        match read_u32(self.record(), NODE_PARENT) {
+            NONE => None,
+            idx => self.catalog.node(idx as usize),
+        }
+    }
+
+    // direct children, subtree is the contiguous range following the node
+    pub fn children(&self) -> impl Iterator<Item = VssCatalogNode<'a>> + 'a {
+        let catalog = self.catalog;
+        let parent = self.idx as u32;
+        let prefix = self.vpath();
+        (self.idx + 1..catalog.count)
+            .map(move |idx| VssCatalogNode { catalog, idx })
+            .take_while(move |node| node.vpath().starts_with(prefix))
+            .filter(move |node| read_u32(node.record(), NODE_PARENT) == parent)
+    }
+}
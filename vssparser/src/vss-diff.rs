@@ -0,0 +1,162 @@
+/*
+ * Copyright (C) 2015-2023 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
+ * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
+ *
+ * structural diff between two parsed specs, nodes are matched on vpath
+ *   - added/removed nodes are reported once, subtree nodes are not folded
+ *   - changed nodes report one entry per field (type, datatype, unit, min, max, ...)
+ */
+
+use std::fmt;
+
+use crate::index::*;
+use crate::types::*;
+use crate::units::*;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum VssChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+impl VssChangeKind {
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            VssChangeKind::Added => "added",
+            VssChangeKind::Removed => "removed",
+            VssChangeKind::Changed => "changed",
+        }
+    }
+}
+
+// field, old and new values are only set for changed nodes
+#[derive(Debug, Clone, PartialEq)]
+pub struct VssChange {
+    pub kind: VssChangeKind,
+    pub vpath: String,
+    pub field: Option<&'static str>,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+impl VssChange {
+    fn node(kind: VssChangeKind, vpath: &str) -> Self {
+        VssChange {
+            kind,
+            vpath: vpath.to_string(),
+            field: None,
+            old: None,
+            new: None,
+        }
+    }
+}
+
+// one line per change: "+ vpath", "- vpath" or "~ vpath field: old -> new"
+impl fmt::Display for VssChange {
+    fn fmt(&self, format: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            VssChangeKind::Added => write!(format, "+ {}", self.vpath),
+            VssChangeKind::Removed => write!(format, "- {}", self.vpath),
+            VssChangeKind::Changed => write!(
+                format,
+                "~ {} {}: {} -> {}",
+                self.vpath,
+                self.field.unwrap_or(""),
+                self.old.as_deref().unwrap_or("(none)"),
+                self.new.as_deref().unwrap_or("(none)"),
+            ),
+        }
+    }
+}
+
+fn list_field(values: &[String]) -> Option<String> {
+    if values.is_empty() {
+        None
+    } else {
+        Some(format!("[{}]", values.join(", ")))
+    }
+}
+
+// comparable fields of one node, unset fields are None
+pub(crate) fn diff_fields(node: &VssNode) -> Vec<(&'static str, Option<String>)> {
+    let (instances, aggregate) = match node {
+        VssNode::Branch(obj) => {
+            let instances = obj
+                .instances
+                .iter()
+                .map(|instance| {
+                    let prefix = instance.prefix.as_deref().unwrap_or("");
+                    format!("{}[{}]", prefix, instance.array.join(","))
+                })
+                .collect::<Vec<String>>();
+            (list_field(&instances), Some(obj.aggregate.to_string()))
+        }
+        _ => (None, None),
+    };
+    vec![
+        ("type", Some(node.vtype().to_str().to_string())),
+        (
+            "datatype",
+            node.datatype().map(|value| value.to_str().to_string()),
+        ),
+        ("arraysize", node.arraysize().map(|value| value.to_string())),
+        (
+            "unit",
+            node.unit()
+                .filter(|value| **value != VssUnit::None)
+                .map(|value| value.to_str().to_string()),
+        ),
+        ("min", node.min().map(|value| value.to_string())),
+        ("max", node.max().map(|value| value.to_string())),
+        ("allowed", list_field(node.allowed())),
+        ("default", list_field(node.default())),
+        ("instances", instances),
+        ("aggregate", aggregate),
+        (
+            "instantiate",
+            (!node.instantiate()).then(|| "false".to_string()),
+        ),
+        ("description", node.description().cloned()),
+        ("comment", node.comment().cloned()),
+        ("deprecation", node.deprecation().cloned()),
+        ("fka", list_field(node.fka())),
+    ]
+}
+
+// changes are sorted by vpath, a changed node keeps field order
+pub fn vss_diff(old: &VssSpec, new: &VssSpec) -> Vec<VssChange> {
+    let old_index = VssIndex::new(old);
+    let new_index = VssIndex::new(new);
+    let mut changes: Vec<VssChange> = Vec::new();
+
+    for node in old_index.iter() {
+        match new_index.get(node.vpath()) {
+            None => changes.push(VssChange::node(VssChangeKind::Removed, node.vpath())),
+            Some(other) => {
+                let fields = diff_fields(&node).into_iter().zip(diff_fields(&other));
+                for ((field, old), (_, new)) in fields {
+                    if old != new {
+                        changes.push(VssChange {
+                            kind: VssChangeKind::Changed,
+                            vpath: node.vpath().to_string(),
+                            field: Some(field),
+                            old,
+                            new,
+                        });
+                    }
+                }
+            }
+        }
+    }
+    for node in new_index.iter() {
+        if old_index.get(node.vpath()).is_none() {
+            changes.push(VssChange::node(VssChangeKind::Added, node.vpath()));
+        }
+    }
+    changes.sort_by(|a, b| a.vpath.cmp(&b.vpath));
+    changes
+}
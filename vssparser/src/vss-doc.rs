@@ -0,0 +1,348 @@
+/*
+ * Copyright (C) 2015-2023 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
+ * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
+ *
+ * documentation generator: branch tree + one section per node, as markdown or static html
+ *   - branch sections list their direct children, leaf sections hold a field/value table
+ *   - anchors follow vpath (Vehicle.Cabin.Door => #vehicle-cabin-door)
+ */
+
+use std::fs;
+use std::str::FromStr;
+
+use crate::index::*;
+use crate::types::*;
+use crate::units::*;
+
+const DOC_TITLE: &str = "Vehicle Signal Specification";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VssDocFormat {
+    Markdown,
+    Html,
+}
+
+impl FromStr for VssDocFormat {
+    type Err = AfbError;
+    fn from_str(value: &str) -> Result<Self, AfbError> {
+        match value.to_lowercase().as_str() {
+            "md" | "markdown" => Ok(VssDocFormat::Markdown),
+            "html" | "htm" => Ok(VssDocFormat::Html),
+            _ => Err(AfbError::new(
+                "vss-doc-format",
+                format!("unsupported doc format:{} (markdown|html)", value),
+            )),
+        }
+    }
+}
+
+fn doc_anchor(vpath: &str) -> String {
+    vpath.to_lowercase().replace('.', "-")
+}
+
+fn doc_name(vpath: &str) -> &str {
+    vpath.rsplit('.').next().unwrap_or(vpath)
+}
+
+// table cells hold one line, multi-line texts (block scalars) keep their breaks as <br>
+fn md_escape(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', "<br>")
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn doc_datatype(node: &VssNode) -> Option<String> {
+    let datatype = node.datatype()?;
+    match node.arraysize() {
+        None => Some(datatype.to_str().to_string()),
+        Some(0) => Some(format!("{}[]", datatype.to_str())),
+        Some(size) => Some(format!("{}[{}]", datatype.to_str(), size)),
+    }
+}
+
+// unit symbol followed by long label and description from unit registry
+fn doc_unit(node: &VssNode, units: &VssUnitPool) -> Option<String> {
+    let unit = node.unit()?;
+    if let VssUnit::None = unit {
+        return None;
+    }
+    match units.get(unit) {
+        Some(info) => Some(format!(
+            "{} ({}: {})",
+            unit.to_str(),
+            info.label,
+            info.description
+        )),
+        None => Some(unit.to_str().to_string()),
+    }
+}
+
+fn doc_range(node: &VssNode) -> Option<String> {
+    match (node.min(), node.max()) {
+        (Some(min), Some(max)) => Some(format!("{} .. {}", min, max)),
+        (Some(min), None) => Some(format!(">= {}", min)),
+        (None, Some(max)) => Some(format!("<= {}", max)),
+        (None, None) => None,
+    }
+}
+
+// field/value rows of one node, empty fields are skipped
+fn doc_rows(
+    spec: &VssSpec,
+    units: &VssUnitPool,
+    node: &VssNode,
+    locale: Option<&str>,
+) -> Vec<(&'static str, String)> {
+    let mut rows: Vec<(&'static str, String)> = Vec::new();
+    let vtype = match node.access() {
+        VssAccess::None => node.vtype().to_str().to_string(),
+        access => format!("{} ({})", node.vtype().to_str(), access.to_str()),
+    };
+    rows.push(("type", vtype));
+    let name = node.name_l10n(locale);
+    if name != doc_name(node.vpath()) {
+        rows.push(("name", name.to_string()));
+    }
+    if let Some(value) = doc_datatype(node) {
+        rows.push(("datatype", value));
+    }
+    if let Some(value) = doc_unit(node, units) {
+        rows.push(("unit", value));
+    }
+    if let Some(value) = doc_range(node) {
+        rows.push(("range", value));
+    }
+    if !node.allowed().is_empty() {
+        rows.push(("allowed", node.allowed().join(", ")));
+    }
+    if !node.default().is_empty() {
+        rows.push(("default", node.default().join(", ")));
+    }
+    if let VssNode::Branch(obj) = node {
+        if obj.aggregate {
+            rows.push(("aggregate", "true".to_string()));
+        }
+        let instances = obj
+            .instances
+            .iter()
+            .map(|instance| {
+                let prefix = instance.prefix.as_deref().unwrap_or("");
+                format!("{}[{}]", prefix, instance.array.join(","))
+            })
+            .collect::<Vec<String>>();
+        if !instances.is_empty() {
+            rows.push(("instances", instances.join(" ")));
+        }
+    }
+    if !node.instantiate() {
+        rows.push(("instantiate", "false".to_string()));
+    }
+    if let Some(value) = node.description_l10n(locale) {
+        rows.push(("description", value.to_string()));
+    }
+    if let Some(value) = node.comment_l10n(locale) {
+        rows.push(("comment", value.to_string()));
+    }
+    if let Some(value) = node.deprecation() {
+        rows.push(("deprecation", value.to_string()));
+    }
+    if !node.fka().is_empty() {
+        rows.push(("fka", node.fka().join(", ")));
+    }
+    let location = node.location();
+    let filename = spec.filename(location);
+    rows.push(("source", format!("{}:{}", filename.basename, location.line)));
+    rows
+}
+
+// branch tree (depth relative to its root, vpath)
+fn doc_tree<'a>(index: &VssIndex<'a>) -> Vec<(usize, &'a str)> {
+    let mut tree: Vec<(usize, &'a str)> = Vec::new();
+    let mut roots: Vec<&'a str> = Vec::new();
+    for node in index.iter() {
+        if let VssNode::Branch(_) = node {
+            let vpath = node.vpath();
+            while let Some(root) = roots.last() {
+                if vpath.starts_with(root) && vpath[root.len()..].starts_with('.') {
+                    break;
+                }
+                roots.pop();
+            }
+            tree.push((roots.len(), vpath));
+            roots.push(vpath);
+        }
+    }
+    tree
+}
+
+fn doc_markdown(
+    spec: &VssSpec,
+    units: &VssUnitPool,
+    index: &VssIndex,
+    locale: Option<&str>,
+) -> String {
+    let mut out = format!("# {}\n\n## Signal tree\n\n", DOC_TITLE);
+    for (depth, vpath) in doc_tree(index) {
+        out.push_str(&format!(
+            "{:indent$}- [{}](#{})\n",
+            "",
+            doc_name(vpath),
+            doc_anchor(vpath),
+            indent = depth * 2
+        ));
+    }
+
+    for node in index.iter() {
+        let vpath = node.vpath();
+        let level = match node {
+            VssNode::Branch(_) => "##",
+            _ => "###",
+        };
+        out.push_str(&format!(
+            "\n<a id=\"{}\"></a>\n\n{} {}\n\n",
+            doc_anchor(vpath),
+            level,
+            vpath
+        ));
+        out.push_str("| field | value |\n|---|---|\n");
+        for (label, value) in doc_rows(spec, units, &node, locale) {
+            out.push_str(&format!("| {} | {} |\n", label, md_escape(&value)));
+        }
+
+        let children = index.direct_children(vpath);
+        if !children.is_empty() {
+            out.push_str("\n| node | type | description |\n|---|---|---|\n");
+            for child in children {
+                out.push_str(&format!(
+                    "| [{}](#{}) | {} | {} |\n",
+                    doc_name(child.vpath()),
+                    doc_anchor(child.vpath()),
+                    child.vtype().to_str(),
+                    md_escape(child.description_l10n(locale).unwrap_or(""))
+                ));
+            }
+        }
+    }
+    out
+}
+
+fn doc_html(spec: &VssSpec, units: &VssUnitPool, index: &VssIndex, locale: Option<&str>) -> String {
+    let mut out = String::new();
+    out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    out.push_str(&format!("<title>{}</title>\n", DOC_TITLE));
+    out.push_str(concat!(
+        "<style>\n",
+        "body { font-family: sans-serif; margin: 2em; }\n",
+        "table { border-collapse: collapse; margin-bottom: 1em; }\n",
+        "td, th { border: 1px solid #ccc; padding: 0.2em 0.6em; text-align: left; }\n",
+        "</style>\n"
+    ));
+    out.push_str(&format!("</head>\n<body>\n<h1>{}</h1>\n<nav>\n", DOC_TITLE));
+
+    // nested lists follow branch depth, sub-lists stay within their parent item
+    let mut opened = 0;
+    for (depth, vpath) in doc_tree(index) {
+        if depth == opened {
+            out.push_str("<ul>\n");
+            opened += 1;
+        } else {
+            out.push_str("</li>\n");
+            while opened > depth + 1 {
+                out.push_str("</ul>\n</li>\n");
+                opened -= 1;
+            }
+        }
+        out.push_str(&format!(
+            "<li><a href=\"#{}\">{}</a>",
+            doc_anchor(vpath),
+            html_escape(doc_name(vpath))
+        ));
+    }
+    if opened > 0 {
+        out.push_str("</li>\n");
+        for _ in 1..opened {
+            out.push_str("</ul>\n</li>\n");
+        }
+        out.push_str("</ul>\n");
+    }
+    out.push_str("</nav>\n");
+
+    for node in index.iter() {
+        let vpath = node.vpath();
+        let level = match node {
+            VssNode::Branch(_) => "h2",
+            _ => "h3",
+        };
+        out.push_str(&format!(
+            "<section id=\"{}\">\n<{}>{}</{}>\n<table>\n",
+            doc_anchor(vpath),
+            level,
+            html_escape(vpath),
+            level
+        ));
+        for (label, value) in doc_rows(spec, units, &node, locale) {
+            out.push_str(&format!(
+                "<tr><th>{}</th><td>{}</td></tr>\n",
+                label,
+                html_escape(&value)
+            ));
+        }
+        out.push_str("</table>\n");
+
+        let children = index.direct_children(vpath);
+        if !children.is_empty() {
+            out.push_str("<table>\n<tr><th>node</th><th>type</th><th>description</th></tr>\n");
+            for child in children {
+                out.push_str(&format!(
+                    "<tr><td><a href=\"#{}\">{}</a></td><td>{}</td><td>{}</td></tr>\n",
+                    doc_anchor(child.vpath()),
+                    html_escape(doc_name(child.vpath())),
+                    child.vtype().to_str(),
+                    html_escape(child.description_l10n(locale).unwrap_or(""))
+                ));
+            }
+            out.push_str("</table>\n");
+        }
+        out.push_str("</section>\n");
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+// texts are selected within locale with fallback on english
+pub fn vss_doc_build(
+    spec: &VssSpec,
+    units: &VssUnitPool,
+    format: VssDocFormat,
+    locale: Option<&str>,
+) -> String {
+    let index = VssIndex::new(spec);
+    match format {
+        VssDocFormat::Markdown => doc_markdown(spec, units, &index, locale),
+        VssDocFormat::Html => doc_html(spec, units, &index, locale),
+    }
+}
+
+pub fn vss_doc_to_file(
+    spec: &VssSpec,
+    units: &VssUnitPool,
+    format: VssDocFormat,
+    locale: Option<&str>,
+    filename: &str,
+) -> Result<(), AfbError> {
+    match fs::write(filename, vss_doc_build(spec, units, format, locale)) {
+        Ok(()) => Ok(()),
+        Err(error) => Err(AfbError::new(
+            "vss-doc-write",
+            format!("{} ({})", filename, error),
+        )),
+    }
+}
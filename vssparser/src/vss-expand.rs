@@ -0,0 +1,158 @@
+/*
+ * Copyright (C) 2015-2023 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
+ * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
+ *
+ * instance expansion: nodes below an instance branch are repeated under every instance
+ *   Vehicle.Cabin.Door (instances: Row[1,2], ["Left","Right"]) + Vehicle.Cabin.Door.IsOpen
+ *   => Vehicle.Cabin.Door.Row1, Vehicle.Cabin.Door.Row1.Left, Vehicle.Cabin.Door.Row1.Left.IsOpen ...
+ *   - 'instantiate: false' nodes (and their children) stay once below the instance branch
+ */
+
+use std::collections::{HashMap, HashSet};
+
+use crate::index::*;
+use crate::types::*;
+
+// every path followed by every name of each level
+fn expand_levels(mut paths: Vec<String>, levels: &[Vec<String>]) -> Vec<String> {
+    for names in levels {
+        paths = paths
+            .iter()
+            .flat_map(|path| names.iter().map(move |name| format!("{}.{}", path, name)))
+            .collect();
+    }
+    paths
+}
+
+struct Expander<'a> {
+    levels: HashMap<&'a str, Vec<Vec<String>>>,
+    // vpaths flagged 'instantiate: false'
+    fixed: HashSet<&'a str>,
+}
+
+impl Expander<'_> {
+    // instances of every enclosing instance branch are inserted after it
+    fn vpaths(&self, vpath: &str) -> Vec<String> {
+        let segments = vpath.split('.').collect::<Vec<&str>>();
+        let mut paths = vec![String::new()];
+        for (idx, segment) in segments.iter().enumerate() {
+            for path in paths.iter_mut() {
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                path.push_str(segment);
+            }
+            if idx + 1 < segments.len() && !self.not_instantiated(&segments, idx) {
+                if let Some(levels) = self.levels.get(segments[..=idx].join(".").as_str()) {
+                    paths = expand_levels(paths, levels);
+                }
+            }
+        }
+        paths
+    }
+
+    // node or one of its parents below instance branch at depth idx is not instantiated
+    fn not_instantiated(&self, segments: &[&str], idx: usize) -> bool {
+        (idx + 1..segments.len())
+            .any(|last| self.fixed.contains(segments[..=last].join(".").as_str()))
+    }
+}
+
+// return a new spec where instances are expanded, instance branches lose their instances list
+pub fn vss_expand_instances(spec: &VssSpec) -> VssSpec {
+    let expander = Expander {
+        levels: spec
+            .branches
+            .iter()
+            .filter(|branch| !branch.instances.is_empty())
+            .map(|branch| {
+                let levels = branch.instances.iter().map(|value| value.names()).collect();
+                (branch.vpath.as_str(), levels)
+            })
+            .collect(),
+        fixed: VssIndex::new(spec)
+            .iter()
+            .filter(|node| !node.instantiate())
+            .map(|node| node.vpath())
+            .collect(),
+    };
+
+    let mut expanded = VssSpec {
+        diagnostics: spec.diagnostics.clone(),
+        files: spec.files.clone(),
+        trailing: spec.trailing.clone(),
+        ..Default::default()
+    };
+
+    for branch in &spec.branches {
+        for vpath in expander.vpaths(&branch.vpath) {
+            // intermediate instance branches inherit their parent description
+            let mut levels: Vec<String> = Vec::new();
+            for depth in 1..=branch.instances.len() {
+                levels.extend(expand_levels(
+                    vec![vpath.clone()],
+                    &expander.levels[branch.vpath.as_str()][..depth],
+                ));
+            }
+            let mut obj = branch.clone();
+            // former vpaths name one node, instantiated copies do not inherit them
+            if vpath != branch.vpath {
+                obj.fka = Vec::new();
+            }
+            obj.vpath = vpath;
+            obj.instances = Vec::new();
+            expanded.branches.push(obj);
+
+            for vpath in levels {
+                expanded.branches.push(VssBranch {
+                    vpath,
+                    vtype: VssObjectType::Branch,
+                    description: branch.description.clone(),
+                    comment: branch.comment.clone(),
+                    deprecation: None,
+                    fka: Vec::new(),
+                    instantiate: true,
+                    location: branch.location,
+                    instances: Vec::new(),
+                    aggregate: false,
+                    l10n: Vec::new(),
+                    trivia: None,
+                });
+            }
+        }
+    }
+    for sensor in &spec.sensors {
+        for vpath in expander.vpaths(&sensor.vpath) {
+            let mut obj = sensor.clone();
+            if vpath != sensor.vpath {
+                obj.fka = Vec::new();
+            }
+            obj.vpath = vpath;
+            expanded.sensors.push(obj);
+        }
+    }
+    for actuator in &spec.actuators {
+        for vpath in expander.vpaths(&actuator.vpath) {
+            let mut obj = actuator.clone();
+            if vpath != actuator.vpath {
+                obj.fka = Vec::new();
+            }
+            obj.vpath = vpath;
+            expanded.actuators.push(obj);
+        }
+    }
+    for attribute in &spec.attributes {
+        for vpath in expander.vpaths(&attribute.vpath) {
+            let mut obj = attribute.clone();
+            if vpath != attribute.vpath {
+                obj.fka = Vec::new();
+            }
+            obj.vpath = vpath;
+            expanded.attributes.push(obj);
+        }
+    }
+    expanded
+}
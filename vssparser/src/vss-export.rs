@@ -0,0 +1,499 @@
+/*
+ * Copyright (C) 2015-2023 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
+ * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
+ *
+ * spec exporters, output follows vss-tools layout where one exists
+ *   - json: nested tree, branches hold their nodes within "children"
+ *   - yaml: flat mapping keyed by vpath
+ *   - csv: one row per node (Signal,Type,DataType,Deprecated,Unit,Min,Max,Desc,Comment,Allowed,Default,Id)
+ *   - proto: one proto3 message per branch
+ */
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use crate::catalog::*;
+use crate::index::*;
+use crate::types::*;
+use crate::units::*;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VssExportFormat {
+    Json,
+    Yaml,
+    Csv,
+    Proto,
+}
+
+impl FromStr for VssExportFormat {
+    type Err = AfbError;
+    fn from_str(value: &str) -> Result<Self, AfbError> {
+        match value.to_lowercase().as_str() {
+            "json" => Ok(VssExportFormat::Json),
+            "yaml" | "yml" => Ok(VssExportFormat::Yaml),
+            "csv" => Ok(VssExportFormat::Csv),
+            "proto" | "protobuf" => Ok(VssExportFormat::Proto),
+            _ => Err(AfbError::new(
+                "vss-export-format",
+                format!("unsupported export format:{} (json|yaml|csv|proto)", value),
+            )),
+        }
+    }
+}
+
+fn export_name(vpath: &str) -> &str {
+    vpath.rsplit('.').next().unwrap_or(vpath)
+}
+
+fn export_datatype(node: &VssNode) -> Option<String> {
+    let datatype = node.datatype()?;
+    match node.arraysize() {
+        None => Some(datatype.to_str().to_string()),
+        Some(_) => Some(format!("{}[]", datatype.to_str())),
+    }
+}
+
+fn export_unit<'a>(node: &VssNode<'a>) -> Option<&'a str> {
+    match node.unit()? {
+        VssUnit::None => None,
+        unit => Some(unit.to_str()),
+    }
+}
+
+fn export_instances(node: &VssNode) -> Vec<Vec<String>> {
+    match node {
+        VssNode::Branch(obj) => obj.instances.iter().map(|value| value.names()).collect(),
+        _ => Vec::new(),
+    }
+}
+
+// numeric and boolean values stay bare when they parse as such, anything else is a string
+fn is_bare(node: &VssNode, value: &str) -> bool {
+    match node.datatype() {
+        Some(VssValueType::Boolean) => value == "true" || value == "false",
+        Some(datatype) if datatype.is_numeric() => value.parse::<f64>().is_ok(),
+        _ => false,
+    }
+}
+
+pub(crate) fn json_string(text: &str) -> String {
+    let mut out = String::from("\"");
+    for chr in text.chars() {
+        match chr {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            chr if (chr as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", chr as u32)),
+            chr => out.push(chr),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn json_value(node: &VssNode, value: &str) -> String {
+    if is_bare(node, value) {
+        value.to_string()
+    } else {
+        json_string(value)
+    }
+}
+
+fn json_values(node: &VssNode, values: &[String]) -> String {
+    let values = values
+        .iter()
+        .map(|value| json_value(node, value))
+        .collect::<Vec<String>>();
+    format!("[{}]", values.join(", "))
+}
+
+struct JsonWriter<'a, 'b> {
+    index: &'b VssIndex<'a>,
+    locale: Option<&'b str>,
+    out: String,
+}
+
+impl<'a> JsonWriter<'a, '_> {
+    fn node(&mut self, node: VssNode<'a>, indent: usize) {
+        let pad = " ".repeat(indent + 2);
+        let mut fields: Vec<(&str, String)> = Vec::new();
+        fields.push(("type", json_string(node.vtype().to_str())));
+        if let Some(value) = export_datatype(&node) {
+            fields.push(("datatype", json_string(&value)));
+        }
+        if let Some(size) = node.arraysize() {
+            if size > 0 {
+                fields.push(("arraysize", size.to_string()));
+            }
+        }
+        if let Some(value) = export_unit(&node) {
+            fields.push(("unit", json_string(value)));
+        }
+        if let Some(value) = node.min() {
+            fields.push(("min", value.to_string()));
+        }
+        if let Some(value) = node.max() {
+            fields.push(("max", value.to_string()));
+        }
+        if !node.allowed().is_empty() {
+            fields.push(("allowed", json_values(&node, node.allowed())));
+        }
+        match node.default() {
+            [] => {}
+            [value] if node.arraysize().is_none() => {
+                fields.push(("default", json_value(&node, value)))
+            }
+            values => fields.push(("default", json_values(&node, values))),
+        }
+        let instances = export_instances(&node);
+        if !instances.is_empty() {
+            let levels = instances
+                .iter()
+                .map(|names| {
+                    let names = names
+                        .iter()
+                        .map(|name| json_string(name))
+                        .collect::<Vec<_>>();
+                    format!("[{}]", names.join(", "))
+                })
+                .collect::<Vec<String>>();
+            fields.push(("instances", format!("[{}]", levels.join(", "))));
+        }
+        if let VssNode::Branch(obj) = node {
+            if obj.aggregate {
+                fields.push(("aggregate", "true".to_string()));
+            }
+        }
+        if !node.instantiate() {
+            fields.push(("instantiate", "false".to_string()));
+        }
+        if let Some(value) = node.description_l10n(self.locale) {
+            fields.push(("description", json_string(value)));
+        }
+        if let Some(value) = node.comment_l10n(self.locale) {
+            fields.push(("comment", json_string(value)));
+        }
+        if let Some(value) = node.deprecation() {
+            fields.push(("deprecation", json_string(value)));
+        }
+        if !node.fka().is_empty() {
+            let values = node
+                .fka()
+                .iter()
+                .map(|value| json_string(value))
+                .collect::<Vec<_>>();
+            fields.push(("fka", format!("[{}]", values.join(", "))));
+        }
+
+        self.out.push_str("{\n");
+        for (idx, (key, value)) in fields.iter().enumerate() {
+            self.out.push_str(&format!("{}\"{}\": {}", pad, key, value));
+            if idx + 1 < fields.len() {
+                self.out.push(',');
+            }
+            self.out.push('\n');
+        }
+        if let VssNode::Branch(_) = node {
+            self.out.pop();
+            self.out.push_str(&format!(",\n{}\"children\": ", pad));
+            let children = self.index.direct_children(node.vpath());
+            self.children(&children, indent + 2);
+            self.out.push('\n');
+        }
+        self.out.push_str(&format!("{}}}", " ".repeat(indent)));
+    }
+
+    fn children(&mut self, nodes: &[VssNode<'a>], indent: usize) {
+        if nodes.is_empty() {
+            self.out.push_str("{}");
+            return;
+        }
+        let pad = " ".repeat(indent + 2);
+        self.out.push_str("{\n");
+        for (idx, node) in nodes.iter().enumerate() {
+            self.out.push_str(&format!(
+                "{}{}: ",
+                pad,
+                json_string(export_name(node.vpath()))
+            ));
+            self.node(*node, indent + 2);
+            if idx + 1 < nodes.len() {
+                self.out.push(',');
+            }
+            self.out.push('\n');
+        }
+        self.out.push_str(&format!("{}}}", " ".repeat(indent)));
+    }
+}
+
+// top level nodes have no parent within index, they keep their full vpath as key
+fn export_json(index: &VssIndex, locale: Option<&str>) -> String {
+    let roots: Vec<VssNode> = index
+        .iter()
+        .filter(|node| match node.vpath().rsplit_once('.') {
+            Some((parent, _)) => index.get(parent).is_none(),
+            None => true,
+        })
+        .collect();
+    let mut writer = JsonWriter {
+        index,
+        locale,
+        out: String::new(),
+    };
+    if roots.is_empty() {
+        writer.out.push_str("{}");
+    } else {
+        writer.out.push_str("{\n");
+        for (idx, node) in roots.iter().enumerate() {
+            writer
+                .out
+                .push_str(&format!("  {}: ", json_string(node.vpath())));
+            writer.node(*node, 2);
+            if idx + 1 < roots.len() {
+                writer.out.push(',');
+            }
+            writer.out.push('\n');
+        }
+        writer.out.push('}');
+    }
+    writer.out.push('\n');
+    writer.out
+}
+
+fn yaml_string(text: &str) -> String {
+    format!(
+        "\"{}\"",
+        text.replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+            .replace('\t', "\\t")
+    )
+}
+
+fn yaml_value(node: &VssNode, value: &str) -> String {
+    if is_bare(node, value) {
+        value.to_string()
+    } else {
+        yaml_string(value)
+    }
+}
+
+fn yaml_values(node: &VssNode, values: &[String]) -> String {
+    let values = values
+        .iter()
+        .map(|value| yaml_value(node, value))
+        .collect::<Vec<String>>();
+    format!("[{}]", values.join(", "))
+}
+
+fn export_yaml(index: &VssIndex, locale: Option<&str>) -> String {
+    let mut out = String::new();
+    for node in index.iter() {
+        out.push_str(&format!("{}:\n", node.vpath()));
+        out.push_str(&format!("  type: {}\n", node.vtype().to_str()));
+        if let Some(value) = export_datatype(&node) {
+            out.push_str(&format!("  datatype: {}\n", value));
+        }
+        if let Some(size) = node.arraysize() {
+            if size > 0 {
+                out.push_str(&format!("  arraysize: {}\n", size));
+            }
+        }
+        if let Some(value) = export_unit(&node) {
+            out.push_str(&format!("  unit: {}\n", value));
+        }
+        if let Some(value) = node.min() {
+            out.push_str(&format!("  min: {}\n", value));
+        }
+        if let Some(value) = node.max() {
+            out.push_str(&format!("  max: {}\n", value));
+        }
+        if !node.allowed().is_empty() {
+            out.push_str(&format!(
+                "  allowed: {}\n",
+                yaml_values(&node, node.allowed())
+            ));
+        }
+        match node.default() {
+            [] => {}
+            [value] if node.arraysize().is_none() => {
+                out.push_str(&format!("  default: {}\n", yaml_value(&node, value)))
+            }
+            values => out.push_str(&format!("  default: {}\n", yaml_values(&node, values))),
+        }
+        let instances = export_instances(&node);
+        if !instances.is_empty() {
+            out.push_str("  instances:\n");
+            for names in instances {
+                let names = names
+                    .iter()
+                    .map(|name| yaml_string(name))
+                    .collect::<Vec<_>>();
+                out.push_str(&format!("  - [{}]\n", names.join(", ")));
+            }
+        }
+        if let VssNode::Branch(obj) = node {
+            if obj.aggregate {
+                out.push_str("  aggregate: true\n");
+            }
+        }
+        if !node.instantiate() {
+            out.push_str("  instantiate: false\n");
+        }
+        if let Some(value) = node.description_l10n(locale) {
+            out.push_str(&format!("  description: {}\n", yaml_string(value)));
+        }
+        if let Some(value) = node.comment_l10n(locale) {
+            out.push_str(&format!("  comment: {}\n", yaml_string(value)));
+        }
+        if let Some(value) = node.deprecation() {
+            out.push_str(&format!("  deprecation: {}\n", yaml_string(value)));
+        }
+        if !node.fka().is_empty() {
+            let values = node
+                .fka()
+                .iter()
+                .map(|value| yaml_string(value))
+                .collect::<Vec<_>>();
+            out.push_str(&format!("  fka: [{}]\n", values.join(", ")));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+// every field is quoted, embedded quotes are doubled
+fn csv_field(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "\"\""))
+}
+
+fn export_csv(index: &VssIndex, locale: Option<&str>) -> String {
+    let header = [
+        "Signal",
+        "Type",
+        "DataType",
+        "Deprecated",
+        "Unit",
+        "Min",
+        "Max",
+        "Desc",
+        "Comment",
+        "Allowed",
+        "Default",
+        "Id",
+    ];
+    let mut out = header
+        .iter()
+        .map(|label| csv_field(label))
+        .collect::<Vec<String>>()
+        .join(",");
+    out.push('\n');
+    for node in index.iter() {
+        let row = [
+            node.vpath().to_string(),
+            node.vtype().to_str().to_string(),
+            export_datatype(&node).unwrap_or_default(),
+            node.deprecation().cloned().unwrap_or_default(),
+            export_unit(&node).unwrap_or("").to_string(),
+            node.min()
+                .map(|value| value.to_string())
+                .unwrap_or_default(),
+            node.max()
+                .map(|value| value.to_string())
+                .unwrap_or_default(),
+            node.description_l10n(locale).unwrap_or("").to_string(),
+            node.comment_l10n(locale).unwrap_or("").to_string(),
+            node.allowed().join(", "),
+            node.default().join(", "),
+            vss_uuid(node.vpath()).simple().to_string(),
+        ];
+        out.push_str(
+            &row.iter()
+                .map(|value| csv_field(value))
+                .collect::<Vec<String>>()
+                .join(","),
+        );
+        out.push('\n');
+    }
+    out
+}
+
+fn proto_message(vpath: &str) -> String {
+    vpath.replace('.', "")
+}
+
+fn proto_type(datatype: &VssValueType) -> &'static str {
+    match datatype {
+        VssValueType::Uint8 | VssValueType::Uint16 | VssValueType::Uint32 => "uint32",
+        VssValueType::Int8 | VssValueType::Int16 | VssValueType::Int32 => "int32",
+        VssValueType::Uint64 => "uint64",
+        VssValueType::Int64 => "int64",
+        VssValueType::Boolean => "bool",
+        VssValueType::Float => "float",
+        VssValueType::Double => "double",
+        VssValueType::String | VssValueType::Unset => "string",
+    }
+}
+
+// nodes are grouped by parent vpath, every branch owns a message even when empty
+fn export_proto(index: &VssIndex, locale: Option<&str>) -> String {
+    let mut messages: BTreeMap<&str, Vec<VssNode>> = BTreeMap::new();
+    for node in index.iter() {
+        if let VssNode::Branch(_) = node {
+            messages.entry(node.vpath()).or_default();
+        }
+        if let Some((parent, _)) = node.vpath().rsplit_once('.') {
+            messages.entry(parent).or_default().push(node);
+        }
+    }
+
+    let mut out = String::from("syntax = \"proto3\";\n");
+    for (vpath, nodes) in messages {
+        out.push('\n');
+        if let Some(VssNode::Branch(obj)) = index.get(vpath) {
+            if obj.aggregate {
+                out.push_str("// aggregate: fields are read and written as one record\n");
+            }
+        }
+        out.push_str(&format!("message {} {{\n", proto_message(vpath)));
+        for (idx, node) in nodes.iter().enumerate() {
+            if let Some(value) = node.description_l10n(locale) {
+                out.push_str(&format!("  // {}\n", value.replace('\n', " ")));
+            }
+            let ptype = match node.datatype() {
+                Some(datatype) => {
+                    let ptype = proto_type(datatype).to_string();
+                    match node.arraysize() {
+                        Some(_) => format!("repeated {}", ptype),
+                        None => ptype,
+                    }
+                }
+                None => proto_message(node.vpath()),
+            };
+            out.push_str(&format!(
+                "  {} {} = {};\n",
+                ptype,
+                export_name(node.vpath()),
+                idx + 1
+            ));
+        }
+        out.push_str("}\n");
+    }
+    out
+}
+
+// texts are selected within locale with fallback on english
+pub fn vss_export_build(spec: &VssSpec, format: VssExportFormat, locale: Option<&str>) -> String {
+    let index = VssIndex::new(spec);
+    match format {
+        VssExportFormat::Json => export_json(&index, locale),
+        VssExportFormat::Yaml => export_yaml(&index, locale),
+        VssExportFormat::Csv => export_csv(&index, locale),
+        VssExportFormat::Proto => export_proto(&index, locale),
+    }
+}
@@ -0,0 +1,548 @@
+/*
+ * Copyright (C) 2015-2023 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
+ * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
+ *
+ * vspec formatter: rewrite one vspec file in canonical style, comments and #include lines kept
+ *   - lines are classified with parser's line grammar, comments and blank lines are kept as trivia
+ *   - one blank line between nodes, 2-space indent, fixed key order
+ *   - allowed/default/instances values requoted, flow list when it fits width, block otherwise
+ *   - long description/comment wrapped at value column (parser joins them back with a space),
+ *     block scalars (| or >) are kept as is
+ */
+
+use std::fs;
+use std::mem;
+
+use crate::parser::*;
+use crate::scalar::*;
+use crate::types::*;
+use crate::utils::*;
+
+// canonical key order, localized keys (ex: description.fr) follow their base key
+const FMT_KEY_ORDER: [&str; 16] = [
+    "type",
+    "datatype",
+    "arraysize",
+    "unit",
+    "min",
+    "max",
+    "allowed",
+    "default",
+    "instances",
+    "aggregate",
+    "instantiate",
+    "deprecation",
+    "fka",
+    "description",
+    "comment",
+    "name",
+];
+
+const FMT_INDENT: &str = "  ";
+
+pub struct VssFormatOptions {
+    pub width: usize,
+    pub quote: char,
+}
+
+impl Default for VssFormatOptions {
+    fn default() -> Self {
+        VssFormatOptions {
+            width: 100,
+            quote: '\'',
+        }
+    }
+}
+
+struct FmtEntry<'a> {
+    comments: Vec<&'a str>,
+    key: &'a str,
+    value: &'a str,
+    lines: Vec<(usize, &'a str)>,
+}
+
+// trivia: blank lines and comments preceding label, comments closing node block (inner)
+struct FmtNode<'a> {
+    label: &'a str,
+    indent: usize,
+    entries: Vec<FmtEntry<'a>>,
+    trivia: VssTrivia,
+}
+
+// a blank line following comments starts a new trivia item, comments keep their spacing
+enum FmtItem<'a> {
+    Trivia(VssTrivia),
+    Include(&'a str),
+    Node(FmtNode<'a>),
+    Other(&'a str),
+}
+
+// source line classified with parser's line grammar, raw text is kept for re-indentation
+struct FmtLine<'a> {
+    raw: &'a str,
+    kind: VssType<'a>,
+}
+
+// text is kept raw, quote is the one found within source (None for plain scalars)
+struct FmtValue<'a> {
+    text: &'a str,
+    quote: Option<char>,
+}
+
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+// key: value, key may be localized (description.fr)
+fn entry_key(text: &str) -> Option<(&str, &str)> {
+    let (key, value) = text.split_once(':')?;
+    let key = key.trim_end();
+    let valid = key
+        .chars()
+        .all(|chr| chr.is_alphanumeric() || chr == '.' || chr == '_' || chr == '-');
+    if key.is_empty() || !valid || !(value.is_empty() || value.starts_with(' ')) {
+        return None;
+    }
+    Some((key, value.trim()))
+}
+
+fn key_rank(key: &str) -> (usize, bool) {
+    let (base, localized) = match key.split_once('.') {
+        Some((base, _)) => (base, true),
+        None => (key, false),
+    };
+    match FMT_KEY_ORDER.iter().position(|value| *value == base) {
+        Some(idx) => (idx, localized),
+        None => (FMT_KEY_ORDER.len(), false),
+    }
+}
+
+fn scan_lines(source: &str) -> Vec<FmtLine<'_>> {
+    let mut lines = Vec::new();
+    let mut input = source;
+    while !input.is_empty() {
+        // any text is a data line, get_line only fails on empty input
+        let (next, kind) = match get_line(input) {
+            Ok(result) => result,
+            Err(_) => break,
+        };
+        let raw = input[..input.len() - next.len()].trim_end_matches('\n');
+        lines.push(FmtLine { raw, kind });
+        input = next;
+    }
+    lines
+}
+
+// whitespace only lines are blank lines, as for the loader
+fn is_blank(line: &FmtLine) -> bool {
+    match line.kind {
+        VssType::Empty() | VssType::Eof() => true,
+        VssType::Data(text) => text.trim().is_empty(),
+        _ => false,
+    }
+}
+
+// node block ends on first non indented line, inner blank lines are dropped
+fn parse_node<'a>(lines: &[FmtLine<'a>], label: &'a str, mut idx: usize) -> (FmtNode<'a>, usize) {
+    let mut node = FmtNode {
+        label,
+        indent: 0,
+        entries: Vec::new(),
+        trivia: VssTrivia::default(),
+    };
+    let mut comments: Vec<&'a str> = Vec::new();
+
+    while idx < lines.len() {
+        let line = &lines[idx];
+        let include = matches!(line.kind, VssType::Include(_));
+        if is_blank(line) {
+            let next = lines[idx..].iter().find(|line| !is_blank(line));
+            match next {
+                Some(next)
+                    if indent_of(next.raw) > 0 && !matches!(next.kind, VssType::Include(_)) =>
+                {
+                    idx += 1;
+                    continue;
+                }
+                _ => break,
+            }
+        }
+        let indent = indent_of(line.raw);
+        if indent == 0 || include {
+            break;
+        }
+        idx += 1;
+
+        let text = line.raw.trim();
+        if let VssType::Comment(_) = line.kind {
+            comments.push(text);
+            continue;
+        }
+        if node.indent == 0 {
+            node.indent = indent;
+        }
+        match entry_key(text) {
+            Some((key, value)) if indent == node.indent => node.entries.push(FmtEntry {
+                comments: mem::take(&mut comments),
+                key,
+                value,
+                lines: Vec::new(),
+            }),
+            _ => match node.entries.last_mut() {
+                Some(entry) => entry.lines.push((indent, text)),
+                None => node.entries.push(FmtEntry {
+                    comments: mem::take(&mut comments),
+                    key: "",
+                    value: text,
+                    lines: Vec::new(),
+                }),
+            },
+        }
+    }
+    node.trivia.inner = comments.iter().map(|text| text.to_string()).collect();
+    (node, idx)
+}
+
+fn parse_items(source: &str) -> Vec<FmtItem<'_>> {
+    let lines = scan_lines(source);
+    let mut items: Vec<FmtItem> = Vec::new();
+    let mut leading = VssTrivia::default();
+    let mut idx = 0;
+
+    while idx < lines.len() {
+        let line = &lines[idx];
+        idx += 1;
+        if is_blank(line) {
+            if !leading.comments.is_empty() {
+                items.push(FmtItem::Trivia(mem::take(&mut leading)));
+            }
+            leading.blank_lines += 1;
+            continue;
+        }
+        if let VssType::Comment(_) = line.kind {
+            leading.comments.push(line.raw.trim().to_string());
+            continue;
+        }
+        let label = match line.kind {
+            VssType::Data(text) if indent_of(text) == 0 => vss_node_label(text),
+            _ => None,
+        };
+        if let Some(label) = label {
+            let (mut node, next) = parse_node(&lines, label, idx);
+            node.trivia.blank_lines = leading.blank_lines;
+            node.trivia.comments = mem::take(&mut leading).comments;
+            items.push(FmtItem::Node(node));
+            idx = next;
+            continue;
+        }
+        if !leading.is_empty() {
+            items.push(FmtItem::Trivia(mem::take(&mut leading)));
+        }
+        match line.kind {
+            VssType::Include(_) => items.push(FmtItem::Include(line.raw.trim_end())),
+            _ => items.push(FmtItem::Other(line.raw.trim_end())),
+        }
+    }
+    if !leading.is_empty() {
+        items.push(FmtItem::Trivia(leading));
+    }
+    items
+}
+
+// entry value with its continuation lines as one single text
+fn joined_value(entry: &FmtEntry) -> String {
+    let mut parts: Vec<&str> = Vec::new();
+    if !entry.value.is_empty() {
+        parts.push(entry.value);
+    }
+    for (_, text) in &entry.lines {
+        parts.push(text);
+    }
+    parts.join(" ")
+}
+
+// '\' is an escape within double quotes only, such raw text keeps its original quote
+fn quote_value(text: &str, original: char, quote: char) -> String {
+    let quote = match (text.contains('\\'), text.contains(quote)) {
+        (true, _) => original,
+        (false, true) if quote == '\'' => '"',
+        (false, true) => '\'',
+        (false, false) => quote,
+    };
+    format!("{}{}{}", quote, text, quote)
+}
+
+fn one_value(text: &str) -> Option<FmtValue<'_>> {
+    let text = text.trim();
+    for quote in ['"', '\''] {
+        if let Some(inner) = text.strip_prefix(quote) {
+            let inner = inner.strip_suffix(quote)?;
+            if inner.contains(quote) {
+                return None;
+            }
+            return Some(FmtValue {
+                text: inner,
+                quote: Some(quote),
+            });
+        }
+    }
+    let invalid = ['"', '\'', '[', ']', ',', '#'];
+    if text.is_empty() || text.contains(invalid) {
+        return None;
+    }
+    Some(FmtValue { text, quote: None })
+}
+
+// [value1, 'value2', "value3"] with ',' within quotes allowed
+fn flow_values(text: &str) -> Option<Vec<FmtValue<'_>>> {
+    let inner = text.trim().strip_prefix('[')?.strip_suffix(']')?;
+    let mut items = flow_split(inner, ',')?;
+    // trailing comma is accepted
+    if items.last().is_some_and(|item| item.trim().is_empty()) {
+        items.pop();
+    }
+    items.into_iter().map(one_value).collect()
+}
+
+fn format_values(values: &[FmtValue], quote: char) -> Vec<String> {
+    values
+        .iter()
+        .map(|value| match value.quote {
+            Some(original) => quote_value(value.text, original, quote),
+            None => value.text.to_string(),
+        })
+        .collect()
+}
+
+// Prefix[val1,...valn] or [val1,...valn]
+fn format_instance(text: &str, quote: char) -> Option<String> {
+    let text = text.trim();
+    let split = text.find('[')?;
+    let prefix = text[..split].trim();
+    if !prefix.chars().all(|chr| chr.is_alphanumeric()) {
+        return None;
+    }
+    let values = flow_values(&text[split..])?;
+    Some(format!(
+        "{}[{}]",
+        prefix,
+        format_values(&values, quote).join(", ")
+    ))
+}
+
+// keep original lines, only re-indent them relatively to canonical indent
+fn push_raw(out: &mut String, entry: &FmtEntry, indent: usize) {
+    out.push_str(FMT_INDENT);
+    match entry.key.is_empty() {
+        true => out.push_str(entry.value),
+        false if entry.value.is_empty() => out.push_str(&format!("{}:", entry.key)),
+        false => out.push_str(&format!("{}: {}", entry.key, entry.value)),
+    }
+    out.push('\n');
+    for (idt, text) in &entry.lines {
+        let spaces = FMT_INDENT.len() + idt.saturating_sub(indent);
+        out.push_str(&format!("{:spaces$}{}\n", "", text, spaces = spaces));
+    }
+}
+
+// wrap words at width (in chars), continuation lines are aligned on value column
+fn push_text(out: &mut String, key: &str, text: &str, width: usize) {
+    let mut line = format!("{}{}:", FMT_INDENT, key);
+    let column = line.len() + 1;
+    let mut first = true;
+    for word in text.split_whitespace() {
+        let fits = line.chars().count() + 1 + word.chars().count() <= width;
+        // a continuation line starting with '#' would be taken as a comment
+        if !first && !fits && !word.starts_with('#') && line.len() > column {
+            out.push_str(&line);
+            out.push('\n');
+            line = format!("{:column$}{}", "", word, column = column);
+        } else {
+            line.push(' ');
+            line.push_str(word);
+        }
+        first = false;
+    }
+    out.push_str(&line);
+    out.push('\n');
+}
+
+fn push_list(out: &mut String, key: &str, values: &[String], width: usize) {
+    let line = format!("{}{}: [{}]", FMT_INDENT, key, values.join(", "));
+    if line.chars().count() <= width || values.len() < 2 {
+        out.push_str(&line);
+        out.push('\n');
+        return;
+    }
+    out.push_str(&format!("{}{}: [\n", FMT_INDENT, key));
+    for (idx, value) in values.iter().enumerate() {
+        let sep = if idx + 1 < values.len() { "," } else { "" };
+        out.push_str(&format!("{}{}{}{}\n", FMT_INDENT, FMT_INDENT, value, sep));
+    }
+    out.push_str(&format!("{}]\n", FMT_INDENT));
+}
+
+fn push_entry(out: &mut String, entry: &FmtEntry, indent: usize, options: &VssFormatOptions) {
+    for comment in &entry.comments {
+        out.push_str(&format!("{}{}\n", FMT_INDENT, comment));
+    }
+    let base = entry.key.split('.').next().unwrap_or(entry.key);
+    match base {
+        // block scalars (| or >) keep their line breaks
+        "description" | "comment" | "name" | "deprecation"
+            if entry.value.starts_with(['|', '>']) =>
+        {
+            push_raw(out, entry, indent)
+        }
+        "description" | "comment" | "name" | "deprecation" => {
+            push_text(out, entry.key, &joined_value(entry), options.width)
+        }
+        "allowed" | "default" => {
+            let text = joined_value(entry);
+            let values = match text.starts_with('[') {
+                true => flow_values(&text),
+                false => one_value(&text).map(|value| vec![value]),
+            };
+            match values {
+                Some(values) if text.starts_with('[') => {
+                    let values = format_values(&values, options.quote);
+                    push_list(out, entry.key, &values, options.width);
+                }
+                Some(values) => {
+                    let values = format_values(&values, options.quote);
+                    out.push_str(&format!("{}{}: {}\n", FMT_INDENT, entry.key, values[0]));
+                }
+                None => push_raw(out, entry, indent),
+            }
+        }
+        "instances" => {
+            let instances: Option<Vec<String>> = match entry.value.is_empty() {
+                false if entry.lines.is_empty() => {
+                    format_instance(entry.value, options.quote).map(|value| vec![value])
+                }
+                false => None,
+                true => entry
+                    .lines
+                    .iter()
+                    .map(|(_, text)| format_instance(text.strip_prefix('-')?, options.quote))
+                    .collect(),
+            };
+            match instances {
+                Some(instances) if instances.len() == 1 => {
+                    out.push_str(&format!("{}instances: {}\n", FMT_INDENT, instances[0]))
+                }
+                Some(instances) if !instances.is_empty() => {
+                    out.push_str(&format!("{}instances:\n", FMT_INDENT));
+                    for instance in instances {
+                        out.push_str(&format!("{}{}- {}\n", FMT_INDENT, FMT_INDENT, instance));
+                    }
+                }
+                _ => push_raw(out, entry, indent),
+            }
+        }
+        _ if entry.lines.is_empty() && !entry.key.is_empty() => {
+            out.push_str(&format!("{}{}: {}\n", FMT_INDENT, entry.key, entry.value))
+        }
+        _ => push_raw(out, entry, indent),
+    }
+}
+
+fn push_node(out: &mut String, node: &mut FmtNode, options: &VssFormatOptions) {
+    out.push_str(&format!("{}:\n", node.label));
+    node.entries.sort_by_key(|entry| key_rank(entry.key));
+    for entry in &node.entries {
+        push_entry(out, entry, node.indent, options);
+    }
+    for comment in &node.trivia.inner {
+        out.push_str(&format!("{}{}\n", FMT_INDENT, comment));
+    }
+}
+
+struct FmtWriter {
+    out: String,
+    blank: bool,
+    after_node: bool,
+}
+
+impl FmtWriter {
+    // blank lines are squeezed into one, leading ones are dropped
+    fn trivia(&mut self, trivia: &VssTrivia) {
+        if trivia.blank_lines > 0 {
+            self.blank = !self.out.is_empty();
+        }
+        for comment in &trivia.comments {
+            self.line(comment);
+        }
+    }
+
+    fn line(&mut self, text: &str) {
+        if self.blank || self.after_node {
+            self.out.push('\n');
+        }
+        self.out.push_str(text);
+        self.out.push('\n');
+        self.blank = false;
+        self.after_node = false;
+    }
+
+    fn node(&mut self, node: &mut FmtNode, options: &VssFormatOptions) {
+        self.trivia(&node.trivia);
+        if !self.out.is_empty() && (self.blank || !is_comment(&self.out)) {
+            self.out.push('\n');
+        }
+        push_node(&mut self.out, node, options);
+        self.blank = false;
+        self.after_node = true;
+    }
+}
+
+// return source in canonical style, formatting an already formatted source is a no-op
+pub fn vss_format(source: &str, options: &VssFormatOptions) -> String {
+    let mut writer = FmtWriter {
+        out: String::new(),
+        blank: false,
+        after_node: false,
+    };
+    for item in parse_items(source) {
+        match item {
+            FmtItem::Trivia(trivia) => writer.trivia(&trivia),
+            FmtItem::Include(text) | FmtItem::Other(text) => writer.line(text),
+            FmtItem::Node(mut node) => writer.node(&mut node, options),
+        }
+    }
+    writer.out
+}
+
+// true when last emitted line is a top level comment (attached to next node)
+fn is_comment(out: &str) -> bool {
+    let last = out.trim_end_matches('\n').rsplit('\n').next().unwrap_or("");
+    last.starts_with('#') && !last.starts_with("#include")
+}
+
+// check=true only compares, otherwise file is rewritten when needed
+// return true when file was already in canonical style
+pub fn vss_format_file(
+    filename: &str,
+    options: &VssFormatOptions,
+    check: bool,
+) -> Result<bool, AfbError> {
+    // BOM and CRLF line breaks are kept, invalid utf-8 is never rewritten
+    let decoded = VssText::from_file(filename)?;
+    let source = decoded.encode(&decoded.text);
+    let formatted = decoded.encode(&vss_format(&decoded.text, options));
+    if formatted == source {
+        return Ok(true);
+    }
+    if !check {
+        if let Err(error) = fs::write(filename, formatted) {
+            return Err(AfbError::new(
+                "vss-format-write",
+                format!("{} ({})", filename, error),
+            ));
+        }
+    }
+    Ok(false)
+}
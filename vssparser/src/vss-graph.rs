@@ -0,0 +1,245 @@
+/*
+ * Copyright (C) 2015-2023 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
+ * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
+ *
+ * signal tree visualisation as graphviz (dot) or mermaid graph
+ *   - graph starts at root vpath (or every top level node), depth counts levels below root
+ *   - instance branches are either collapsed (instances within label) or expanded,
+ *     in which case children are repeated under every instance
+ */
+
+use std::str::FromStr;
+
+use crate::index::*;
+use crate::types::*;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VssGraphFormat {
+    Dot,
+    Mermaid,
+}
+
+impl FromStr for VssGraphFormat {
+    type Err = AfbError;
+    fn from_str(value: &str) -> Result<Self, AfbError> {
+        match value.to_lowercase().as_str() {
+            "dot" | "graphviz" => Ok(VssGraphFormat::Dot),
+            "mermaid" | "mmd" => Ok(VssGraphFormat::Mermaid),
+            _ => Err(AfbError::new(
+                "vss-graph-format",
+                format!("unsupported graph format:{} (dot|mermaid)", value),
+            )),
+        }
+    }
+}
+
+pub struct VssGraphOptions {
+    pub root: Option<String>,
+    pub depth: Option<usize>,
+    pub collapse_instances: bool,
+    pub colors: bool,
+}
+
+impl Default for VssGraphOptions {
+    fn default() -> Self {
+        VssGraphOptions {
+            root: None,
+            depth: None,
+            collapse_instances: true,
+            colors: true,
+        }
+    }
+}
+
+// (class, fill colour), expanded instances are drawn dashed
+const GRAPH_COLORS: [(&str, &str); 5] = [
+    ("branch", "#d9d9d9"),
+    ("sensor", "#a6d96a"),
+    ("actuator", "#fdae61"),
+    ("attribute", "#abd9e9"),
+    ("instance", "#f0f0f0"),
+];
+
+struct GraphNode {
+    label: String,
+    class: &'static str,
+}
+
+struct Graph<'a, 'b> {
+    index: &'b VssIndex<'a>,
+    options: &'b VssGraphOptions,
+    nodes: Vec<GraphNode>,
+    edges: Vec<(usize, usize)>,
+}
+
+fn graph_class(node: &VssNode) -> &'static str {
+    match node {
+        VssNode::Branch(_) => "branch",
+        VssNode::Sensor(_) => "sensor",
+        VssNode::Actuator(_) => "actuator",
+        VssNode::Attribute(_) => "attribute",
+    }
+}
+
+impl<'a> Graph<'a, '_> {
+    fn push(&mut self, label: String, class: &'static str, parent: Option<usize>) -> usize {
+        self.nodes.push(GraphNode { label, class });
+        let idx = self.nodes.len() - 1;
+        if let Some(parent) = parent {
+            self.edges.push((parent, idx));
+        }
+        idx
+    }
+
+    fn too_deep(&self, depth: usize) -> bool {
+        match self.options.depth {
+            Some(limit) => depth > limit,
+            None => false,
+        }
+    }
+
+    fn walk(&mut self, node: VssNode<'a>, depth: usize, parent: Option<usize>) {
+        let name = node.vpath().rsplit('.').next().unwrap_or("");
+        let mut label = name.to_string();
+        let mut levels: Vec<Vec<String>> = Vec::new();
+        if let VssNode::Branch(obj) = node {
+            if self.options.collapse_instances {
+                for instance in &obj.instances {
+                    label.push_str(&format!("\n{}", instance.names().join(",")));
+                }
+            } else {
+                levels = obj
+                    .instances
+                    .iter()
+                    .map(|instance| instance.names())
+                    .collect();
+            }
+        }
+        let idx = self.push(label, graph_class(&node), parent);
+        self.instances(node, &levels, depth + 1, idx);
+    }
+
+    // one level per instance declaration, children hang on the last one
+    fn instances(
+        &mut self,
+        node: VssNode<'a>,
+        levels: &[Vec<String>],
+        depth: usize,
+        parent: usize,
+    ) {
+        if self.too_deep(depth) {
+            return;
+        }
+        match levels.split_first() {
+            Some((names, levels)) => {
+                for name in names {
+                    let idx = self.push(name.clone(), "instance", Some(parent));
+                    self.instances(node, levels, depth + 1, idx);
+                }
+            }
+            None => {
+                for child in self.index.direct_children(node.vpath()) {
+                    self.walk(child, depth, Some(parent));
+                }
+            }
+        }
+    }
+}
+
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn mermaid_escape(text: &str) -> String {
+    text.replace('"', "#quot;").replace('\n', "<br/>")
+}
+
+fn graph_dot(graph: &Graph) -> String {
+    let mut out = String::from("digraph vss {\n  rankdir=LR;\n");
+    out.push_str("  node [shape=box, style=\"rounded,filled\", fillcolor=\"#ffffff\"];\n");
+    for (idx, node) in graph.nodes.iter().enumerate() {
+        let mut attrs = format!("label=\"{}\"", dot_escape(&node.label));
+        if graph.options.colors {
+            if let Some((_, color)) = GRAPH_COLORS.iter().find(|(class, _)| *class == node.class) {
+                attrs.push_str(&format!(", fillcolor=\"{}\"", color));
+            }
+        }
+        if node.class == "instance" {
+            attrs.push_str(", style=\"rounded,filled,dashed\"");
+        }
+        out.push_str(&format!("  n{} [{}];\n", idx, attrs));
+    }
+    for (from, to) in &graph.edges {
+        out.push_str(&format!("  n{} -> n{};\n", from, to));
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn graph_mermaid(graph: &Graph) -> String {
+    let mut out = String::from("graph LR\n");
+    for (idx, node) in graph.nodes.iter().enumerate() {
+        out.push_str(&format!("  n{}[\"{}\"]", idx, mermaid_escape(&node.label)));
+        if graph.options.colors {
+            out.push_str(&format!(":::{}", node.class));
+        }
+        out.push('\n');
+    }
+    for (from, to) in &graph.edges {
+        out.push_str(&format!("  n{} --> n{}\n", from, to));
+    }
+    if graph.options.colors {
+        for (class, color) in GRAPH_COLORS {
+            out.push_str(&format!("  classDef {} fill:{}\n", class, color));
+        }
+    }
+    out
+}
+
+pub fn vss_graph_build(
+    spec: &VssSpec,
+    format: VssGraphFormat,
+    options: &VssGraphOptions,
+) -> Result<String, AfbError> {
+    let index = VssIndex::new(spec);
+    let roots: Vec<VssNode> = match &options.root {
+        Some(vpath) => match index.get(vpath) {
+            Some(node) => vec![node],
+            None => {
+                return Err(AfbError::new(
+                    "vss-graph-root",
+                    format!("root vpath:{} not found", vpath),
+                ))
+            }
+        },
+        // top level nodes have no parent within index
+        None => index
+            .iter()
+            .filter(|node| match node.vpath().rsplit_once('.') {
+                Some((parent, _)) => index.get(parent).is_none(),
+                None => true,
+            })
+            .collect(),
+    };
+
+    let mut graph = Graph {
+        index: &index,
+        options,
+        nodes: Vec::new(),
+        edges: Vec::new(),
+    };
+    for root in roots {
+        graph.walk(root, 0, None);
+    }
+
+    let output = match format {
+        VssGraphFormat::Dot => graph_dot(&graph),
+        VssGraphFormat::Mermaid => graph_mermaid(&graph),
+    };
+    Ok(output)
+}
@@ -0,0 +1,460 @@
+/*
+ * Copyright (C) 2015-2023 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
+ * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
+ *
+ */
+
+use std::collections::BTreeMap;
+
+use crate::types::*;
+use crate::units::*;
+
+// borrowed view on any parsed vss object
+#[derive(Debug, Clone, Copy)]
+pub enum VssNode<'a> {
+    Branch(&'a VssBranch),
+    Sensor(&'a VssSensor),
+    Actuator(&'a VssActuator),
+    Attribute(&'a VssAttribute),
+}
+
+impl<'a> From<&'a VssObject> for VssNode<'a> {
+    fn from(object: &'a VssObject) -> Self {
+        match object {
+            VssObject::Branch(obj) => VssNode::Branch(obj),
+            VssObject::Sensor(obj) => VssNode::Sensor(obj),
+            VssObject::Actuator(obj) => VssNode::Actuator(obj),
+            VssObject::Attribute(obj) => VssNode::Attribute(obj),
+        }
+    }
+}
+
+impl<'a> VssNode<'a> {
+    pub fn vpath(&self) -> &'a str {
+        match self {
+            VssNode::Branch(obj) => obj.vpath.as_str(),
+            VssNode::Sensor(obj) => obj.vpath.as_str(),
+            VssNode::Actuator(obj) => obj.vpath.as_str(),
+            VssNode::Attribute(obj) => obj.vpath.as_str(),
+        }
+    }
+
+    pub fn vtype(&self) -> &'a VssObjectType {
+        match self {
+            VssNode::Branch(obj) => &obj.vtype,
+            VssNode::Sensor(obj) => &obj.vtype,
+            VssNode::Actuator(obj) => &obj.vtype,
+            VssNode::Attribute(obj) => &obj.vtype,
+        }
+    }
+
+    pub fn description(&self) -> Option<&'a String> {
+        match self {
+            VssNode::Branch(obj) => obj.description.as_ref(),
+            VssNode::Sensor(obj) => obj.description.as_ref(),
+            VssNode::Actuator(obj) => obj.description.as_ref(),
+            VssNode::Attribute(obj) => obj.description.as_ref(),
+        }
+    }
+
+    pub fn comment(&self) -> Option<&'a String> {
+        match self {
+            VssNode::Branch(obj) => obj.comment.as_ref(),
+            VssNode::Sensor(obj) => obj.comment.as_ref(),
+            VssNode::Actuator(obj) => obj.comment.as_ref(),
+            VssNode::Attribute(obj) => obj.comment.as_ref(),
+        }
+    }
+
+    pub fn deprecation(&self) -> Option<&'a String> {
+        match self {
+            VssNode::Branch(obj) => obj.deprecation.as_ref(),
+            VssNode::Sensor(obj) => obj.deprecation.as_ref(),
+            VssNode::Actuator(obj) => obj.deprecation.as_ref(),
+            VssNode::Attribute(obj) => obj.deprecation.as_ref(),
+        }
+    }
+
+    pub fn fka(&self) -> &'a [String] {
+        match self {
+            VssNode::Branch(obj) => &obj.fka,
+            VssNode::Sensor(obj) => &obj.fka,
+            VssNode::Actuator(obj) => &obj.fka,
+            VssNode::Attribute(obj) => &obj.fka,
+        }
+    }
+
+    pub fn instantiate(&self) -> bool {
+        match self {
+            VssNode::Branch(obj) => obj.instantiate,
+            VssNode::Sensor(obj) => obj.instantiate,
+            VssNode::Actuator(obj) => obj.instantiate,
+            VssNode::Attribute(obj) => obj.instantiate,
+        }
+    }
+
+    pub fn l10n(&self) -> &'a [VssL10n] {
+        match self {
+            VssNode::Branch(obj) => &obj.l10n,
+            VssNode::Sensor(obj) => &obj.l10n,
+            VssNode::Actuator(obj) => &obj.l10n,
+            VssNode::Attribute(obj) => &obj.l10n,
+        }
+    }
+
+    // friendly name falls back on last vpath segment
+    pub fn name_l10n(&self, locale: Option<&str>) -> &'a str {
+        match VssL10n::select(self.l10n(), locale, |l10n| l10n.name.as_ref(), None) {
+            Some(name) => name,
+            None => self.vpath().rsplit('.').next().unwrap_or(""),
+        }
+    }
+
+    pub fn description_l10n(&self, locale: Option<&str>) -> Option<&'a str> {
+        VssL10n::select(
+            self.l10n(),
+            locale,
+            |l10n| l10n.description.as_ref(),
+            self.description(),
+        )
+    }
+
+    pub fn comment_l10n(&self, locale: Option<&str>) -> Option<&'a str> {
+        VssL10n::select(
+            self.l10n(),
+            locale,
+            |l10n| l10n.comment.as_ref(),
+            self.comment(),
+        )
+    }
+
+    pub fn access(&self) -> VssAccess {
+        self.vtype().access()
+    }
+
+    pub fn arraysize(&self) -> Option<usize> {
+        match self {
+            VssNode::Branch(_) => None,
+            VssNode::Sensor(obj) => obj.arraysize,
+            VssNode::Actuator(obj) => obj.arraysize,
+            VssNode::Attribute(obj) => obj.arraysize,
+        }
+    }
+
+    pub fn allowed(&self) -> &'a [String] {
+        match self {
+            VssNode::Branch(_) => &[],
+            VssNode::Sensor(obj) => &obj.allowed,
+            VssNode::Actuator(obj) => &obj.allowed,
+            VssNode::Attribute(obj) => &obj.allowed,
+        }
+    }
+
+    pub fn default(&self) -> &'a [String] {
+        match self {
+            VssNode::Branch(_) => &[],
+            VssNode::Sensor(obj) => &obj.default,
+            VssNode::Actuator(obj) => &obj.default,
+            VssNode::Attribute(obj) => &obj.default,
+        }
+    }
+
+    // branches have neither datatype nor unit
+    pub fn datatype(&self) -> Option<&'a VssValueType> {
+        match self {
+            VssNode::Branch(_) => None,
+            VssNode::Sensor(obj) => Some(&obj.datatype),
+            VssNode::Actuator(obj) => Some(&obj.datatype),
+            VssNode::Attribute(obj) => Some(&obj.datatype),
+        }
+    }
+
+    pub fn unit(&self) -> Option<&'a VssUnit> {
+        match self {
+            VssNode::Branch(_) => None,
+            VssNode::Sensor(obj) => Some(&obj.unit),
+            VssNode::Actuator(obj) => Some(&obj.unit),
+            VssNode::Attribute(obj) => Some(&obj.unit),
+        }
+    }
+
+    pub fn min(&self) -> Option<i64> {
+        match self {
+            VssNode::Sensor(obj) => obj.min,
+            VssNode::Actuator(obj) => obj.min,
+            _ => None,
+        }
+    }
+
+    pub fn max(&self) -> Option<i64> {
+        match self {
+            VssNode::Sensor(obj) => obj.max,
+            VssNode::Actuator(obj) => obj.max,
+            _ => None,
+        }
+    }
+
+    pub fn location(&self) -> VssLocation {
+        match self {
+            VssNode::Branch(obj) => obj.location,
+            VssNode::Sensor(obj) => obj.location,
+            VssNode::Actuator(obj) => obj.location,
+            VssNode::Attribute(obj) => obj.location,
+        }
+    }
+
+    pub fn println(&self, locator: &Locator) {
+        match self {
+            VssNode::Branch(obj) => obj.println(locator),
+            VssNode::Sensor(obj) => obj.println(locator),
+            VssNode::Actuator(obj) => obj.println(locator),
+            VssNode::Attribute(obj) => obj.println(locator),
+        }
+    }
+}
+
+// optional criteria applied on top of a vpath pattern, unset fields match everything
+pub struct VssFilter {
+    pub vtype: Option<VssObjectType>,
+    pub datatype: Option<VssValueType>,
+    pub unit: Option<VssUnit>,
+    pub access: Option<VssAccess>,
+}
+
+impl VssFilter {
+    pub fn new() -> Self {
+        VssFilter {
+            vtype: None,
+            datatype: None,
+            unit: None,
+            access: None,
+        }
+    }
+
+    pub fn matches(&self, node: &VssNode) -> bool {
+        if let Some(vtype) = &self.vtype {
+            if node.vtype() != vtype {
+                return false;
+            }
+        }
+        if let Some(datatype) = &self.datatype {
+            if node.datatype() != Some(datatype) {
+                return false;
+            }
+        }
+        if let Some(unit) = &self.unit {
+            if node.unit() != Some(unit) {
+                return false;
+            }
+        }
+        if let Some(access) = &self.access {
+            if node.access() != *access {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl Default for VssFilter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// glob within one vpath segment ('*' matches any sequence of chars)
+fn glob_segment(pattern: &str, segment: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or("");
+    if !segment.starts_with(first) {
+        return false;
+    }
+    let mut rest = &segment[first.len()..];
+    let mut parts: Vec<&str> = parts.collect();
+    let last = match parts.pop() {
+        None => return rest.is_empty(), // no '*' within pattern
+        Some(last) => last,
+    };
+    for part in parts {
+        match rest.find(part) {
+            None => return false,
+            Some(idx) => rest = &rest[idx + part.len()..],
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+// '**' matches zero or more segments, any other segment is matched with glob_segment
+fn glob_vpath(pattern: &[&str], vpath: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => vpath.is_empty(),
+        Some((&"**", tail)) => (0..=vpath.len()).any(|idx| glob_vpath(tail, &vpath[idx..])),
+        Some((head, tail)) => match vpath.split_first() {
+            None => false,
+            Some((segment, rest)) => glob_segment(head, segment) && glob_vpath(tail, rest),
+        },
+    }
+}
+
+// sorted vpath index over a parsed spec, lookups are O(log n) and prefix scans only visit matching subtree
+pub struct VssIndex<'a> {
+    nodes: BTreeMap<&'a str, VssNode<'a>>,
+    // former vpath => current vpath, collisions are reported by vss_check_aliases
+    aliases: BTreeMap<&'a str, &'a str>,
+}
+
+// node found by VssIndex::resolve, alias is set when lookup used a former vpath
+#[derive(Debug, Clone, Copy)]
+pub struct VssResolved<'a> {
+    pub node: VssNode<'a>,
+    pub alias: Option<&'a str>,
+}
+
+impl VssResolved<'_> {
+    pub fn notice(&self) -> Option<String> {
+        self.alias.map(|alias| {
+            format!(
+                "{} is a former vpath, use {} instead",
+                alias,
+                self.node.vpath()
+            )
+        })
+    }
+}
+
+impl<'a> VssIndex<'a> {
+    pub fn new(spec: &'a VssSpec) -> Self {
+        let mut index = VssIndex {
+            nodes: BTreeMap::new(),
+            aliases: BTreeMap::new(),
+        };
+
+        // when a vpath is declared twice the first definition wins
+        for obj in &spec.branches {
+            index.insert(VssNode::Branch(obj));
+        }
+        for obj in &spec.sensors {
+            index.insert(VssNode::Sensor(obj));
+        }
+        for obj in &spec.actuators {
+            index.insert(VssNode::Actuator(obj));
+        }
+        for obj in &spec.attributes {
+            index.insert(VssNode::Attribute(obj));
+        }
+        index
+    }
+
+    fn insert(&mut self, node: VssNode<'a>) {
+        for alias in node.fka() {
+            self.aliases.entry(alias.as_str()).or_insert(node.vpath());
+        }
+        self.nodes.entry(node.vpath()).or_insert(node);
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    // exact vpath lookup
+    pub fn get(&self, vpath: &str) -> Option<VssNode<'a>> {
+        self.nodes.get(vpath).copied()
+    }
+
+    // exact lookup falling back on former vpaths (fka), current vpaths win over aliases
+    pub fn resolve(&self, vpath: &str) -> Option<VssResolved<'a>> {
+        if let Some(node) = self.get(vpath) {
+            return Some(VssResolved { node, alias: None });
+        }
+        let (alias, current) = self.aliases.get_key_value(vpath)?;
+        Some(VssResolved {
+            node: self.get(current)?,
+            alias: Some(alias),
+        })
+    }
+
+    // former vpath => current vpath, in former vpath order
+    pub fn aliases(&self) -> impl Iterator<Item = (&'a str, &'a str)> + '_ {
+        self.aliases.iter().map(|(alias, vpath)| (*alias, *vpath))
+    }
+
+    // every node in vpath order
+    pub fn iter(&self) -> impl Iterator<Item = VssNode<'a>> + '_ {
+        self.nodes.values().copied()
+    }
+
+    // all nodes below a branch (recursively), branch itself excluded
+    pub fn children(&self, vpath: &str) -> Vec<VssNode<'a>> {
+        let prefix = format!("{}.", vpath);
+        self.scan(prefix.as_str()).collect()
+    }
+
+    // nodes one level below a branch
+    pub fn direct_children(&self, vpath: &str) -> Vec<VssNode<'a>> {
+        let prefix = format!("{}.", vpath);
+        self.scan(prefix.as_str())
+            .filter(|node| !node.vpath()[prefix.len()..].contains('.'))
+            .collect()
+    }
+
+    fn scan<'b>(&'b self, prefix: &'b str) -> impl Iterator<Item = VssNode<'a>> + 'b {
+        self.nodes
+            .range::<str, _>((
+                std::ops::Bound::Included(prefix),
+                std::ops::Bound::Unbounded,
+            ))
+            .take_while(move |(vpath, _)| vpath.starts_with(prefix))
+            .map(|(_, node)| *node)
+    }
+
+    // pattern segments are split on '.', '*' matches within one segment and '**' matches any depth
+    // ex: Vehicle.Cabin.* Vehicle.**.IsOpen Vehicle.Cabin.Door.Row*.**
+    pub fn query(&self, pattern: &str) -> Vec<VssNode<'a>> {
+        let segments = pattern.split('.').collect::<Vec<&str>>();
+
+        // leading segments without wildcard restrict the scanned subtree
+        let fixed = segments
+            .iter()
+            .take_while(|segment| !segment.contains('*'))
+            .count();
+        if fixed == segments.len() {
+            return self.get(pattern).into_iter().collect();
+        }
+        let prefix = if fixed == 0 {
+            String::new()
+        } else {
+            format!("{}.", segments[..fixed].join("."))
+        };
+
+        let mut result = Vec::new();
+        for node in self.scan(prefix.as_str()) {
+            let vpath = node.vpath().split('.').collect::<Vec<&str>>();
+            if glob_vpath(&segments[fixed..], &vpath[fixed..]) {
+                result.push(node);
+            }
+        }
+
+        // '**' may match zero segment, in which case the prefix branch itself is part of the result
+        if fixed > 0 && segments[fixed..].iter().all(|segment| *segment == "**") {
+            if let Some(node) = self.get(&prefix[..prefix.len() - 1]) {
+                result.insert(0, node);
+            }
+        }
+        result
+    }
+
+    // pattern query restricted by node type, datatype, unit and/or access
+    pub fn filter(&self, pattern: &str, filter: &VssFilter) -> Vec<VssNode<'a>> {
+        self.query(pattern)
+            .into_iter()
+            .filter(|node| filter.matches(node))
+            .collect()
+    }
+}
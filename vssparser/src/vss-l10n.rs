@@ -0,0 +1,97 @@
+/*
+ * Copyright (C) 2015-2023 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
+ * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
+ *
+ * translation side file (ex: fr.vspec.l10n) is keyed by vpath
+ *   Vehicle.Speed:
+ *     name: Vitesse
+ *     description: Vitesse du véhicule.
+ */
+
+use std::collections::HashMap;
+
+use crate::index::*;
+use crate::types::*;
+use crate::yaml::*;
+
+// locale defaults to side file basename prefix ('fr' for fr.vspec.l10n)
+pub fn vss_l10n_from_file(
+    spec: &mut VssSpec,
+    filename: &str,
+    locale: Option<&str>,
+) -> Result<usize, AfbError> {
+    let locale = match locale {
+        Some(locale) => locale.to_string(),
+        None => {
+            let basename = filename.rsplit('/').next().unwrap_or(filename);
+            basename.split('.').next().unwrap_or(basename).to_string()
+        }
+    };
+
+    let mut nodes: HashMap<String, &mut Vec<VssL10n>> = HashMap::new();
+    for obj in &mut spec.branches {
+        nodes.entry(obj.vpath.clone()).or_insert(&mut obj.l10n);
+    }
+    for obj in &mut spec.sensors {
+        nodes.entry(obj.vpath.clone()).or_insert(&mut obj.l10n);
+    }
+    for obj in &mut spec.actuators {
+        nodes.entry(obj.vpath.clone()).or_insert(&mut obj.l10n);
+    }
+    for obj in &mut spec.attributes {
+        nodes.entry(obj.vpath.clone()).or_insert(&mut obj.l10n);
+    }
+
+    let entries = vss_yaml_from_file(filename)?;
+    for entry in &entries {
+        let l10n = match nodes.get_mut(&entry.key) {
+            Some(l10n) => l10n,
+            None => {
+                return Err(AfbError::new(
+                    "vss-l10n-vpath",
+                    format!("{}:{} vpath:{} not found", filename, entry.line, entry.key),
+                ))
+            }
+        };
+
+        let idx = match l10n.iter().position(|value| value.locale == locale) {
+            Some(idx) => idx,
+            None => {
+                l10n.push(VssL10n::new(locale.as_str()));
+                l10n.len() - 1
+            }
+        };
+        // side file overloads inline translations
+        let translation = &mut l10n[idx];
+        if let Some(value) = entry.get_str("name") {
+            translation.name = Some(value.to_string());
+        }
+        if let Some(value) = entry.get_str("description") {
+            translation.description = Some(value.to_string());
+        }
+        if let Some(value) = entry.get_str("comment") {
+            translation.comment = Some(value.to_string());
+        }
+    }
+    Ok(entries.len())
+}
+
+// vpath of documented nodes without description for a given locale (language fallback accepted)
+pub fn vss_l10n_missing<'a>(spec: &'a VssSpec, locale: &str) -> Vec<&'a str> {
+    let mut missing = Vec::new();
+    for node in VssIndex::new(spec).iter() {
+        if node.description().is_none() {
+            continue;
+        }
+        let translated = VssL10n::find(node.l10n(), locale)
+            .iter()
+            .any(|l10n| l10n.description.is_some());
+        if !translated {
+            missing.push(node.vpath());
+        }
+    }
+    missing
+}
@@ -20,19 +20,85 @@ extern crate nom;
 #[path = "./vss-utils.rs"]
 mod utils;
 
+#[path = "./vss-scalar.rs"]
+mod scalar;
+
+#[path = "./vss-yaml.rs"]
+mod yaml;
+
 #[path = "./vss-units.rs"]
 mod units;
 
 #[path = "./vss-types.rs"]
 mod types;
 
+#[path = "./vss-version.rs"]
+mod version;
+
 #[path = "./vss-parser.rs"]
 mod parser;
 
+#[path = "./vss-index.rs"]
+mod index;
+
+#[path = "./vss-validate.rs"]
+mod validate;
+
+#[path = "./vss-aggregate.rs"]
+mod aggregate;
+
+#[path = "./vss-l10n.rs"]
+mod l10n;
+
+#[path = "./vss-catalog.rs"]
+mod catalog;
+
+#[path = "./vss-format.rs"]
+mod format;
+
+#[path = "./vss-doc.rs"]
+mod doc;
+
+#[path = "./vss-graph.rs"]
+mod graph;
+
+#[path = "./vss-expand.rs"]
+mod expand;
+
+#[path = "./vss-export.rs"]
+mod export;
+
+#[path = "./vss-diff.rs"]
+mod diff;
+
+#[path = "./vss-stats.rs"]
+mod stats;
+
+#[path = "./vss-lint.rs"]
+mod lint;
+
+#[path = "./vss-blame.rs"]
+mod blame;
+
 pub mod prelude {
-    pub use crate::utils::*;
+    pub use crate::aggregate::*;
+    pub use crate::blame::*;
+    pub use crate::catalog::*;
+    pub use crate::diff::*;
+    pub use crate::doc::*;
+    pub use crate::expand::*;
+    pub use crate::export::*;
+    pub use crate::format::*;
+    pub use crate::graph::*;
+    pub use crate::index::*;
+    pub use crate::l10n::*;
+    pub use crate::lint::*;
     pub use crate::parser::*;
+    pub use crate::stats::*;
     pub use crate::types::*;
     pub use crate::units::*;
+    pub use crate::utils::*;
+    pub use crate::validate::*;
+    pub use crate::version::*;
+    pub use crate::yaml::*;
 }
-
@@ -0,0 +1,316 @@
+/*
+ * Copyright (C) 2015-2023 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
+ * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
+ *
+ * naming convention linter, rules run over node names (last vpath segment)
+ *   - each rule has a severity (error|warning|off), defaults can be overloaded from a yaml config
+ *   - '# vss-lint: disable' (all rules) or '# vss-lint: disable=rule,rule' before or within a node
+ *     block suppresses findings on this node, directives are kept whatever the trivia mode
+ *
+ * config file:
+ *   rules:
+ *     abbreviation: error
+ *     description-missing: warning
+ *   boolean-prefixes: [Is, Has, Can]
+ *   unit-words: [Kmh, Celsius]
+ *   abbreviations: [Temp, Pos]
+ */
+
+use crate::index::*;
+use crate::types::*;
+use crate::yaml::*;
+
+pub const LINT_MARKER: &str = "vss-lint:";
+
+// (uid, default severity, description)
+const LINT_RULES: [(&str, Option<VssLevel>, &str); 5] = [
+    (
+        "name-case",
+        Some(VssLevel::Warning),
+        "node names are UpperCamelCase",
+    ),
+    (
+        "boolean-prefix",
+        Some(VssLevel::Warning),
+        "boolean names start with a prefix (Is, Has)",
+    ),
+    (
+        "unit-in-name",
+        Some(VssLevel::Warning),
+        "node names do not embed a unit",
+    ),
+    (
+        "abbreviation",
+        Some(VssLevel::Warning),
+        "node names do not use abbreviations",
+    ),
+    ("description-missing", None, "every node has a description"),
+];
+
+const LINT_BOOLEAN_PREFIXES: [&str; 2] = ["Is", "Has"];
+
+const LINT_UNIT_WORDS: [&str; 22] = [
+    "Kmh",
+    "Kph",
+    "Mph",
+    "Km",
+    "Meter",
+    "Meters",
+    "Celsius",
+    "Fahrenheit",
+    "Kelvin",
+    "Percent",
+    "Kw",
+    "Kwh",
+    "Rpm",
+    "Sec",
+    "Seconds",
+    "Liter",
+    "Liters",
+    "Volt",
+    "Volts",
+    "Amps",
+    "Kpa",
+    "Psi",
+];
+
+const LINT_ABBREVIATIONS: [&str; 14] = [
+    "Temp", "Pos", "Num", "Cnt", "Btn", "Ctrl", "Veh", "Spd", "Lvl", "Dist", "Pct", "Sts", "Req",
+    "Cfg",
+];
+
+pub struct VssLintRule {
+    pub uid: &'static str,
+    pub level: Option<VssLevel>,
+    pub description: &'static str,
+}
+
+pub struct VssLintConfig {
+    pub rules: Vec<VssLintRule>,
+    pub boolean_prefixes: Vec<String>,
+    pub unit_words: Vec<String>,
+    pub abbreviations: Vec<String>,
+}
+
+impl Default for VssLintConfig {
+    fn default() -> Self {
+        VssLintConfig {
+            rules: LINT_RULES
+                .iter()
+                .map(|(uid, level, description)| VssLintRule {
+                    uid,
+                    level: level.clone(),
+                    description,
+                })
+                .collect(),
+            boolean_prefixes: LINT_BOOLEAN_PREFIXES.map(String::from).to_vec(),
+            unit_words: LINT_UNIT_WORDS.map(String::from).to_vec(),
+            abbreviations: LINT_ABBREVIATIONS.map(String::from).to_vec(),
+        }
+    }
+}
+
+fn lint_config_error(filename: &str, line: u32, info: String) -> AfbError {
+    AfbError::new("vss-lint-config", format!("{}:{} {}", filename, line, info))
+}
+
+impl VssLintConfig {
+    // defaults overloaded by config file, lists replace default ones
+    pub fn from_file(filename: &str) -> Result<Self, AfbError> {
+        let mut config = VssLintConfig::default();
+        for node in vss_yaml_from_file(filename)? {
+            match node.key.as_str() {
+                "rules" => {
+                    for rule in &node.children {
+                        let level = match rule.value.as_deref() {
+                            Some("error") => Some(VssLevel::Error),
+                            Some("warning") => Some(VssLevel::Warning),
+                            Some("off") => None,
+                            value => {
+                                return Err(lint_config_error(
+                                    filename,
+                                    rule.line,
+                                    format!(
+                                        "rule:{} invalid severity:{} (error|warning|off)",
+                                        rule.key,
+                                        value.unwrap_or("")
+                                    ),
+                                ))
+                            }
+                        };
+                        if !config.set_level(&rule.key, level) {
+                            return Err(lint_config_error(
+                                filename,
+                                rule.line,
+                                format!("unknown rule:{}", rule.key),
+                            ));
+                        }
+                    }
+                }
+                "boolean-prefixes" => config.boolean_prefixes = node.list.clone(),
+                "unit-words" => config.unit_words = node.list.clone(),
+                "abbreviations" => config.abbreviations = node.list.clone(),
+                _ => {
+                    return Err(lint_config_error(
+                        filename,
+                        node.line,
+                        format!("unknown key:{}", node.key),
+                    ))
+                }
+            }
+        }
+        Ok(config)
+    }
+
+    // return false when rule does not exist
+    pub fn set_level(&mut self, uid: &str, level: Option<VssLevel>) -> bool {
+        match self.rules.iter_mut().find(|rule| rule.uid == uid) {
+            Some(rule) => {
+                rule.level = level;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn level(&self, uid: &str) -> Option<VssLevel> {
+        match self.rules.iter().find(|rule| rule.uid == uid) {
+            Some(rule) => rule.level.clone(),
+            None => None,
+        }
+    }
+}
+
+// split UpperCamelCase name into words, acronyms stay together (ABSIsOn => ABS,Is,On)
+fn lint_words(name: &str) -> Vec<&str> {
+    let chars = name.char_indices().collect::<Vec<(usize, char)>>();
+    let mut words = Vec::new();
+    let mut start = 0;
+    for idx in 1..chars.len() {
+        let (offset, chr) = chars[idx];
+        let previous = chars[idx - 1].1;
+        let next_lower = chars.get(idx + 1).map(|(_, chr)| chr.is_lowercase());
+        if chr.is_uppercase()
+            && (!previous.is_uppercase() || (previous.is_uppercase() && next_lower == Some(true)))
+        {
+            words.push(&name[start..offset]);
+            start = offset;
+        }
+    }
+    if start < name.len() {
+        words.push(&name[start..]);
+    }
+    words
+}
+
+// rules suppressed by node comments, None when every rule is
+fn lint_suppressed(node: &VssNode) -> Option<Vec<String>> {
+    let trivia = match node {
+        VssNode::Branch(obj) => &obj.trivia,
+        VssNode::Sensor(obj) => &obj.trivia,
+        VssNode::Actuator(obj) => &obj.trivia,
+        VssNode::Attribute(obj) => &obj.trivia,
+    };
+    let mut rules = Vec::new();
+    if let Some(trivia) = trivia {
+        for comment in trivia.comments.iter().chain(trivia.inner.iter()) {
+            let text = comment.trim_start_matches('#').trim();
+            let text = match text.strip_prefix(LINT_MARKER) {
+                Some(text) => text.trim(),
+                None => continue,
+            };
+            match text.strip_prefix("disable") {
+                Some("") => return None,
+                Some(list) => match list.trim_start().strip_prefix('=') {
+                    Some(list) => rules.extend(list.split(',').map(|uid| uid.trim().to_string())),
+                    None => continue,
+                },
+                None => continue,
+            }
+        }
+    }
+    Some(rules)
+}
+
+fn lint_node(node: &VssNode, config: &VssLintConfig) -> Vec<(&'static str, String)> {
+    let name = node.vpath().rsplit('.').next().unwrap_or("");
+    let words = lint_words(name);
+    let mut findings: Vec<(&'static str, String)> = Vec::new();
+
+    let upper = name.chars().next().map(|chr| chr.is_ascii_uppercase()) == Some(true);
+    if !upper || !name.chars().all(|chr| chr.is_ascii_alphanumeric()) {
+        findings.push(("name-case", format!("name:{} is not UpperCamelCase", name)));
+    }
+    if node.datatype() == Some(&VssValueType::Boolean)
+        && !config
+            .boolean_prefixes
+            .iter()
+            .any(|prefix| words.first() == Some(&prefix.as_str()))
+    {
+        findings.push((
+            "boolean-prefix",
+            format!(
+                "boolean name:{} should start with {}",
+                name,
+                config.boolean_prefixes.join("|")
+            ),
+        ));
+    }
+    for word in &words {
+        if config
+            .unit_words
+            .iter()
+            .any(|unit| unit.eq_ignore_ascii_case(word))
+        {
+            findings.push((
+                "unit-in-name",
+                format!("name:{} embeds unit:{}", name, word),
+            ));
+        }
+        if config
+            .abbreviations
+            .iter()
+            .any(|value| value.eq_ignore_ascii_case(word))
+        {
+            findings.push((
+                "abbreviation",
+                format!("name:{} uses abbreviation:{}", name, word),
+            ));
+        }
+    }
+    match node.description() {
+        Some(value) if !value.trim().is_empty() => {}
+        _ => findings.push(("description-missing", "no description".to_string())),
+    }
+    findings
+}
+
+// findings sorted by vpath, uid is the rule name
+pub fn vss_lint(spec: &VssSpec, config: &VssLintConfig) -> Vec<VssDiagnostic> {
+    let index = VssIndex::new(spec);
+    let mut diagnostics = Vec::new();
+    for node in index.iter() {
+        let suppressed = match lint_suppressed(&node) {
+            Some(rules) => rules,
+            None => continue,
+        };
+        for (uid, info) in lint_node(&node, config) {
+            if suppressed.iter().any(|rule| rule == uid) {
+                continue;
+            }
+            if let Some(level) = config.level(uid) {
+                diagnostics.push(VssDiagnostic::new(
+                    level,
+                    uid,
+                    node.vpath(),
+                    info,
+                    node.location(),
+                ));
+            }
+        }
+    }
+    diagnostics
+}
@@ -7,13 +7,17 @@
  *
  */
 
+use std::str::FromStr;
+
+use crate::scalar::*;
 use crate::types::*;
 use crate::units::*;
 use crate::utils::*;
+use crate::version::*;
 
 use nom::{
     branch::alt,
-    bytes::complete::{tag, tag_no_case, take_while, take_while_m_n},
+    bytes::complete::{tag, take_while, take_while1, take_while_m_n},
     character::complete::{alphanumeric1, char, newline, not_line_ending, space0, space1},
     combinator::{eof, opt},
     error::{Error, ErrorKind},
@@ -22,7 +26,7 @@ use nom::{
 };
 
 // error ref: https://github.com/rust-bakery/nom/blob/main/doc/error_management.md
-fn nom_to_code_error<'a>(error: nom::Err<Error<&'a str>>) -> (&'a str, ErrorKind) {
+fn nom_to_code_error(error: nom::Err<Error<&str>>) -> (&str, ErrorKind) {
     match error {
         nom::Err::Error(error) => (error.input, error.code),
         nom::Err::Incomplete(_error) => ("label-not-found", ErrorKind::Fail),
@@ -40,11 +44,6 @@ fn close_bracket(s: &str) -> IResult<&str, char> {
     char(']')(s)
 }
 
-fn quote<'a>(input: &'a str) -> IResult<&'a str, char> {
-    let (input, char) = alt((char('"'), char('\'')))(input)?;
-    Ok((input, char))
-}
-
 fn sharp(s: &str) -> IResult<&str, char> {
     char('#')(s)
 }
@@ -70,57 +69,300 @@ fn _ignore_empty_lines(mut input: &str) -> &str {
     }
 }
 
-fn end_of_file<'a>(input: &'a str) -> IResult<&'a str, char> {
+fn end_of_file(input: &str) -> IResult<&str, char> {
     let _ = eof(input)?;
     Ok((input, ' '))
 }
 
 // search \n or eof
-fn eol<'a>(input: &'a str) -> IResult<&'a str, char> {
+fn eol(input: &str) -> IResult<&str, char> {
     let (input, char) = alt((newline, end_of_file))(input)?;
     Ok((input, char))
 }
 
-fn is_valid_string1(chr: char) -> bool {
-    chr != '"' && chr.is_ascii()
+fn yaml_error(input: &str) -> nom::Err<Error<&str>> {
+    nom::Err::Error(Error {
+        input,
+        code: ErrorKind::Verify,
+    })
 }
-fn is_valid_string2(chr: char) -> bool {
-    chr != '\'' && chr.is_ascii()
+
+// next line when indented deeper than idt_size, returns (next, indent, line without indent)
+fn indented_line(input: &str, idt_size: usize) -> Option<(&str, usize, &str)> {
+    let indent = line_indent(input);
+    if indent <= idt_size {
+        return None;
+    }
+    let (next, line) = not_line_ending::<&str, Error<&str>>(&input[indent..]).ok()?;
+    let (next, _) = eol(next).ok()?;
+    Some((next, indent, line))
 }
 
-fn vss_string<'a>(input: &'a str) -> IResult<&'a str, String> {
+// end of value with an optional trailing '# comment'
+fn end_of_value(input: &str) -> IResult<&str, ()> {
     let (input, _) = space0(input)?;
-    let (input, quote) = quote(input)?;
-    let (input, text) = if quote == '"' {
-        let (input, text) = take_while(is_valid_string1)(input)?;
-        let (input, _) = char('"')(input)?;
-        (input, text)
-    } else {
-        let (input, text) = take_while(is_valid_string2)(input)?;
-        let (input, _) = char('\'')(input)?;
-        (input, text)
+    let (input, _) = opt(tuple((sharp, not_line_ending)))(input)?;
+    let (input, _) = eol(input)?;
+    Ok((input, ()))
+}
+
+// single line value: quoted or plain scalar with optional trailing comment
+fn scalar_value(input: &str, idt_size: usize) -> IResult<&str, String> {
+    let (input, _) = space0(input)?;
+    if quote(input).is_ok() {
+        let (input, text) = quoted_text(input, idt_size)?;
+        let (input, _) = end_of_value(input)?;
+        return Ok((input, text));
+    }
+    let (input, line) = not_line_ending(input)?;
+    let (input, _) = eol(input)?;
+    Ok((input, plain_text(line).to_string()))
+}
+
+// literal '|' keeps line breaks, folded '>' joins lines, chomping indicators are accepted
+// and values never end with a line break
+fn block_scalar(
+    input: &str,
+    style: char,
+    indicator: Option<usize>,
+    idt_size: usize,
+) -> IResult<&str, String> {
+    let mut block = indicator.map(|size| idt_size + size);
+    let mut text = String::new();
+    let mut next = input;
+    while let Some((pointer, indent, line)) = indented_line(next, idt_size) {
+        next = pointer;
+        if line.trim().is_empty() {
+            text.push('\n');
+            continue;
+        }
+        let block = *block.get_or_insert(indent);
+        let line = match indent.checked_sub(block) {
+            Some(extra) => format!("{}{}", " ".repeat(extra), line),
+            None => return Err(yaml_error(line)),
+        };
+        if !text.is_empty() && !text.ends_with('\n') {
+            // more indented lines keep their line break within folded text
+            let joint = if style == '>' && !line.starts_with(' ') {
+                ' '
+            } else {
+                '\n'
+            };
+            text.push(joint);
+        }
+        text.push_str(&line);
+    }
+    Ok((next, text.trim_end_matches('\n').to_string()))
+}
+
+// text value (description, comment, ...): block scalar, quoted scalar or plain scalar
+// continued on every following line indented deeper than its label
+fn text_value(input: &str, idt_size: usize) -> IResult<&str, String> {
+    let (input, _) = space0(input)?;
+    let header = tuple((
+        alt((char('|'), char('>'))),
+        take_while(|chr: char| chr == '+' || chr == '-' || chr.is_ascii_digit()),
+        end_of_value,
+    ))(input);
+    if let Ok((next, (style, flags, _))) = header {
+        let indicator = flags
+            .trim_matches(['+', '-'])
+            .parse::<usize>()
+            .ok()
+            .filter(|size| *size > 0);
+        return block_scalar(next, style, indicator, idt_size);
+    }
+    if quote(input).is_ok() {
+        let (input, text) = quoted_text(input, idt_size)?;
+        let (input, _) = end_of_value(input)?;
+        return Ok((input, text));
+    }
+
+    let (input, line) = not_line_ending(input)?;
+    let (mut input, _) = eol(input)?;
+    let mut result = vec![plain_text(line)];
+    while let Some((next, _, line)) = indented_line(input, idt_size) {
+        result.push(plain_text(line));
+        input = next;
+    }
+    result.retain(|text| !text.is_empty());
+    Ok((input, result.join(" ")))
+}
+
+// yaml 1.2 core numbers, hexadecimal, octal and exponent forms are normalized to decimal text
+fn yaml_number(text: &str) -> Option<String> {
+    let (sign, digits) = match text.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", text.strip_prefix('+').unwrap_or(text)),
+    };
+    let radix = match digits.get(..2) {
+        Some("0x") => Some(16),
+        Some("0o") => Some(8),
+        _ => None,
     };
+    if let Some(radix) = radix {
+        let value = i64::from_str_radix(&digits[2..], radix).ok()?;
+        return Some(format!("{}{}", sign, value));
+    }
+    if !digits.starts_with(|chr: char| chr.is_ascii_digit() || chr == '.') {
+        return None;
+    }
+    let value = text.parse::<f64>().ok()?;
+    if digits.contains(['e', 'E']) {
+        Some(value.to_string())
+    } else {
+        Some(format!("{}{}", sign, digits))
+    }
+}
+
+// integer value, 1e3 or 0x10 are accepted while 0.5 is not
+fn yaml_integer(text: &str) -> Option<i64> {
+    yaml_number(text)?.parse::<i64>().ok()
+}
+
+// value of sequence labels, nested flow sequences are kept for instances
+#[derive(Debug)]
+enum YamlValue {
+    Scalar(String),
+    Sequence(Vec<YamlValue>),
+}
+
+fn plain_scalar(text: &str) -> YamlValue {
+    match yaml_number(text) {
+        Some(value) => YamlValue::Scalar(value),
+        None => YamlValue::Scalar(text.to_string()),
+    }
+}
+
+// blanks, line breaks and comments within flow sequences
+fn flow_blanks(mut input: &str) -> &str {
+    loop {
+        input = input.trim_start_matches([' ', '\t', '\n']);
+        match input.strip_prefix('#') {
+            Some(comment) => input = comment.trim_start_matches(|chr| chr != '\n'),
+            None => break input,
+        }
+    }
+}
+
+// plain scalar within flow sequence ends on ',' or ']', Row[1,2] brackets are kept balanced
+fn flow_plain(input: &str) -> IResult<&str, YamlValue> {
+    let mut depth = 0;
+    let mut end = input.len();
+    for (idx, chr) in input.char_indices() {
+        match chr {
+            '[' => depth += 1,
+            ']' if depth > 0 => depth -= 1,
+            ',' | ']' | '\n' => {
+                end = idx;
+                break;
+            }
+            '#' if input[..idx].ends_with([' ', '\t']) => {
+                end = idx;
+                break;
+            }
+            _ => {}
+        }
+    }
+    let text = input[..end].trim();
+    if text.is_empty() {
+        return Err(yaml_error(input));
+    }
+    Ok((&input[end..], plain_scalar(text)))
+}
+
+// [value1, 'value2', [nested]] possibly spread over several lines
+fn flow_sequence(input: &str) -> IResult<&str, Vec<YamlValue>> {
+    let (mut input, _) = open_bracket(input)?;
+    let mut values = Vec::new();
+    loop {
+        input = flow_blanks(input);
+        if let Ok((next, _)) = close_bracket(input) {
+            break Ok((next, values));
+        }
+        let (next, value) = if open_bracket(input).is_ok() {
+            let (next, values) = flow_sequence(input)?;
+            (next, YamlValue::Sequence(values))
+        } else if quote(input).is_ok() {
+            let (next, text) = quoted_text(input, 0)?;
+            (next, YamlValue::Scalar(text))
+        } else {
+            flow_plain(input)?
+        };
+        values.push(value);
+        let next = flow_blanks(next);
+        let (next, separator) = alt((close_bracket, comma))(next)?;
+        if separator == ']' {
+            break Ok((next, values));
+        }
+        input = next;
+    }
+}
+
+// one item of a block sequence, after '- '
+fn block_item(input: &str, idt_size: usize) -> IResult<&str, YamlValue> {
     let (input, _) = space0(input)?;
-    if text.len() == 0 {
-        return Err(nom::Err::Error(Error {
-            input: input,
-            code: ErrorKind::Eof,
-        }));
+    if open_bracket(input).is_ok() {
+        let (input, values) = flow_sequence(input)?;
+        let (input, _) = end_of_value(input)?;
+        return Ok((input, YamlValue::Sequence(values)));
     }
-    Ok((input, text.to_owned()))
+    if quote(input).is_ok() {
+        let (input, text) = quoted_text(input, idt_size)?;
+        let (input, _) = end_of_value(input)?;
+        return Ok((input, YamlValue::Scalar(text)));
+    }
+    let (input, line) = not_line_ending(input)?;
+    let (input, _) = eol(input)?;
+    let text = plain_text(line);
+    if text.is_empty() {
+        return Err(yaml_error(line));
+    }
+    Ok((input, plain_scalar(text)))
 }
 
-fn string_or_number<'a>(input: &'a str) -> IResult<&'a str, String> {
-    let (input, value) = alt((vss_number, vss_string))(input)?;
-    Ok((input, value))
+// '- item' lines, sequence may share its label indentation
+fn block_sequence(input: &str, idt_size: usize) -> IResult<&str, Vec<YamlValue>> {
+    let seq_indent = line_indent(input);
+    let mut values = Vec::new();
+    let mut next = input;
+    while seq_indent >= idt_size && line_indent(next) == seq_indent {
+        let item = &next[seq_indent..];
+        let item = match tuple((dash, space1))(item) {
+            Ok((item, _)) => item,
+            Err(_) => break,
+        };
+        let (pointer, value) = block_item(item, seq_indent)?;
+        values.push(value);
+        next = pointer;
+    }
+    if values.is_empty() {
+        return Err(yaml_error(input));
+    }
+    Ok((next, values))
+}
+
+// sequence label value: flow sequence, block sequence on following lines or single scalar
+fn sequence_value(input: &str, idt_size: usize) -> IResult<&str, Vec<YamlValue>> {
+    let (input, _) = space0(input)?;
+    if open_bracket(input).is_ok() {
+        let (input, values) = flow_sequence(input)?;
+        let (input, _) = end_of_value(input)?;
+        return Ok((input, values));
+    }
+    if let Ok((next, _)) = end_of_value(input) {
+        return block_sequence(next, idt_size);
+    }
+    let (input, value) = block_item(input, idt_size)?;
+    Ok((input, vec![value]))
 }
 
 // vss vss_path accept everything but space
 fn is_valid_pathname(chr: char) -> bool {
-    !chr.is_ascii_whitespace() && chr.is_ascii()
+    !chr.is_whitespace()
 }
 
-fn vss_path<'a>(input: &'a str) -> IResult<&'a str, String> {
+fn vss_path(input: &str) -> IResult<&str, String> {
     let (input, text) = take_while(is_valid_pathname)(input)?;
     Ok((input, text.to_owned()))
 }
@@ -130,123 +372,120 @@ fn is_valid_argument(chr: char) -> bool {
     chr.is_alphanumeric() || chr == '.'
 }
 
-fn argument<'a>(input: &'a str) -> IResult<&'a str, String> {
+fn argument(input: &str) -> IResult<&str, String> {
     let (input, text) = take_while(is_valid_argument)(input)?;
     Ok((input, text.to_owned()))
 }
 
-fn is_valid_numeric(chr: char) -> bool {
-    chr.is_numeric() || chr == '-' || chr == '.'
-}
-fn vss_number<'a>(input: &'a str) -> IResult<&'a str, String> {
-    let (input, _) = space0(input)?;
-    let (input, value) = take_while(is_valid_numeric)(input)?;
-    if value.len() == 0 {
-        return Err(nom::Err::Error(Error {
-            input: input,
-            code: ErrorKind::Eof,
-        }));
-    }
-    Ok((input, value.to_owned()))
-}
-
 // search for #include and build debug info and keep track on branch prefix
-fn include_line<'a>(input: &'a str) -> IResult<&'a str, VssType> {
+fn include_line<'a>(input: &'a str) -> IResult<&'a str, VssType<'a>> {
     let (input, _) = space0(input)?;
     let (input, _) = tag("#include")(input)?;
     let (input, _) = space1(input)?;
     let (input, filename) = vss_path(input)?;
-    let (input, _) = space1(input)?;
-    let (input, prefix) = opt(vss_path)(input)?;
+    let (input, _) = space0(input)?;
+    let (input, prefix) = vss_path(input)?;
     let (input, _) = eol(input)?;
     let include = VssInclude {
-        filename: filename,
-        prefix: prefix,
+        filename,
+        prefix: if prefix.is_empty() {
+            None
+        } else {
+            Some(prefix)
+        },
     };
     Ok((input, VssType::Include(include)))
 }
 
 // continuous lines are regroup into one single line
-fn empty_line<'a>(input: &'a str) -> IResult<&'a str, VssType> {
+fn empty_line<'a>(input: &'a str) -> IResult<&'a str, VssType<'a>> {
     let (input, _) = eol(input)?;
     Ok((input, VssType::Empty()))
 }
 
 // comment line are removed, while keeping track of line vss_number
-fn comment_line<'a>(input: &'a str) -> IResult<&'a str, VssType> {
+fn comment_line<'a>(input: &'a str) -> IResult<&'a str, VssType<'a>> {
     let (input, _) = space0(input)?;
     let (input, _) = sharp(input)?;
     let (input, value) = not_line_ending(input)?;
     let (input, _) = eol(input)?;
-    Ok((input, VssType::Comment(value.to_string())))
+    Ok((input, VssType::Comment(value)))
 }
 
 // data line is anything with contend
-fn data_line<'a>(input: &'a str) -> IResult<&'a str, VssType> {
+fn data_line<'a>(input: &'a str) -> IResult<&'a str, VssType<'a>> {
     let (input, value) = not_line_ending(input)?;
     let (input, _) = eol(input)?;
-    Ok((input, VssType::Data(value.to_string())))
+    Ok((input, VssType::Data(value)))
 }
 
 // check for end of buffer
-pub fn eof_data<'a>(input: &'a str) -> IResult<&'a str, VssType> {
+pub fn eof_data<'a>(input: &'a str) -> IResult<&'a str, VssType<'a>> {
     let _ = eof(input)?;
     Ok(("", VssType::Eof()))
 }
 
 // get line return a share enum for every class of line
-pub fn get_line<'a>(input: &'a str, vss: &VssHandle) -> IResult<&'a str, ()> {
-    let (input, line) = alt((empty_line, include_line, comment_line, data_line, eof_data))(input)?;
-    vss.count.set(vss.count.get() + 1);
-    match line {
-        VssType::Include(include) => {
-            let mut vss_incl = VssHandle::new(
-                include.filename,
-                Some(vss.filename.dirname.clone()),
-                include.prefix,
-            );
-            vss_incl.data = vss.data.clone();
-
-            match vss_from_file(&vss_incl) {
-                Err(error) => {
-                    return Err(nom::Err::Error(Error {
-                        input: to_static_str(error.get_info()),
-                        code: ErrorKind::Eof,
-                    }))
-                }
-                Ok(_) => {}
-            }
-        }
-
-        VssType::Data(text) => {
-            let data = VssLine {
-                line: vss.count.get(),
-                text: text,
-                filename: vss.filename.clone(),
-            };
-            let mut vss_data = vss.data.try_borrow_mut().unwrap();
-            vss_data.lines.push(data);
-        }
+pub fn get_line<'a>(input: &'a str) -> IResult<&'a str, VssType<'a>> {
+    alt((empty_line, include_line, comment_line, data_line, eof_data))(input)
+}
 
-        // force an empty line at eof
-        VssType::Eof() => {
-            println!("Done file:{}", vss.filename.basename);
-        }
+// 'Label:' line opening a block node, label follows argument rule
+pub fn vss_node_label(line: &str) -> Option<&str> {
+    let result: IResult<&str, (&str, char, &str)> =
+        tuple((take_while1(is_valid_argument), colum, space0))(line);
+    match result {
+        Ok(("", (label, _, _))) => Some(label),
+        _ => None,
+    }
+}
 
-        // remove empty lines
-        VssType::Empty() => {}
+// 'Label: {' starts a flow map node
+pub fn vss_flow_start(text: &str) -> bool {
+    let result = tuple((space0, argument, colum, space0, char('{')))(text);
+    matches!(result, Ok((_, (_, label, _, _, _))) if !label.is_empty())
+}
 
-        // remove comments
-        VssType::Comment(_) => {}
+// flow map node 'Label: {type: sensor, datatype: float}' rewritten as block lines,
+// Ok(None) while its closing brace is still to come
+pub fn vss_flow_map(text: &str) -> Result<Option<Vec<String>>, String> {
+    let indent = &text[..line_indent(text)];
+    let (label, body) = match text.trim().split_once(':') {
+        Some((label, body)) => (label.trim(), body.trim_start()),
+        None => return Err("invalid flow map".to_string()),
+    };
+    // split on closing brace, anything following it can only be a comment
+    let (body, tail) = match flow_split(body, '}') {
+        Some(parts) if parts.len() == 2 => (&parts[0][1..], parts[1].trim()),
+        Some(_) => return Err("nested flow map not supported".to_string()),
+        None => return Ok(None),
+    };
+    if !tail.is_empty() && !tail.starts_with('#') {
+        return Err(format!("unexpected '{}' after flow map", tail));
     }
-    Ok((input, ()))
+    let mut lines = vec![format!("{}{}:", indent, label)];
+    for entry in flow_split(body, ',').unwrap_or_default() {
+        if entry.trim().is_empty() {
+            continue;
+        }
+        match entry.split_once(':') {
+            Some((key, value)) if !key.trim().is_empty() => lines.push(format!(
+                "{}  {}: {}",
+                indent,
+                yaml_unquote(key).unwrap_or_else(|| key.trim().to_string()),
+                value.trim()
+            )),
+            _ => return Err(format!("invalid flow map entry '{}'", entry.trim())),
+        }
+    }
+    Ok(Some(lines))
 }
 
 fn check_indent(input: &str, idt_size: usize) -> IResult<&str, usize> {
     let (input, spaces) = take_while_m_n(0, idt_size, |c| c == ' ')(input)?;
     if idt_size != spaces.len() {
         let err = nom::Err::Error(Error {
-            input: input,
+            input,
             code: ErrorKind::Fail,
         });
         return Err(err);
@@ -258,7 +497,7 @@ fn check_indent(input: &str, idt_size: usize) -> IResult<&str, usize> {
 fn vss_label(input: &str) -> IResult<&str, (String, usize)> {
     let (input, label) = argument(input)?;
     let (input, _) = colum(input)?;
-    let (input, _) = eol(input)?;
+    let (input, _) = end_of_value(input)?;
     let (_, indent) = space1(input)?;
     Ok((input, (label.to_string(), indent.len())))
 }
@@ -276,31 +515,6 @@ pub fn get_one_line(input: &str) -> IResult<&str, String> {
     Ok((input, value.to_string()))
 }
 
-// get data from an indented line
-fn text_indent(input: &str, idt_size: usize) -> IResult<&str, String> {
-    let (input, _) = check_indent(input, idt_size)?;
-    let (input, value) = not_line_ending(input)?;
-    let (input, _) = eol(input)?;
-    Ok((input, value.to_string()))
-}
-
-// return a block of indented line as a vector or string
-fn many_indent_lines(mut input: &str, idt_size: usize) -> IResult<&str, Vec<String>> {
-    let mut result: Vec<String> = Vec::new();
-    loop {
-        match text_indent(input, idt_size) {
-            Ok((pointer, value)) => {
-                input = pointer;
-                result.push(value);
-            }
-            Err(_) => {
-                break;
-            }
-        }
-    }
-    Ok((input, result))
-}
-
 // search for a tag (word+':') within an indexed line
 fn search_indent_tag<'a>(
     input: &'a str,
@@ -328,21 +542,20 @@ fn search_indent_tag<'a>(
 fn vss_datatype(input: &str, idt_size: usize) -> IResult<&str, VssElement> {
     let label = "datatype:";
     let (input, (start, _)) = search_indent_tag(input, label, idt_size)?;
-    let (input, value) = argument(input)?;
-    let (input, _) = space0(input)?;
-    let (input, array_opt) = opt(tag("[]"))(input)?;
-    let (input, _) = space0(input)?;
-    let (input, _) = eol(input)?;
-    match VssValueType::from_str(value.as_str()) {
+    let (input, value) = scalar_value(input, idt_size)?;
+    let (value, is_array) = match value.strip_suffix("[]") {
+        Some(value) => (value.trim_end(), true),
+        None => (value.as_str(), false),
+    };
+    match VssValueType::from_str(value) {
+        Err(_) if vss_struct_reference(value) => {
+            Ok((input, VssElement::DataStruct(value.to_string())))
+        }
         Err(error) => Err(afb_to_nom_error(start, &error)),
         Ok(value) => {
-            let is_array = match array_opt {
-                Some(_) => true,
-                None => false,
-            };
             let data_type = VssDataType {
                 is_type: value,
-                is_array: is_array,
+                is_array,
             };
             Ok((input, VssElement::DataType(data_type)))
         }
@@ -353,283 +566,297 @@ fn vss_datatype(input: &str, idt_size: usize) -> IResult<&str, VssElement> {
 fn vss_objtype(input: &str, idt_size: usize) -> IResult<&str, VssObjectType> {
     let label = "type:";
     let (input, (start, _)) = search_indent_tag(input, label, idt_size)?;
-    let (input, value) = argument(input)?;
-    let (input, _) = space0(input)?;
-    let (input, _) = eol(input)?;
+    let (input, value) = scalar_value(input, idt_size)?;
     match VssObjectType::from_str(value.as_str()) {
         Err(error) => Err(afb_to_nom_error(start, &error)),
         Ok(value) => Ok((input, value)),
     }
 }
 
+// unit validity is checked against context unit registry once object is built
 fn vss_unit(input: &str, idt_size: usize) -> IResult<&str, VssElement> {
     let label = "unit:";
-    let (input, (start, _)) = search_indent_tag(input, label, idt_size)?;
-    let (input, value) = vss_path(input)?;
-    let (input, _) = eol(input)?;
-    match VssUnit::from_str(value.as_str()) {
-        Err(error) => Err(afb_to_nom_error(start, &error)),
-        Ok(value) => Ok((input, VssElement::ObjUnit(value))),
+    let (input, _) = search_indent_tag(input, label, idt_size)?;
+    let (input, value) = scalar_value(input, idt_size)?;
+    let unit = VssUnit::from_label(value.as_str());
+    Ok((input, VssElement::ObjUnit(unit)))
+}
+
+fn check_unit<'a>(
+    context: &VssContext,
+    start: &'a str,
+    unit: &VssUnit,
+    idt_size: usize,
+) -> IResult<&'a str, ()> {
+    if let VssUnit::None = unit {
+        return Ok((start, ()));
     }
+    if context.units.get(unit).is_none() {
+        let (_, (input, _)) = search_indent_tag(start, "unit:", idt_size)?;
+        eprintln!(
+            "parsing-error: unit '{}' not within unit registry",
+            unit.to_str()
+        );
+        return Err(nom::Err::Error(Error {
+            input,
+            code: ErrorKind::Verify,
+        }));
+    }
+    Ok((start, ()))
 }
 
 fn vss_arraysize(input: &str, idt_size: usize) -> IResult<&str, VssElement> {
     let label = "arraysize:";
     let (input, (start, _)) = search_indent_tag(input, label, idt_size)?;
-    let (input, value) = argument(input)?;
-    let (input, _) = eol(input)?;
-    match value.parse::<usize>() {
-        Err(_error) => Err(nom::Err::Error(Error {
+    let (input, value) = scalar_value(input, idt_size)?;
+    match yaml_integer(&value).and_then(|value| usize::try_from(value).ok()) {
+        None => Err(nom::Err::Error(Error {
             input: start,
             code: ErrorKind::AlphaNumeric,
         })),
-        Ok(value) => Ok((input, VssElement::DataArraySz(value))),
+        Some(value) => Ok((input, VssElement::DataArraySz(value))),
     }
 }
 
 fn vss_min(input: &str, idt_size: usize) -> IResult<&str, VssElement> {
     let label = "min:";
     let (input, (start, _)) = search_indent_tag(input, label, idt_size)?;
-    let (input, value) = vss_number(input)?;
-    let (input, _) = eol(input)?;
-    match value.parse::<i64>() {
-        Err(_error) => Err(nom::Err::Error(Error {
+    let (input, value) = scalar_value(input, idt_size)?;
+    match yaml_integer(&value) {
+        None => Err(nom::Err::Error(Error {
             input: start,
             code: ErrorKind::AlphaNumeric,
         })),
-        Ok(value) => Ok((input, VssElement::DataMinVal(value))),
+        Some(value) => Ok((input, VssElement::DataMinVal(value))),
     }
 }
 
 fn vss_max(input: &str, idt_size: usize) -> IResult<&str, VssElement> {
-    let label = "man:";
+    let label = "max:";
     let (input, (start, _)) = search_indent_tag(input, label, idt_size)?;
-    let (input, value) = vss_number(input)?;
-    let (input, _) = eol(input)?;
-    match value.parse::<i64>() {
-        Err(_error) => Err(nom::Err::Error(Error {
+    let (input, value) = scalar_value(input, idt_size)?;
+    match yaml_integer(&value) {
+        None => Err(nom::Err::Error(Error {
             input: start,
             code: ErrorKind::AlphaNumeric,
         })),
-        Ok(value) => Ok((input, VssElement::DataMaxVal(value))),
+        Some(value) => Ok((input, VssElement::DataMaxVal(value))),
     }
 }
 
-fn vss_aggregate<'a>(input: &'a str, idt_size: usize) -> IResult<&'a str, VssElement> {
-    let label = "aggregate";
-    let (input, _) = search_indent_tag(input, label, idt_size)?;
-    let (input, _) = space0(input)?;
-    let (input, value) = alt((tag_no_case("true"), tag_no_case("false")))(input)?;
-    let (input, _) = eol(input)?;
-    let result = if value != "true" { true } else { false };
-    Ok((input, VssElement::ObjAggregate(result)))
-}
-
-fn indent_dash<'a>(input: &'a str, idt_size: usize) -> IResult<&'a str, ()> {
-    let (input, _) = check_indent(input, idt_size)?;
-    let (input, _) = dash(input)?;
-    let (input, _) = space1(input)?;
-    Ok((input, ()))
+fn vss_aggregate(input: &str, idt_size: usize) -> IResult<&str, VssElement> {
+    let label = "aggregate:";
+    let (input, (start, _)) = search_indent_tag(input, label, idt_size)?;
+    let (input, value) = scalar_value(input, idt_size)?;
+    if value.eq_ignore_ascii_case("true") {
+        Ok((input, VssElement::ObjAggregate(true)))
+    } else if value.eq_ignore_ascii_case("false") {
+        Ok((input, VssElement::ObjAggregate(false)))
+    } else {
+        Err(nom::Err::Error(Error {
+            input: start,
+            code: ErrorKind::Tag,
+        }))
+    }
 }
 
-fn indent_string_or_number<'a>(input: &'a str, idt_size: usize) -> IResult<&'a str, String> {
-    let (input, _) = check_indent(input, idt_size)?;
-    let (input, value) = string_or_number(input)?;
-    Ok((input, value))
+fn vss_instantiate(input: &str, idt_size: usize) -> IResult<&str, VssElement> {
+    let label = "instantiate:";
+    let (input, (start, _)) = search_indent_tag(input, label, idt_size)?;
+    let (input, value) = scalar_value(input, idt_size)?;
+    if value.eq_ignore_ascii_case("true") {
+        Ok((input, VssElement::ObjInstantiate(true)))
+    } else if value.eq_ignore_ascii_case("false") {
+        Ok((input, VssElement::ObjInstantiate(false)))
+    } else {
+        Err(nom::Err::Error(Error {
+            input: start,
+            code: ErrorKind::Tag,
+        }))
+    }
 }
 
-// ["Left","Right"] [1,2] Prefix[val1,...valn]
-fn get_one_instance<'a>(input: &'a str) -> IResult<&'a str, VssInstance> {
-    let mut instance = VssInstance {
-        prefix: None,
-        array: Vec::new(),
-    };
-    let (input, _) = space0(input)?;
-    let (input, prefix) = opt(alphanumeric1)(input)?;
-    let (input, _) = space0(input)?;
-    match prefix {
-        None => {}
-        Some(value) => instance.prefix = Some(value.to_string()),
-    };
-    let (mut input, _) = open_bracket(input)?;
-    let input = loop {
-        let (next, _) = opt(quote)(input)?;
-        let (next, value) = opt(alphanumeric1)(next)?;
-        let (next, _) = opt(quote)(next)?;
-        match value {
-            Some(data) => instance.array.push(data.to_string()),
-            None => {}
-        };
-        let (next, value) = alt((close_bracket, comma))(next)?;
-        let (next, _) = space0(next)?;
-        if value == ']' {
-            break next;
-        };
-        input = next;
-    };
-    Ok((input, instance))
+// Prefix[val1,...valn] scalar, None for a plain instance name
+fn range_instance(text: &str) -> Option<VssInstance> {
+    let (prefix, list) = text.strip_suffix(']')?.split_once('[')?;
+    let prefix = prefix.trim();
+    Some(VssInstance {
+        prefix: if prefix.is_empty() {
+            None
+        } else {
+            Some(prefix.to_string())
+        },
+        array: flow_split(list, ',')?
+            .into_iter()
+            .map(|value| yaml_unquote(value).unwrap_or_else(|| value.trim().to_string()))
+            .filter(|value| !value.is_empty())
+            .collect(),
+    })
 }
 
-fn vss_instances<'a>(input: &'a str, idt_size: usize) -> IResult<&'a str, VssElement> {
+// Row[1,4] | ["Left","Right"] | a sequence of both, consecutive names build one instance
+fn vss_instances(input: &str, idt_size: usize) -> IResult<&str, VssElement> {
     let label = "instances:";
-    let mut instances: Vec<VssInstance> = Vec::new();
     let (input, _) = search_indent_tag(input, label, idt_size)?;
+    let (input, values) = sequence_value(input, idt_size)?;
 
-    let (input, instance) = opt(get_one_instance)(input)?;
-    let input = match instance {
-        Some(value) => {
-            instances.push(value);
-            input
-        }
-        None => {
-            let (input, _) = eol(input)?;
-            let (_, spaces) = space1(input)?;
-            let idt_size = spaces.len();
-
-            let mut next = input;
-            let next = loop {
-                let input = match indent_dash(next, idt_size) {
-                    Err(_error) => break next,
-                    Ok((pointer, _)) => pointer,
-                };
-                let (input, instance) = get_one_instance(input)?;
-                instances.push(instance);
-                next = input;
-            };
-            next
+    let mut instances: Vec<VssInstance> = Vec::new();
+    let mut names: Vec<String> = Vec::new();
+    let flush = |names: &mut Vec<String>, instances: &mut Vec<VssInstance>| {
+        if !names.is_empty() {
+            instances.push(VssInstance {
+                prefix: None,
+                array: std::mem::take(names),
+            });
         }
     };
-    Ok((input, VssElement::ObjInstances(instances)))
-}
-
-// value | [value1,value2,...]
-fn get_one_allowed<'a>(input: &'a str) -> IResult<&'a str, Vec<String>> {
-    let (input, _) = space0(input)?;
-    let mut allowed: Vec<String> = Vec::new();
-    let (mut input, bracket) = opt(open_bracket)(input)?;
-    let input = match bracket {
-        None => {
-            let (input, value) = string_or_number(input)?;
-            allowed.push(value);
-            input
+    for value in values {
+        match value {
+            YamlValue::Scalar(text) => match range_instance(&text) {
+                Some(instance) => {
+                    flush(&mut names, &mut instances);
+                    instances.push(instance);
+                }
+                None => names.push(text),
+            },
+            YamlValue::Sequence(values) => {
+                flush(&mut names, &mut instances);
+                let mut array = Vec::new();
+                for value in values {
+                    match value {
+                        YamlValue::Scalar(text) => array.push(text),
+                        YamlValue::Sequence(_) => return Err(yaml_error(input)),
+                    }
+                }
+                instances.push(VssInstance {
+                    prefix: None,
+                    array,
+                });
+            }
         }
-
-        Some(_) => loop {
-            let (next, value) = opt(string_or_number)(input)?;
-            match value {
-                Some(data) => allowed.push(data.to_string()),
-                None => {}
-            };
-            let (next, value) = alt((close_bracket, comma))(next)?;
-            let (next, _) = space0(next)?;
-            if value == ']' {
-                break next;
-            };
-            input = next;
-        },
-    };
-    Ok((input, allowed))
+    }
+    flush(&mut names, &mut instances);
+    Ok((input, VssElement::ObjInstances(instances)))
 }
 
+// value | [value1,value2,...] | block sequence of '- value' lines
 fn vss_array<'a>(input: &'a str, label: &str, idt_size: usize) -> IResult<&'a str, Vec<String>> {
-    let (input, _) = search_indent_tag(input, label, idt_size)?;
-    let mut allowed: Vec<String>;
-
-    let (input, result) = opt(get_one_allowed)(input)?;
-    let input = match result {
-        Some(value) => {
-            allowed = value;
-            input
-        }
-        None => {
-            allowed = Vec::new();
-            let (input, _) = space0(input)?;
-            let (input, _) = open_bracket(input)?;
-            let (input, _) = space0(input)?;
-            let (input, value) = opt(string_or_number)(input)?;
-            let input = match value {
-                None => input,
-                Some(value) => {
-                    allowed.push(value);
-                    let (input, _) = space0(input)?;
-                    let (input, _) = comma(input)?;
-                    let (input, _) = not_line_ending(input)?;
-                    input
-                }
-            };
-
-            let (input, _) = eol(input)?;
-            let (_, spaces) = space1(input)?;
-            let idt_size = spaces.len();
-
-            let mut next = input;
-            let next = loop {
-                let input = match indent_string_or_number(next, idt_size) {
-                    Err(_error) => break next,
-                    Ok((pointer, value)) => {
-                        allowed.push(value);
-                        pointer
-                    }
-                };
-                let (input, _) = not_line_ending(input)?;
-                let (input, _) = newline(input)?;
-                next = input;
-            };
-            next
+    let (input, (start, _)) = search_indent_tag(input, label, idt_size)?;
+    let (input, values) = sequence_value(input, idt_size)?;
+    let mut result = Vec::with_capacity(values.len());
+    for value in values {
+        match value {
+            YamlValue::Scalar(text) => result.push(text),
+            YamlValue::Sequence(_) => return Err(yaml_error(start)),
         }
-    };
-    Ok((input, allowed))
+    }
+    Ok((input, result))
 }
 
-fn vss_allowed<'a>(input: &'a str, idt_size: usize) -> IResult<&'a str, VssElement> {
+fn vss_allowed(input: &str, idt_size: usize) -> IResult<&str, VssElement> {
     let label = "allowed:";
     let (input, values) = vss_array(input, label, idt_size)?;
     Ok((input, VssElement::DataAllowed(values)))
 }
 
-fn vss_default<'a>(input: &'a str, idt_size: usize) -> IResult<&'a str, VssElement> {
+// former vpaths, full paths are never prefixed
+fn vss_fka(input: &str, idt_size: usize) -> IResult<&str, VssElement> {
+    let label = "fka:";
+    let (input, values) = vss_array(input, label, idt_size)?;
+    Ok((input, VssElement::ObjFka(values)))
+}
+
+fn vss_default(input: &str, idt_size: usize) -> IResult<&str, VssElement> {
     let label = "default:";
     let (input, values) = vss_array(input, label, idt_size)?;
     Ok((input, VssElement::DataDefault(values)))
 }
 
 // eat a block until indentation stop and check for empty new line
-fn ignore_indent_block<'a>(input: &'a str, idt_size: usize) -> IResult<&'a str, ()> {
+fn ignore_indent_block(input: &str, idt_size: usize) -> IResult<&str, ()> {
     let mut start = input;
-    loop {
-        match check_indent(start, idt_size) {
-            Ok((input, _)) => {
-                let (input, _) = not_line_ending(input)?;
-                let (input, _) = eol(input)?;
-                start = input;
-            }
-            Err(_) => break,
-        };
+    while let Ok((input, _)) = check_indent(start, idt_size) {
+        let (input, _) = not_line_ending(input)?;
+        let (input, _) = eol(input)?;
+        start = input;
     }
     let (input, _) = opt(eol)(start)?;
     Ok((input, ()))
 }
 
-fn get_block_indent<'a>(
-    input: &'a str,
-    label: &str,
-    idt_size: usize,
-) -> IResult<&'a str, VssElement> {
-    let (input, (_, idt_new)) = search_indent_tag(input, label, idt_size)?;
-    let (input, value) = not_line_ending(input)?;
-    let (input, _) = eol(input)?;
+fn get_block_indent<'a>(input: &'a str, label: &str, idt_size: usize) -> IResult<&'a str, String> {
+    let (input, _) = search_indent_tag(input, label, idt_size)?;
+    text_value(input, idt_size)
+}
 
-    let (input, mut result) = many_indent_lines(input, idt_new)?;
-    result.insert(0, value.to_string());
+fn is_valid_locale(chr: char) -> bool {
+    chr.is_ascii_alphanumeric() || chr == '_' || chr == '-'
+}
 
-    Ok((input, VssElement::ObjDescription(result.join(" "))))
+// localized label: description.fr: comment.fr: name.fr:
+fn l10n_label(input: &str) -> IResult<&str, (&str, &str)> {
+    let (input, field) = alt((tag("description"), tag("comment"), tag("name")))(input)?;
+    let (input, _) = char('.')(input)?;
+    let (input, locale) = take_while1(is_valid_locale)(input)?;
+    let (input, _) = colum(input)?;
+    let (input, _) = space0(input)?;
+    Ok((input, (field, locale)))
 }
 
-fn vss_description<'a>(input: &'a str, idt_size: usize) -> IResult<&'a str, VssElement> {
-    get_block_indent(input, "description:", idt_size)
+// collect every localized label of the indentation block
+fn vss_l10n(input: &str, idt_size: usize) -> IResult<&str, VssElement> {
+    let mut result: Vec<VssL10n> = Vec::new();
+    let mut next = input;
+    while let Ok((start, _)) = check_indent(next, idt_size) {
+        let (field, locale, value) = match l10n_label(start) {
+            Ok((pointer, (field, locale))) => {
+                let (pointer, value) = text_value(pointer, idt_size)?;
+                next = pointer;
+                (field, locale, value)
+            }
+            Err(_) => {
+                let (pointer, _) = not_line_ending(start)?;
+                let (pointer, _) = eol(pointer)?;
+                next = pointer;
+                continue;
+            }
+        };
+
+        let idx = match result.iter().position(|l10n| l10n.locale == locale) {
+            Some(idx) => idx,
+            None => {
+                result.push(VssL10n::new(locale));
+                result.len() - 1
+            }
+        };
+        let l10n = &mut result[idx];
+        match field {
+            "name" => l10n.name = Some(value),
+            "comment" => l10n.comment = Some(value),
+            _ => l10n.description = Some(value),
+        }
+    }
+
+    if result.is_empty() {
+        return Err(nom::Err::Error(Error {
+            input,
+            code: ErrorKind::Fail,
+        }));
+    }
+    Ok((input, VssElement::ObjL10n(result)))
 }
-fn vss_comment<'a>(input: &'a str, idt_size: usize) -> IResult<&'a str, VssElement> {
-    get_block_indent(input, "comment:", idt_size)
+
+fn vss_description(input: &str, idt_size: usize) -> IResult<&str, VssElement> {
+    let (input, value) = get_block_indent(input, "description:", idt_size)?;
+    Ok((input, VssElement::ObjDescription(value)))
+}
+fn vss_comment(input: &str, idt_size: usize) -> IResult<&str, VssElement> {
+    let (input, value) = get_block_indent(input, "comment:", idt_size)?;
+    Ok((input, VssElement::ObjComment(value)))
+}
+fn vss_deprecation(input: &str, idt_size: usize) -> IResult<&str, VssElement> {
+    let (input, value) = get_block_indent(input, "deprecation:", idt_size)?;
+    Ok((input, VssElement::ObjDeprecation(value)))
 }
 
 // equivalent to permutation with indentation support
@@ -650,7 +877,7 @@ fn get_indent_objects(
                     ErrorKind::Fail => {}
                     _ => {
                         return Err(nom::Err::Error(Error {
-                            input: input,
+                            input,
                             code: ErrorKind::Verify,
                         }));
                     }
@@ -662,13 +889,25 @@ fn get_indent_objects(
     Ok((input, result))
 }
 
+// labels are checked against node type, then against target version profile
 fn check_authorized_labels<'a>(
+    context: &VssContext,
+    vpath: &str,
     mut start: &'a str,
     idt_size: usize,
     mut labels: Vec<&'static str>,
 ) -> IResult<&'a str, ()> {
-    let mut defaults: Vec<&'static str> = vec!["type", "deprecation", "description", "comment"];
+    let mut defaults: Vec<&'static str> = vec![
+        "type",
+        "deprecation",
+        "description",
+        "comment",
+        "fka",
+        "instantiate",
+    ];
     labels.append(&mut defaults);
+    // yaml mapping keys are unique, including localized ones
+    let mut seen: Vec<(&str, Option<&str>)> = Vec::new();
 
     let input = loop {
         let mut index = labels.iter();
@@ -677,7 +916,9 @@ fn check_authorized_labels<'a>(
             Err(_) => break start,
         };
 
-        let result = tuple((alphanumeric1, space0, colum))(input);
+        // localized labels (ex: description.fr:) are checked on their base label
+        let locale = opt(tuple((char('.'), take_while1(is_valid_locale))));
+        let result = tuple((alphanumeric1, locale, space0, colum))(input);
         let label = match result {
             Err(_) => {
                 let (input, _) = not_line_ending(input)?;
@@ -685,20 +926,49 @@ fn check_authorized_labels<'a>(
                 start = input;
                 continue;
             }
-            Ok((input, (label, _, _))) => {
-                let (input, _) = not_line_ending(input)?;
-                let (input, _) = newline(input)?;
-                start = input;
-                label
+            Ok((pointer, (label, locale, _, _))) => {
+                let (pointer, _) = not_line_ending(pointer)?;
+                let (pointer, _) = newline(pointer)?;
+                start = pointer;
+                let key = (label, locale.map(|(_, locale)| locale));
+                if seen.contains(&key) {
+                    eprintln!("parsing-error: duplicated tag => '{}:'", label);
+                    return Err(nom::Err::Error(Error {
+                        input,
+                        code: ErrorKind::Satisfy,
+                    }));
+                }
+                seen.push(key);
+                match locale {
+                    None => label,
+                    Some(_) if label == "name" => continue,
+                    Some(_) if label == "description" || label == "comment" => label,
+                    Some(_) => {
+                        eprintln!("parsing-error: tag '{}:' cannot be localized", label);
+                        return Err(nom::Err::Error(Error {
+                            input,
+                            code: ErrorKind::Satisfy,
+                        }));
+                    }
+                }
             }
         };
 
-        match index.find(|&value| *value == label) {
-            Some(_) => {}
-            None => {
+        match (index.find(|&value| *value == label), vss_key_version(label)) {
+            (Some(_), Some(version)) if version > context.version => context.warning(
+                "vss-version-feature",
+                vpath,
+                format!(
+                    "'{}:' requires vss {} (target {})",
+                    label, version, context.version
+                ),
+                input,
+            ),
+            (Some(_), Some(_)) => {}
+            _ => {
                 eprintln!("parsing-error: unauthorized tag => '{}:'", label);
                 return Err(nom::Err::Error(Error {
-                    input: input,
+                    input,
                     code: ErrorKind::Satisfy,
                 }));
             }
@@ -709,19 +979,24 @@ fn check_authorized_labels<'a>(
 }
 
 fn vss_attribute<'a>(
-    locator: &Locator,
+    context: &VssContext,
+    head: &str,
     start: &'a str,
     label: String,
     vtype: VssObjectType,
     indent: usize,
 ) -> IResult<&'a str, VssObject> {
-    let mut object = VssAttribute::new(locator, start, label, vtype);
+    let mut object = VssAttribute::new(context, head, label, vtype);
     let (input, elements) = get_indent_objects(
         start,
         indent,
         vec![
             vss_description,
             vss_comment,
+            vss_deprecation,
+            vss_fka,
+            vss_instantiate,
+            vss_l10n,
             vss_datatype,
             vss_unit,
             vss_default,
@@ -733,81 +1008,120 @@ fn vss_attribute<'a>(
         match elem {
             VssElement::DataType(data) => {
                 object.datatype = data.is_type;
-                if let None = object.arraysize {
-                    if data.is_array {
-                        object.arraysize = Some(0)
-                    }
+                if object.arraysize.is_none() && data.is_array {
+                    object.arraysize = Some(0)
                 };
             }
+            VssElement::DataStruct(data) => vss_check_struct(context, &object.vpath, &data, head),
             VssElement::ObjUnit(data) => object.unit = data,
             VssElement::ObjDescription(data) => object.description = Some(data),
             VssElement::ObjComment(data) => object.comment = Some(data),
+            VssElement::ObjDeprecation(data) => object.deprecation = Some(data),
+            VssElement::ObjFka(data) => object.fka = data,
+            VssElement::ObjInstantiate(data) => object.instantiate = data,
+            VssElement::ObjL10n(data) => object.l10n = data,
             VssElement::DataDefault(data) => object.default = data,
             VssElement::DataAllowed(data) => object.allowed = data,
             _ => {
                 let _error = nom::Err::Error(Error {
-                    input: input,
+                    input,
                     code: ErrorKind::Satisfy,
                 });
             }
         }
     }
     check_authorized_labels(
+        context,
+        &object.vpath,
         start,
         indent,
-        vec!["arraysize", "datatype", "default", "allowed", "unit", "min", "max"],
+        vec![
+            "arraysize",
+            "datatype",
+            "default",
+            "allowed",
+            "unit",
+            "min",
+            "max",
+        ],
     )?;
+    check_unit(context, start, &object.unit, indent)?;
 
     Ok((input, VssObject::Attribute(object)))
 }
 
 fn vss_branch<'a>(
-    locator: &Locator,
+    context: &VssContext,
+    head: &str,
     start: &'a str,
     label: String,
     vtype: VssObjectType,
     indent: usize,
 ) -> IResult<&'a str, VssObject> {
-    let mut object = VssBranch::new(locator, start, label, vtype);
+    let mut object = VssBranch::new(context, head, label, vtype);
     let (input, elements) = get_indent_objects(
         start,
         indent,
-        vec![vss_description, vss_comment, vss_aggregate, vss_instances],
+        vec![
+            vss_description,
+            vss_comment,
+            vss_deprecation,
+            vss_fka,
+            vss_instantiate,
+            vss_l10n,
+            vss_aggregate,
+            vss_instances,
+        ],
     )?;
 
     for elem in elements {
         match elem {
             VssElement::ObjDescription(data) => object.description = Some(data),
             VssElement::ObjComment(data) => object.comment = Some(data),
+            VssElement::ObjDeprecation(data) => object.deprecation = Some(data),
+            VssElement::ObjFka(data) => object.fka = data,
+            VssElement::ObjInstantiate(data) => object.instantiate = data,
+            VssElement::ObjL10n(data) => object.l10n = data,
             VssElement::ObjAggregate(data) => object.aggregate = data,
             VssElement::ObjInstances(data) => object.instances = data,
             _ => {
                 return Err(nom::Err::Error(Error {
-                    input: input,
+                    input,
                     code: ErrorKind::Satisfy,
                 }))
             }
         }
     }
-    check_authorized_labels(start, indent, vec!["aggregate", "instances"])?;
+    check_authorized_labels(
+        context,
+        &object.vpath,
+        start,
+        indent,
+        vec!["aggregate", "instances"],
+    )?;
 
     Ok((input, VssObject::Branch(object)))
 }
 
 fn vss_sensor<'a>(
-    locator: &Locator,
+    context: &VssContext,
+    head: &str,
     start: &'a str,
     label: String,
     vtype: VssObjectType,
     indent: usize,
 ) -> IResult<&'a str, VssObject> {
-    let mut object = VssSensor::new(locator, start, label, vtype);
+    let mut object = VssSensor::new(context, head, label, vtype);
     let (input, elements) = get_indent_objects(
         start,
         indent,
         vec![
             vss_description,
             vss_comment,
+            vss_deprecation,
+            vss_fka,
+            vss_instantiate,
+            vss_l10n,
             vss_datatype,
             vss_arraysize,
             vss_default,
@@ -822,15 +1136,18 @@ fn vss_sensor<'a>(
         match elem {
             VssElement::DataType(data) => {
                 object.datatype = data.is_type;
-                if let None = object.arraysize {
-                    if data.is_array {
-                        object.arraysize = Some(0)
-                    }
+                if object.arraysize.is_none() && data.is_array {
+                    object.arraysize = Some(0)
                 };
             }
+            VssElement::DataStruct(data) => vss_check_struct(context, &object.vpath, &data, head),
             VssElement::ObjUnit(data) => object.unit = data,
             VssElement::ObjDescription(data) => object.description = Some(data),
             VssElement::ObjComment(data) => object.comment = Some(data),
+            VssElement::ObjDeprecation(data) => object.deprecation = Some(data),
+            VssElement::ObjFka(data) => object.fka = data,
+            VssElement::ObjInstantiate(data) => object.instantiate = data,
+            VssElement::ObjL10n(data) => object.l10n = data,
             VssElement::DataMinVal(data) => object.min = Some(data),
             VssElement::DataMaxVal(data) => object.max = Some(data),
             VssElement::DataDefault(data) => object.default = data,
@@ -846,23 +1163,46 @@ fn vss_sensor<'a>(
     }
 
     check_authorized_labels(
+        context,
+        &object.vpath,
         start,
         indent,
-        vec!["arraysize", "datatype","default", "allowed", "unit", "min", "max"],
+        vec![
+            "arraysize",
+            "datatype",
+            "default",
+            "allowed",
+            "unit",
+            "min",
+            "max",
+        ],
     )?;
+    check_unit(context, start, &object.unit, indent)?;
 
     Ok((input, VssObject::Sensor(object)))
 }
 
+// actuators share sensor's labels, only the access mode differs
+fn vss_actuator<'a>(
+    context: &VssContext,
+    head: &str,
+    start: &'a str,
+    label: String,
+    vtype: VssObjectType,
+    indent: usize,
+) -> IResult<&'a str, VssObject> {
+    match vss_sensor(context, head, start, label, vtype, indent)? {
+        (input, VssObject::Sensor(object)) => Ok((input, VssObject::Actuator(object))),
+        (input, object) => Ok((input, object)),
+    }
+}
+
 // search for vss object data with &str buffer
-pub fn vss_object<'a>(locator: &Locator, input: &'a str) -> IResult<&'a str, VssObject> {
+pub fn vss_object<'a>(context: &VssContext, input: &'a str) -> IResult<&'a str, VssObject> {
     let (start, (label, indent)) = vss_label(input)?;
 
-    // extract prefix from locator
-    let vss_data = locator.data.try_borrow().unwrap();
-    let location = location(locator, start.len());
-    let line = &vss_data.lines[location];
-    let label = match &line.filename.prefix {
+    // extract prefix from current file
+    let label = match &context.filename.prefix {
         Some(prefix) => format!("{}.{}", prefix.clone(), label),
         None => label,
     };
@@ -880,10 +1220,10 @@ pub fn vss_object<'a>(locator: &Locator, input: &'a str) -> IResult<&'a str, Vss
     };
 
     let (input, object) = match vtype {
-        VssObjectType::Attribute => vss_attribute(locator, start, label, vtype, indent)?,
-        VssObjectType::Branch => vss_branch(locator, start, label, vtype, indent)?,
-        VssObjectType::Sensor => vss_sensor(locator, start, label, vtype, indent)?,
-        VssObjectType::Actuator => vss_sensor(locator, start, label, vtype, indent)?,
+        VssObjectType::Attribute => vss_attribute(context, input, start, label, vtype, indent)?,
+        VssObjectType::Branch => vss_branch(context, input, start, label, vtype, indent)?,
+        VssObjectType::Sensor => vss_sensor(context, input, start, label, vtype, indent)?,
+        VssObjectType::Actuator => vss_actuator(context, input, start, label, vtype, indent)?,
         VssObjectType::Unset => {
             panic!("(hoop) internal error object type not set")
         }
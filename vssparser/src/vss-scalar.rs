@@ -0,0 +1,213 @@
+/*
+ * Copyright (C) 2015-2023 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
+ * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
+ *
+ * yaml scalar helpers shared by vspec parser, formatter and side file reader (units, lint config, l10n)
+ *   - quoted scalars with yaml escapes, plain scalars with trailing comments
+ *   - flow collections split outside of quotes with bracket balance check
+ */
+
+use nom::{
+    branch::alt,
+    bytes::complete::take_while,
+    character::complete::char,
+    error::{Error, ErrorKind},
+    IResult,
+};
+
+fn scalar_error(input: &str) -> nom::Err<Error<&str>> {
+    nom::Err::Error(Error {
+        input,
+        code: ErrorKind::Verify,
+    })
+}
+
+pub fn quote(input: &str) -> IResult<&str, char> {
+    let (input, char) = alt((char('"'), char('\'')))(input)?;
+    Ok((input, char))
+}
+
+// run of unescaped characters within double quoted text
+fn is_valid_string1(chr: char) -> bool {
+    chr != '"' && chr != '\\' && chr != '\n'
+}
+// run of characters within single quoted text, quote is escaped by doubling it
+fn is_valid_string2(chr: char) -> bool {
+    chr != '\'' && chr != '\n'
+}
+
+// count leading spaces
+pub fn line_indent(input: &str) -> usize {
+    input.len() - input.trim_start_matches(' ').len()
+}
+
+// plain scalar stops on a '#' starting a comment (line start or preceded by a blank)
+pub fn plain_text(line: &str) -> &str {
+    let end = line
+        .char_indices()
+        .find(|(idx, chr)| *chr == '#' && (*idx == 0 || line[..*idx].ends_with([' ', '\t'])))
+        .map(|(idx, _)| idx)
+        .unwrap_or(line.len());
+    line[..end].trim()
+}
+
+// remove ' #comment' outside of quoted strings
+pub fn strip_comment(text: &str) -> &str {
+    let mut quote: Option<char> = None;
+    let mut previous = ' ';
+    let mut escape = false;
+    for (idx, chr) in text.char_indices() {
+        match quote {
+            Some('"') if chr == '\\' && !escape => {
+                escape = true;
+                previous = chr;
+                continue;
+            }
+            Some(value) if chr == value && !escape => quote = None,
+            Some(_) => {}
+            None if chr == '"' || chr == '\'' => quote = Some(chr),
+            None if chr == '#' && previous.is_whitespace() => return text[..idx].trim_end(),
+            None => {}
+        }
+        escape = false;
+        previous = chr;
+    }
+    text.trim_end()
+}
+
+// \n \t \" \\ \xHH \uHHHH \UHHHHHHHH escapes of double quoted text
+fn escape_char(input: &str) -> IResult<&str, Option<char>> {
+    let (input, _) = char('\\')(input)?;
+    let mut chars = input.chars();
+    let code = match chars.next() {
+        Some(code) => code,
+        None => return Err(scalar_error(input)),
+    };
+    let size = match code {
+        'x' => 2,
+        'u' => 4,
+        'U' => 8,
+        // escaped line break joins lines without space
+        '\n' => {
+            let next = chars.as_str();
+            return Ok((next.trim_start_matches(' '), None));
+        }
+        _ => {
+            let value = match code {
+                'n' => '\n',
+                't' | '\t' => '\t',
+                'r' => '\r',
+                '0' => '\0',
+                'a' => '\x07',
+                'b' => '\x08',
+                'e' => '\x1b',
+                'f' => '\x0c',
+                'v' => '\x0b',
+                '"' | '\\' | '/' | ' ' => code,
+                _ => return Err(scalar_error(input)),
+            };
+            return Ok((chars.as_str(), Some(value)));
+        }
+    };
+    let next = chars.as_str();
+    let value = next
+        .get(..size)
+        .and_then(|hexa| u32::from_str_radix(hexa, 16).ok())
+        .and_then(char::from_u32);
+    match value {
+        Some(value) => Ok((&next[size..], Some(value))),
+        None => Err(scalar_error(input)),
+    }
+}
+
+// quoted scalar, line breaks fold into one space when next line is indented deeper than idt_size
+pub fn quoted_text(input: &str, idt_size: usize) -> IResult<&str, String> {
+    let (mut input, quote) = quote(input)?;
+    let mut text = String::new();
+    loop {
+        let (next, run) = if quote == '"' {
+            take_while(is_valid_string1)(input)?
+        } else {
+            take_while(is_valid_string2)(input)?
+        };
+        text.push_str(run);
+        match next.chars().next() {
+            Some(chr) if chr == quote => {
+                if quote == '\'' && next[1..].starts_with('\'') {
+                    text.push('\'');
+                    input = &next[2..];
+                } else {
+                    break Ok((&next[1..], text));
+                }
+            }
+            Some('\\') => {
+                let (pointer, value) = escape_char(next)?;
+                if let Some(value) = value {
+                    text.push(value);
+                }
+                input = pointer;
+            }
+            Some('\n') if line_indent(&next[1..]) > idt_size => {
+                text.truncate(text.trim_end_matches([' ', '\t']).len());
+                text.push(' ');
+                input = next[1..].trim_start_matches(' ');
+            }
+            _ => break Err(scalar_error(next)),
+        }
+    }
+}
+
+// one scalar value, quotes are removed and escapes decoded, None on an invalid quoted scalar
+pub fn yaml_unquote(text: &str) -> Option<String> {
+    let text = text.trim();
+    if quote(text).is_err() {
+        return Some(text.to_string());
+    }
+    match quoted_text(text, 0) {
+        Ok((tail, value)) if tail.trim().is_empty() => Some(value),
+        _ => None,
+    }
+}
+
+// split on separator at depth 0 and outside quotes, None when brackets or quotes are left open
+// or closed without being opened
+pub fn flow_split(text: &str, separator: char) -> Option<Vec<&str>> {
+    let mut parts = Vec::new();
+    let mut quote: Option<char> = None;
+    let mut depth = 0;
+    let mut first = 0;
+    let mut escape = false;
+    for (idx, chr) in text.char_indices() {
+        match (quote, chr) {
+            (Some('"'), '\\') if !escape => {
+                escape = true;
+                continue;
+            }
+            (Some(value), _) if value == chr && !escape => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(chr),
+            (None, '[' | '{') => depth += 1,
+            (None, _) => {
+                if chr == ']' || chr == '}' {
+                    if depth == 0 {
+                        return None;
+                    }
+                    depth -= 1;
+                }
+                if chr == separator && depth == 0 {
+                    parts.push(&text[first..idx]);
+                    first = idx + chr.len_utf8();
+                }
+            }
+        }
+        escape = false;
+    }
+    if quote.is_some() || depth > 0 {
+        return None;
+    }
+    parts.push(&text[first..]);
+    Some(parts)
+}
@@ -0,0 +1,238 @@
+/*
+ * Copyright (C) 2015-2023 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
+ * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
+ *
+ * spec statistics: counts per node type/datatype, unit histogram per unit class, tree depth,
+ * instance templates, undocumented nodes and per-file contribution
+ *   - depth counts vpath segments (Vehicle=1, Vehicle.Speed=2)
+ *   - template nodes sit below a branch holding instances, instantiated nodes are the ones
+ *     vss_expand_instances creates from them (instance branches included)
+ */
+
+use std::collections::BTreeMap;
+
+use crate::expand::*;
+use crate::export::*;
+use crate::index::*;
+use crate::types::*;
+use crate::units::*;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VssStats {
+    pub nodes: usize,
+    pub vtypes: BTreeMap<String, usize>,
+    pub datatypes: BTreeMap<String, usize>,
+    // unit class => unit label => count, units missing from registry go under "unknown"
+    pub units: BTreeMap<String, BTreeMap<String, usize>>,
+    pub max_depth: usize,
+    pub avg_depth: f64,
+    pub instance_branches: usize,
+    pub template_nodes: usize,
+    pub instantiated_nodes: usize,
+    pub deprecated: usize,
+    pub undocumented: Vec<String>,
+    // (file path, nodes defined within), files follow spec.files order
+    pub files: Vec<(String, usize)>,
+    pub errors: usize,
+    pub warnings: usize,
+}
+
+fn stats_map(out: &mut String, label: &str, map: &BTreeMap<String, usize>, indent: &str) {
+    let values = map
+        .iter()
+        .map(|(key, count)| format!("{}: {}", json_string(key), count))
+        .collect::<Vec<String>>();
+    out.push_str(&format!(
+        "{}{}: {{{}}}",
+        indent,
+        json_string(label),
+        values.join(", ")
+    ));
+}
+
+impl VssStats {
+    pub fn new(spec: &VssSpec, units: &VssUnitPool) -> Self {
+        let index = VssIndex::new(spec);
+        let mut stats = VssStats {
+            nodes: index.len(),
+            files: spec
+                .files
+                .iter()
+                .map(|file| (format!("{}{}", file.dirname, file.basename), 0))
+                .collect(),
+            ..Default::default()
+        };
+
+        let mut depths = 0;
+        let mut templates: Vec<&str> = Vec::new();
+        for node in index.iter() {
+            let vpath = node.vpath();
+            *stats
+                .vtypes
+                .entry(node.vtype().to_str().to_string())
+                .or_default() += 1;
+            if let Some(datatype) = node.datatype() {
+                let label = match node.arraysize() {
+                    Some(_) => format!("{}[]", datatype.to_str()),
+                    None => datatype.to_str().to_string(),
+                };
+                *stats.datatypes.entry(label).or_default() += 1;
+            }
+            if let Some(unit) = node.unit() {
+                if *unit != VssUnit::None {
+                    let class = match units.get(unit) {
+                        Some(info) => info.domain.to_str().to_string(),
+                        None => "unknown".to_string(),
+                    };
+                    *stats
+                        .units
+                        .entry(class)
+                        .or_default()
+                        .entry(unit.to_str().to_string())
+                        .or_default() += 1;
+                }
+            }
+
+            let depth = vpath.split('.').count();
+            depths += depth;
+            stats.max_depth = stats.max_depth.max(depth);
+
+            // index is sorted, a template subtree directly follows its instance branch
+            while let Some(template) = templates.last() {
+                if vpath.starts_with(template) && vpath[template.len()..].starts_with('.') {
+                    break;
+                }
+                templates.pop();
+            }
+            if !templates.is_empty() {
+                stats.template_nodes += 1;
+            }
+            if let VssNode::Branch(obj) = node {
+                if !obj.instances.is_empty() {
+                    stats.instance_branches += 1;
+                    templates.push(vpath);
+                }
+            }
+
+            if node.deprecation().is_some() {
+                stats.deprecated += 1;
+            }
+            match node.description() {
+                Some(value) if !value.trim().is_empty() => {}
+                _ => stats.undocumented.push(vpath.to_string()),
+            }
+            if let Some((_, count)) = stats.files.get_mut(node.location().file as usize) {
+                *count += 1;
+            }
+        }
+        if stats.nodes > 0 {
+            stats.avg_depth = depths as f64 / stats.nodes as f64;
+        }
+        if stats.instance_branches > 0 {
+            let expanded = VssIndex::new(&vss_expand_instances(spec)).len();
+            stats.instantiated_nodes =
+                (expanded + stats.template_nodes).saturating_sub(stats.nodes);
+        }
+        stats.errors = spec
+            .diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.level == VssLevel::Error)
+            .count();
+        stats.warnings = spec.diagnostics.len() - stats.errors;
+        stats
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut out = format!("nodes: {}\n", self.nodes);
+        for (label, count) in &self.vtypes {
+            out.push_str(&format!("  {}: {}\n", label, count));
+        }
+        out.push_str("datatypes:\n");
+        for (label, count) in &self.datatypes {
+            out.push_str(&format!("  {}: {}\n", label, count));
+        }
+        out.push_str("units:\n");
+        for (class, units) in &self.units {
+            let total: usize = units.values().sum();
+            out.push_str(&format!("  {}: {}\n", class, total));
+            for (label, count) in units {
+                out.push_str(&format!("    {}: {}\n", label, count));
+            }
+        }
+        out.push_str(&format!(
+            "depth: max {} avg {:.2}\n",
+            self.max_depth, self.avg_depth
+        ));
+        out.push_str(&format!(
+            "instances: {} branch(es), {} template node(s), {} instantiated node(s)\n",
+            self.instance_branches, self.template_nodes, self.instantiated_nodes
+        ));
+        out.push_str(&format!("deprecated: {}\n", self.deprecated));
+        out.push_str(&format!("undocumented: {}\n", self.undocumented.len()));
+        out.push_str("files:\n");
+        for (filename, count) in &self.files {
+            out.push_str(&format!("  {}: {}\n", filename, count));
+        }
+        out.push_str(&format!(
+            "diagnostics: {} error(s), {} warning(s)\n",
+            self.errors, self.warnings
+        ));
+        out
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut out = format!("{{\n  \"nodes\": {},\n", self.nodes);
+        stats_map(&mut out, "vtypes", &self.vtypes, "  ");
+        out.push_str(",\n");
+        stats_map(&mut out, "datatypes", &self.datatypes, "  ");
+        out.push_str(",\n  \"units\": {");
+        for (idx, (class, units)) in self.units.iter().enumerate() {
+            out.push_str(if idx == 0 { "\n" } else { ",\n" });
+            stats_map(&mut out, class, units, "    ");
+        }
+        if !self.units.is_empty() {
+            out.push_str("\n  ");
+        }
+        out.push_str("},\n");
+        out.push_str(&format!("  \"max_depth\": {},\n", self.max_depth));
+        out.push_str(&format!("  \"avg_depth\": {:.2},\n", self.avg_depth));
+        out.push_str(&format!(
+            "  \"instance_branches\": {},\n",
+            self.instance_branches
+        ));
+        out.push_str(&format!("  \"template_nodes\": {},\n", self.template_nodes));
+        out.push_str(&format!(
+            "  \"instantiated_nodes\": {},\n",
+            self.instantiated_nodes
+        ));
+        out.push_str(&format!("  \"deprecated\": {},\n", self.deprecated));
+        let undocumented = self
+            .undocumented
+            .iter()
+            .map(|vpath| json_string(vpath))
+            .collect::<Vec<String>>();
+        out.push_str(&format!(
+            "  \"undocumented\": [{}],\n",
+            undocumented.join(", ")
+        ));
+        let files = self
+            .files
+            .iter()
+            .map(|(filename, count)| {
+                format!(
+                    "{{\"file\": {}, \"nodes\": {}}}",
+                    json_string(filename),
+                    count
+                )
+            })
+            .collect::<Vec<String>>();
+        // array rather than map, a file included twice holds two entries
+        out.push_str(&format!("  \"files\": [{}],\n", files.join(", ")));
+        out.push_str(&format!("  \"errors\": {},\n", self.errors));
+        out.push_str(&format!("  \"warnings\": {}\n}}\n", self.warnings));
+        out
+    }
+}
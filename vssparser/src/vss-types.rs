@@ -7,11 +7,13 @@
  *
  */
 
-//use crate::utils::*;
-use core::cell::{Cell, RefCell};
-use std::rc::Rc;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::str::FromStr;
 
+//use crate::utils::*;
 use crate::units::*;
+use crate::version::*;
 
 #[derive(Debug)]
 pub struct AfbError {
@@ -34,10 +36,7 @@ impl AfbError {
         to_static_str(text)
     }
     pub fn new(uid: &'static str, info: String) -> Self {
-        AfbError {
-            uid: uid,
-            info: info,
-        }
+        AfbError { uid, info }
     }
     pub fn get_info(&self) -> String {
         self.info.clone()
@@ -48,44 +47,35 @@ pub fn to_static_str(value: String) -> &'static str {
     Box::leak(value.into_boxed_str())
 }
 
-// search line number and filename from &str buffer index
-pub fn location(locator: &Locator, tail: usize) -> usize {
-    let head = if tail < locator.count {
-        locator.count - tail
-    } else {
-        0
-    };
-    for idx in 0..locator.table.len() {
-        if locator.table[idx] > head {
-            return idx;
-        }
-    }
-    return locator.table.len();
+// diagnostic uid, parser uids are borrowed while deserialized ones are owned
+pub type VssUid = Cow<'static, str>;
+
+// how a file entered the spec: main file, #include (location of #include line and prefix
+// as written there) or overlay
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum VssFileOrigin {
+    Root,
+    Include {
+        location: VssLocation,
+        prefix: Option<String>,
+    },
+    Overlay,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Filename {
     pub basename: String,
     pub dirname: String,
+    // full vpath prefix, composed from every enclosing #include
     pub prefix: Option<String>,
+    pub origin: VssFileOrigin,
+    // crc32 of file content as read by the loader, derived files use it to detect stale sources
+    pub checksum: u32,
 }
 
-pub struct VssLine {
-    pub line: u32,
-    pub filename: Rc<Filename>,
-    pub text: String,
-}
-
-pub struct VssData {
-    pub lines: Vec<VssLine>,
-}
-
-pub struct VssHandle {
-    pub count: Cell<u32>,
-    pub filename: Rc<Filename>,
-    pub data: Rc<RefCell<VssData>>,
-}
-
-impl VssHandle {
+impl Filename {
     pub fn new(filename: String, dirname: Option<String>, prefix: Option<String>) -> Self {
         // if absolute path then ignore dirname directory
         let dirname = if !filename.starts_with("/") {
@@ -98,9 +88,16 @@ impl VssHandle {
         let mut path = filename.split("/").collect::<Vec<&str>>();
         let basename = path.pop().unwrap();
         let dirname = match dirname {
-            Some(dirname) => dirname + "/" + path.join("/").as_str() + "/",
+            Some(dirname) => {
+                // collapse repeated '/' (include//body//lights///Fog.vspec)
+                let mut dirname = dirname + "/" + path.join("/").as_str() + "/";
+                while dirname.contains("//") {
+                    dirname = dirname.replace("//", "/");
+                }
+                dirname
+            }
             None => {
-                if path.len() > 0 {
+                if !path.is_empty() {
                     path.join("/") + "/"
                 } else {
                     "./".to_string()
@@ -108,40 +105,201 @@ impl VssHandle {
             }
         };
 
-        VssHandle {
-            count: Cell::new(0),
-            filename: Rc::new(Filename {
-                dirname: dirname,
-                basename: basename.to_string(),
-                prefix: prefix,
+        Filename {
+            dirname,
+            basename: basename.to_string(),
+            prefix,
+            origin: VssFileOrigin::Root,
+            checksum: 0,
+        }
+    }
+}
+
+// compact source position, file is an index within VssData/VssSpec files table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VssLocation {
+    pub file: u32,
+    pub line: u32,
+}
+
+// one vspec file, comments and empty lines are squeezed in place, parsed tokens borrow from buffer
+pub struct VssSource {
+    pub file: u32,
+    pub buffer: String,
+    // buffer offset of each kept line and its line number within original file
+    pub starts: Vec<u32>,
+    pub lines: Vec<u32>,
+    // buffer offset of each #include and included file index
+    pub includes: Vec<(u32, u32)>,
+    // buffer offset of node label lines with their trivia, plus trivia after last node
+    pub trivia: Vec<(u32, VssTrivia)>,
+    pub trailing: Option<VssTrivia>,
+}
+
+impl VssSource {
+    // search line number from &str buffer tail (binary search)
+    pub fn location(&self, tail: usize) -> VssLocation {
+        let head = self.buffer.len().saturating_sub(tail) as u32;
+        let idx = self.starts.partition_point(|start| *start <= head);
+        VssLocation {
+            file: self.file,
+            line: self.lines.get(idx.saturating_sub(1)).copied().unwrap_or(0),
+        }
+    }
+
+    // trivia of the node starting at buffer offset
+    pub fn trivia(&self, offset: u32) -> Option<VssTrivia> {
+        match self
+            .trivia
+            .binary_search_by_key(&offset, |(start, _)| *start)
+        {
+            Ok(idx) => Some(self.trivia[idx].1.clone()),
+            Err(_) => None,
+        }
+    }
+}
+
+// resolution of a vpath defined twice within main spec or within one overlay,
+// conflicting node types (branch vs sensor) are always an error
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VssDuplicates {
+    // error diagnostic, first definition is kept
+    Error,
+    // warning, first definition is kept
+    FirstWins,
+    // warning, last definition replaces previous one as an overlay does
+    LastWins,
+}
+
+impl FromStr for VssDuplicates {
+    type Err = AfbError;
+
+    fn from_str(value: &str) -> Result<Self, AfbError> {
+        match value.to_lowercase().as_str() {
+            "error" => Ok(VssDuplicates::Error),
+            "first" | "first-wins" => Ok(VssDuplicates::FirstWins),
+            "last" | "last-wins" => Ok(VssDuplicates::LastWins),
+            _ => Err(AfbError {
+                uid: "vss-duplicates-policy",
+                info: format!("policy:{} is not one of error|first-wins|last-wins", value),
             }),
-            data: Rc::new(RefCell::new(VssData { lines: Vec::new() })),
         }
     }
 }
 
+pub struct VssData {
+    pub files: Vec<Filename>,
+    pub sources: Vec<VssSource>,
+    // opt-in: keep comments and blank lines within parsed nodes
+    pub trivia: bool,
+    // searched when an #include is not found relative to including file
+    pub include_dirs: Vec<String>,
+    // overlay root files, applied in order on top of main file
+    pub overlays: Vec<u32>,
+    pub duplicates: VssDuplicates,
+    // target specification, newer features are reported as warnings
+    pub version: VssVersion,
+    // findings while reading files (encoding), reported first within spec diagnostics
+    pub diagnostics: Vec<VssDiagnostic>,
+}
+
+// root file, included files are pushed to data.files/data.sources while reading
+pub struct VssHandle {
+    pub data: VssData,
+}
+
+impl VssHandle {
+    pub fn new(filename: String, dirname: Option<String>, prefix: Option<String>) -> Self {
+        VssHandle {
+            data: VssData {
+                files: vec![Filename::new(filename, dirname, prefix)],
+                sources: Vec::new(),
+                trivia: false,
+                include_dirs: Vec::new(),
+                overlays: Vec::new(),
+                duplicates: VssDuplicates::Error,
+                version: VssVersion::latest(),
+                diagnostics: Vec::new(),
+            },
+        }
+    }
+
+    pub fn add_include_dir(&mut self, dirname: &str) {
+        self.data.include_dirs.push(dirname.to_string());
+    }
+
+    // overlay nodes replace main file nodes with the same vpath
+    pub fn add_overlay(&mut self, filename: String) {
+        let mut filename = Filename::new(filename, None, None);
+        filename.origin = VssFileOrigin::Overlay;
+        self.data.files.push(filename);
+        self.data.overlays.push(self.data.files.len() as u32 - 1);
+    }
+}
+
+// per file parsing context, each included file is parsed independently
+pub struct VssContext<'a> {
+    pub source: &'a VssSource,
+    pub filename: &'a Filename,
+    pub units: &'a VssUnitPool,
+    pub version: VssVersion,
+    // non fatal findings while parsing this file
+    pub diagnostics: RefCell<Vec<VssDiagnostic>>,
+}
+
+impl VssContext<'_> {
+    pub fn location(&self, input: &str) -> VssLocation {
+        self.source.location(input.len())
+    }
+
+    pub fn warning(&self, uid: &'static str, vpath: &str, info: String, input: &str) {
+        self.diagnostics.borrow_mut().push(VssDiagnostic::new(
+            VssLevel::Warning,
+            uid,
+            vpath,
+            info,
+            self.location(input),
+        ));
+    }
+
+    pub fn error(&self, uid: &'static str, vpath: &str, info: String, input: &str) {
+        self.diagnostics.borrow_mut().push(VssDiagnostic::new(
+            VssLevel::Error,
+            uid,
+            vpath,
+            info,
+            self.location(input),
+        ));
+    }
+}
+
 #[derive(Debug)]
 pub struct VssInclude {
     pub filename: String,
     pub prefix: Option<String>,
 }
 
-pub enum VssType {
-    Comment(String),
+pub enum VssType<'a> {
+    Comment(&'a str),
     Include(VssInclude),
-    Data(String),
+    Data(&'a str),
     Empty(),
     Eof(),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum VssObject {
     Branch(VssBranch),
     Sensor(VssSensor),
+    Actuator(VssActuator),
     Attribute(VssAttribute),
 }
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 pub enum VssObjectType {
     Branch,
     Sensor,
@@ -150,8 +308,10 @@ pub enum VssObjectType {
     Unset,
 }
 
-impl VssObjectType {
-    pub fn from_str(value: &str) -> Result<Self, AfbError> {
+impl FromStr for VssObjectType {
+    type Err = AfbError;
+
+    fn from_str(value: &str) -> Result<Self, AfbError> {
         match value.to_lowercase().as_str() {
             "branch" => Ok(VssObjectType::Branch),
             "sensor" => Ok(VssObjectType::Sensor),
@@ -163,7 +323,9 @@ impl VssObjectType {
             }),
         }
     }
+}
 
+impl VssObjectType {
     pub fn to_str(&self) -> &'static str {
         match self {
             VssObjectType::Branch => "branch",
@@ -173,8 +335,44 @@ impl VssObjectType {
             VssObjectType::Unset => "attribute",
         }
     }
+
+    // only actuators accept values from clients, sensors and attributes are published by providers
+    pub fn access(&self) -> VssAccess {
+        match self {
+            VssObjectType::Sensor | VssObjectType::Attribute => VssAccess::ReadOnly,
+            VssObjectType::Actuator => VssAccess::ReadWrite,
+            VssObjectType::Branch | VssObjectType::Unset => VssAccess::None,
+        }
+    }
 }
-#[derive(Debug)]
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum VssAccess {
+    None,
+    ReadOnly,
+    ReadWrite,
+}
+
+impl VssAccess {
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            VssAccess::None => "none",
+            VssAccess::ReadOnly => "read-only",
+            VssAccess::ReadWrite => "read-write",
+        }
+    }
+    pub fn is_readable(&self) -> bool {
+        *self != VssAccess::None
+    }
+    pub fn is_writable(&self) -> bool {
+        *self == VssAccess::ReadWrite
+    }
+}
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 pub enum VssValueType {
     Uint8,
     Int8,
@@ -191,25 +389,10 @@ pub enum VssValueType {
     Unset,
 }
 
-impl VssValueType {
-    pub fn to_str(&self) -> &'static str {
-        match self {
-            VssValueType::Uint8 => "uint8",
-            VssValueType::Int8 => "int8",
-            VssValueType::Uint16 => "uint16",
-            VssValueType::Int16 => "int16",
-            VssValueType::Uint32 => "uint32",
-            VssValueType::Int32 => "int32",
-            VssValueType::Uint64 => "uint64",
-            VssValueType::Int64 => "int64",
-            VssValueType::Boolean => "boolean",
-            VssValueType::Float => "float",
-            VssValueType::Double => "double",
-            VssValueType::String => "string",
-            VssValueType::Unset => "unset",
-        }
-    }
-    pub fn from_str(value: &str) -> Result<Self, AfbError> {
+impl FromStr for VssValueType {
+    type Err = AfbError;
+
+    fn from_str(value: &str) -> Result<Self, AfbError> {
         match value.to_lowercase().as_str() {
             "uint8" => Ok(VssValueType::Uint8),
             "int8" => Ok(VssValueType::Int8),
@@ -231,6 +414,32 @@ impl VssValueType {
     }
 }
 
+impl VssValueType {
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            VssValueType::Uint8 => "uint8",
+            VssValueType::Int8 => "int8",
+            VssValueType::Uint16 => "uint16",
+            VssValueType::Int16 => "int16",
+            VssValueType::Uint32 => "uint32",
+            VssValueType::Int32 => "int32",
+            VssValueType::Uint64 => "uint64",
+            VssValueType::Int64 => "int64",
+            VssValueType::Boolean => "boolean",
+            VssValueType::Float => "float",
+            VssValueType::Double => "double",
+            VssValueType::String => "string",
+            VssValueType::Unset => "unset",
+        }
+    }
+    pub fn is_numeric(&self) -> bool {
+        !matches!(
+            self,
+            VssValueType::Boolean | VssValueType::String | VssValueType::Unset
+        )
+    }
+}
+
 pub struct VssDataType {
     pub is_type: VssValueType,
     pub is_array: bool,
@@ -238,11 +447,17 @@ pub struct VssDataType {
 
 pub enum VssElement {
     DataType(VssDataType),
+    // struct datatype reference (4.x), reported but not supported
+    DataStruct(String),
     ObjType(VssObjectType),
     ObjUnit(VssUnit),
     ObjDescription(String),
     ObjComment(String),
+    ObjDeprecation(String),
+    ObjFka(Vec<String>),
+    ObjL10n(Vec<VssL10n>),
     ObjAggregate(bool),
+    ObjInstantiate(bool),
     ObjInstances(Vec<VssInstance>),
     DataAllowed(Vec<String>),
     DataDefault(Vec<String>),
@@ -252,6 +467,7 @@ pub enum VssElement {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum VssDataValue {
     NotAvailable,
     Bool(bool),
@@ -272,53 +488,192 @@ pub enum VssDataValue {
     DoubleArray(Vec<f64>),
 }
 
-#[derive(Debug)]
+// translated texts for one locale, english default stays within node description/comment
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VssL10n {
+    pub locale: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub comment: Option<String>,
+}
+
+impl VssL10n {
+    pub fn new(locale: &str) -> Self {
+        VssL10n {
+            locale: locale.to_string(),
+            name: None,
+            description: None,
+            comment: None,
+        }
+    }
+
+    // 'fr-FR' and 'fr_fr' are equivalent, 'fr_FR' falls back on 'fr' when not available
+    pub fn find<'a>(l10n: &'a [VssL10n], locale: &str) -> Vec<&'a VssL10n> {
+        let locale = locale.to_lowercase().replace('-', "_");
+        let language = match locale.split_once('_') {
+            Some((language, _)) => language,
+            None => locale.as_str(),
+        };
+        let mut result: Vec<&'a VssL10n> = Vec::new();
+        for target in [locale.as_str(), language] {
+            for entry in l10n {
+                if entry.locale.to_lowercase().replace('-', "_") == target
+                    && !result.iter().any(|value| std::ptr::eq(*value, entry))
+                {
+                    result.push(entry);
+                }
+            }
+        }
+        result
+    }
+
+    // localized text when available, english default otherwise
+    pub fn select<'a>(
+        l10n: &'a [VssL10n],
+        locale: Option<&str>,
+        field: fn(&VssL10n) -> Option<&String>,
+        default: Option<&'a String>,
+    ) -> Option<&'a str> {
+        if let Some(locale) = locale {
+            for entry in VssL10n::find(l10n, locale) {
+                if let Some(value) = field(entry) {
+                    return Some(value.as_str());
+                }
+            }
+        }
+        default.map(|value| value.as_str())
+    }
+
+    // display name/description/comment within locator locale
+    pub fn println(
+        l10n: &[VssL10n],
+        locator: &Locator,
+        description: &Option<String>,
+        comment: &Option<String>,
+    ) {
+        let locale = locator.locale.as_deref();
+        if let Some(value) = VssL10n::select(l10n, locale, |l10n| l10n.name.as_ref(), None) {
+            println!("   name: {}", value);
+        }
+        let description = description.as_ref();
+        if let Some(value) =
+            VssL10n::select(l10n, locale, |l10n| l10n.description.as_ref(), description)
+        {
+            println!("   description: {}", value);
+        }
+        let comment = comment.as_ref();
+        if let Some(value) = VssL10n::select(l10n, locale, |l10n| l10n.comment.as_ref(), comment) {
+            println!("   comment: {}", value);
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VssInstance {
     pub prefix: Option<String>,
     pub array: Vec<String>,
 }
 
-#[derive(Debug)]
+impl VssInstance {
+    // expanded instance names: Row[1,4] => Row1..Row4, ["Left","Right"] => Left, Right
+    pub fn names(&self) -> Vec<String> {
+        let prefix = self.prefix.as_deref().unwrap_or("");
+        if let (Some(_), [first, last]) = (&self.prefix, self.array.as_slice()) {
+            if let (Ok(first), Ok(last)) = (first.parse::<u32>(), last.parse::<u32>()) {
+                return (first..=last)
+                    .map(|idx| format!("{}{}", prefix, idx))
+                    .collect();
+            }
+        }
+        self.array
+            .iter()
+            .map(|value| format!("{}{}", prefix, value))
+            .collect()
+    }
+}
+
+// source trivia, only kept when VssData.trivia is set (formatter, doc generator, editors)
+// comments hold text following '#', blank_lines counts empty lines since previous node/#include
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VssTrivia {
+    pub blank_lines: u32,
+    // comments preceding node label
+    pub comments: Vec<String>,
+    // comments within node indentation block
+    pub inner: Vec<String>,
+}
+
+impl VssTrivia {
+    pub fn is_empty(&self) -> bool {
+        self.blank_lines == 0 && self.comments.is_empty() && self.inner.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VssBranch {
     pub vpath: String,
     pub vtype: VssObjectType,
     pub description: Option<String>,
     pub comment: Option<String>,
-    pub location: usize,
+    pub deprecation: Option<String>,
+    // former vpaths (formerly known as), lookups through VssIndex::resolve
+    pub fka: Vec<String>,
+    // false: node is not repeated under instances of an enclosing branch
+    pub instantiate: bool,
+    pub location: VssLocation,
     pub instances: Vec<VssInstance>,
     pub aggregate: bool,
+    pub l10n: Vec<VssL10n>,
+    pub trivia: Option<VssTrivia>,
 }
 
 impl VssBranch {
-    pub fn new(locator: &Locator, input: &str, label: String, vtype: VssObjectType) -> VssBranch {
+    pub fn new(
+        context: &VssContext,
+        input: &str,
+        label: String,
+        vtype: VssObjectType,
+    ) -> VssBranch {
         VssBranch {
             vpath: label,
-            vtype: vtype,
+            vtype,
             description: None,
             comment: None,
+            deprecation: None,
+            fka: Vec::new(),
+            instantiate: true,
             aggregate: false,
             instances: Vec::new(),
-            location: location(locator, input.len()),
+            l10n: Vec::new(),
+            trivia: None,
+            location: context.location(input),
         }
     }
 
     pub fn println(&self, locator: &Locator) {
-        let vss_data = locator.data.try_borrow().unwrap();
-        let line = &vss_data.lines[self.location];
+        let filename = locator.filename(self.location);
         println!(
             "-- vpath: {}  ({}:{})",
-            self.vpath, line.filename.basename, line.line
+            self.vpath, filename.basename, self.location.line
         );
         println!("   type: {}", self.vtype.to_str());
-        if let Some(value) = &self.description {
-            println!("   description: {}", value);
+        VssL10n::println(&self.l10n, locator, &self.description, &self.comment);
+        if let Some(value) = &self.deprecation {
+            println!("   deprecation: {}", value);
         }
-        if let Some(value) = &self.comment {
-            println!("   comment: {}", value);
+        if !self.fka.is_empty() {
+            println!("   fka: {}", self.fka.join(", "));
+        }
+        if !self.instantiate {
+            println!("   instantiate: false");
         }
         println!("   agregate: {}", self.aggregate);
 
-        if self.instances.len() > 0 {
+        if !self.instances.is_empty() {
             println!("   instance:");
             for instance in &self.instances {
                 println!("     -- {:?}{:?}", instance.prefix, instance.array);
@@ -327,36 +682,44 @@ impl VssBranch {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VssSensor {
     pub vpath: String,
     pub vtype: VssObjectType,
     pub description: Option<String>,
     pub comment: Option<String>,
+    pub deprecation: Option<String>,
+    pub fka: Vec<String>,
+    pub instantiate: bool,
     pub min: Option<i64>,
     pub max: Option<i64>,
     pub datatype: VssValueType,
     pub arraysize: Option<usize>,
     pub default: Vec<String>,
     pub allowed: Vec<String>,
-    pub location: usize,
+    pub location: VssLocation,
     pub unit: VssUnit,
+    pub l10n: Vec<VssL10n>,
+    pub trivia: Option<VssTrivia>,
 }
 
 impl VssSensor {
-    pub fn new(locator: &Locator, input: &str, label: String, vtype: VssObjectType) -> VssSensor {
-        let vss_data = locator.data.try_borrow().unwrap();
-        let location = location(locator, input.len());
-        let line = &vss_data.lines[location];
-        let vpath = match &line.filename.prefix {
-            Some(prefix) => format!("{}.{}", prefix, label),
-            None => label,
-        };
+    pub fn new(
+        context: &VssContext,
+        input: &str,
+        label: String,
+        vtype: VssObjectType,
+    ) -> VssSensor {
+        let location = context.location(input);
         VssSensor {
-            vpath: vpath,
-            vtype: vtype,
+            vpath: label,
+            vtype,
             description: None,
             comment: None,
+            deprecation: None,
+            fka: Vec::new(),
+            instantiate: true,
             default: Vec::new(),
             allowed: Vec::new(),
             datatype: VssValueType::Unset,
@@ -364,31 +727,37 @@ impl VssSensor {
             min: None,
             max: None,
             arraysize: None,
-            location: location,
+            location,
+            l10n: Vec::new(),
+            trivia: None,
         }
     }
 
     pub fn println(&self, locator: &Locator) {
-        let vss_data = locator.data.try_borrow().unwrap();
-        let line = &vss_data.lines[self.location];
+        let filename = locator.filename(self.location);
         println!(
             "-- vpath: {}  ({}:{})",
-            self.vpath, line.filename.basename, line.line
+            self.vpath, filename.basename, self.location.line
         );
         println!("   type: {}", self.vtype.to_str());
-        if let Some(value) = &self.description {
-            println!("   description: {}", value);
+        println!("   access: {}", self.vtype.access().to_str());
+        VssL10n::println(&self.l10n, locator, &self.description, &self.comment);
+        if let Some(value) = &self.deprecation {
+            println!("   deprecation: {}", value);
         }
-        if let Some(value) = &self.comment {
-            println!("   comment: {}", value);
+        if !self.fka.is_empty() {
+            println!("   fka: {}", self.fka.join(", "));
+        }
+        if !self.instantiate {
+            println!("   instantiate: false");
         }
         println!("   datatype: {}", self.datatype.to_str());
         if let Some(value) = &self.arraysize {
             println!("   arraysize: {}", value);
         }
-        if self.default.len() > 0 {
+        if !self.default.is_empty() {
             if self.default.len() == 1 {
-                 println!("   default: {}", self.default[0]);
+                println!("   default: {}", self.default[0]);
             } else {
                 println!("   default:");
                 for value in &self.default {
@@ -396,7 +765,7 @@ impl VssSensor {
                 }
             }
         }
-        if self.allowed.len() > 0 {
+        if !self.allowed.is_empty() {
             println!("   allowed:");
             for value in &self.allowed {
                 println!("     {},", value);
@@ -411,62 +780,79 @@ impl VssSensor {
     }
 }
 
-#[derive(Debug)]
+// actuators share sensor layout, vtype carries the access mode (clients may set actuator values)
+pub type VssActuator = VssSensor;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VssAttribute {
     pub vpath: String,
     pub vtype: VssObjectType,
     pub description: Option<String>,
     pub comment: Option<String>,
-    pub location: usize,
+    pub deprecation: Option<String>,
+    pub fka: Vec<String>,
+    pub instantiate: bool,
+    pub location: VssLocation,
     pub datatype: VssValueType,
     pub arraysize: Option<usize>,
     pub default: Vec<String>,
     pub allowed: Vec<String>,
     pub unit: VssUnit,
+    pub l10n: Vec<VssL10n>,
+    pub trivia: Option<VssTrivia>,
 }
 
 impl VssAttribute {
     pub fn new(
-        locator: &Locator,
+        context: &VssContext,
         input: &str,
         label: String,
         vtype: VssObjectType,
     ) -> VssAttribute {
         VssAttribute {
             vpath: label,
-            vtype: vtype,
+            vtype,
             description: None,
             comment: None,
+            deprecation: None,
+            fka: Vec::new(),
+            instantiate: true,
             datatype: VssValueType::Unset,
             default: Vec::new(),
             allowed: Vec::new(),
             arraysize: None,
             unit: VssUnit::None,
-            location: location(locator, input.len()),
+            location: context.location(input),
+            l10n: Vec::new(),
+            trivia: None,
         }
     }
     pub fn println(&self, locator: &Locator) {
-        let vss_data = locator.data.try_borrow().unwrap();
-        let line = &vss_data.lines[self.location];
+        let filename = locator.filename(self.location);
         println!(
             "-- vpath: {}  ({}:{})",
-            self.vpath, line.filename.basename, line.line
+            self.vpath, filename.basename, self.location.line
         );
         println!("   type: {}", self.vtype.to_str());
-        if let Some(value) = &self.description {
-            println!("   description: {}", value);
+        VssL10n::println(&self.l10n, locator, &self.description, &self.comment);
+        if let Some(value) = &self.deprecation {
+            println!("   deprecation: {}", value);
         }
-        if let Some(value) = &self.comment {
-            println!("   comment: {}", value);
+        if !self.fka.is_empty() {
+            println!("   fka: {}", self.fka.join(", "));
+        }
+        if !self.instantiate {
+            println!("   instantiate: false");
         }
         if let Some(value) = &self.arraysize {
             println!("   arraysize: {}", value);
         }
         println!("   datatype: {}", self.datatype.to_str());
 
-        if self.default.len() > 0 {
+        if !self.default.is_empty() {
             if self.default.len() == 1 {
-                 println!("   default: {}", self.default[0]);
+                println!("   default: {}", self.default[0]);
             } else {
                 println!("   default:");
                 for value in &self.default {
@@ -475,7 +861,7 @@ impl VssAttribute {
             }
         }
 
-        if self.allowed.len() > 0 {
+        if !self.allowed.is_empty() {
             println!("   allowed:");
             for value in &self.allowed {
                 println!("     {},", value);
@@ -486,49 +872,134 @@ impl VssAttribute {
 }
 
 pub struct Locator {
-    pub table: Vec<usize>,
-    pub buffer: String,
-    pub count: usize,
-    pub data: Rc<RefCell<VssData>>,
+    pub data: VssData,
+    pub units: VssUnitPool,
+    pub locale: Option<String>,
 }
 
 impl Locator {
     pub fn new(vss: VssHandle) -> Result<Self, AfbError> {
-        let mut locator = Locator {
-            table: Vec::new(),
-            count: 0,
-            buffer: "".to_string(),
-            data: vss.data.clone(),
+        let locator = Locator {
+            data: vss.data,
+            units: VssUnitInfo::get_pool(),
+            locale: None,
         };
+        Ok(locator)
+    }
 
-        // track location and group vector of strings into a single buffer from vector of string
-        let vss_data = match vss.data.try_borrow() {
-            Ok(data) => data,
-            Err(_error) => {
-                return Err(AfbError::new(
-                    "vss-handle-mut",
-                    "fail to get mutable handle".to_string(),
-                ))
-            }
-        };
+    pub fn filename(&self, location: VssLocation) -> &Filename {
+        &self.data.files[location.file as usize]
+    }
+
+    pub fn context<'a>(&'a self, source: &'a VssSource) -> VssContext<'a> {
+        VssContext {
+            source,
+            filename: &self.data.files[source.file as usize],
+            units: &self.units,
+            version: self.data.version,
+            diagnostics: RefCell::new(Vec::new()),
+        }
+    }
+}
 
-        for idx in 0..vss_data.lines.len() {
-            let line = vss_data.lines[idx].text.as_str();
-            locator.count += line.len() + 1;
-            locator.table.push(locator.count);
-            locator.buffer += line;
-            locator.buffer += "\n";
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum VssLevel {
+    Error,
+    Warning,
+}
 
-            // let vline=  &vss_data.lines[idx];
-            // println!("{}{}:{}/{} =>{}", vline.filename.dirname, vline.filename.basename, vss_data.lines[idx].line,locator.count,line);
+impl VssLevel {
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            VssLevel::Error => "error",
+            VssLevel::Warning => "warning",
+        }
+    }
+}
+
+// non fatal findings reported by the parser, location follows VssBranch/VssSensor/VssAttribute
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VssDiagnostic {
+    pub level: VssLevel,
+    pub uid: VssUid,
+    pub vpath: String,
+    pub info: String,
+    pub location: VssLocation,
+}
+
+impl VssDiagnostic {
+    pub fn new(
+        level: VssLevel,
+        uid: &'static str,
+        vpath: &str,
+        info: String,
+        location: VssLocation,
+    ) -> Self {
+        VssDiagnostic {
+            level,
+            uid: Cow::Borrowed(uid),
+            vpath: vpath.to_string(),
+            info,
+            location,
         }
-        //locator.buffer += "\n";
-        Ok(locator)
+    }
+
+    pub fn println(&self, locator: &Locator) {
+        let filename = locator.filename(self.location);
+        println!(
+            "{}: {}{}:{} [{}] {} {}",
+            self.level.to_str(),
+            filename.dirname,
+            filename.basename,
+            self.location.line,
+            self.uid,
+            self.vpath,
+            self.info
+        );
     }
 }
 
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VssSpec {
     pub attributes: Vec<VssAttribute>,
     pub sensors: Vec<VssSensor>,
+    pub actuators: Vec<VssActuator>,
     pub branches: Vec<VssBranch>,
+    pub diagnostics: Vec<VssDiagnostic>,
+    pub files: Vec<Filename>,
+    // file index and trivia following its last node (trivia mode only)
+    pub trailing: Vec<(u32, VssTrivia)>,
+    // nodes replaced by an overlay, in replacement order (see vss_blame)
+    pub superseded: Vec<VssObject>,
+}
+
+// parsed spec only holds owned data, it is shared read-only across threads as Arc<VssSpec>
+const _: () = {
+    const fn is_send_sync<T: Send + Sync>() {}
+    is_send_sync::<VssSpec>();
+    is_send_sync::<Locator>();
+};
+
+impl VssSpec {
+    pub fn filename(&self, location: VssLocation) -> &Filename {
+        &self.files[location.file as usize]
+    }
+
+    pub fn sensors(&self) -> impl Iterator<Item = &VssSensor> {
+        self.sensors.iter()
+    }
+
+    pub fn actuators(&self) -> impl Iterator<Item = &VssActuator> {
+        self.actuators.iter()
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.level == VssLevel::Error)
+    }
 }
@@ -8,10 +8,18 @@
  * reference: (covesa) /vehicle_signal_specification/spec/units.yaml
  */
 
+use std::str::FromStr;
+
 use crate::types::*;
+use crate::yaml::*;
 
 #[allow(non_camel_case_types)]
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "String", from = "String")
+)]
 pub enum VssUnit {
     units,
     mm,
@@ -68,11 +76,19 @@ pub enum VssUnit {
     nm_km,
     dBm,
     kN,
+    K,
+    fahrenheit,
+    mi,
+    mph,
+    psi,
+    Custom(String),
     None,
 }
 
-impl VssUnit {
-    pub fn from_str(value: &str) -> Result<Self, AfbError> {
+impl FromStr for VssUnit {
+    type Err = AfbError;
+
+    fn from_str(value: &str) -> Result<Self, AfbError> {
         match value.to_lowercase().as_str() {
             "units" => Ok(VssUnit::units),
             "mm" => Ok(VssUnit::mm),
@@ -129,6 +145,11 @@ impl VssUnit {
             "nm/km" => Ok(VssUnit::nm_km),
             "dbm" => Ok(VssUnit::dBm),
             "kn" => Ok(VssUnit::kN),
+            "k" => Ok(VssUnit::K),
+            "fahrenheit" => Ok(VssUnit::fahrenheit),
+            "mi" => Ok(VssUnit::mi),
+            "mph" => Ok(VssUnit::mph),
+            "psi" => Ok(VssUnit::psi),
 
             _ => Err(AfbError::new(
                 "vss-objunit-invalid",
@@ -136,8 +157,36 @@ impl VssUnit {
             )),
         }
     }
+}
+
+// serialized with its vspec label
+#[cfg(feature = "serde")]
+impl From<VssUnit> for String {
+    fn from(unit: VssUnit) -> Self {
+        unit.to_str().to_string()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<String> for VssUnit {
+    fn from(label: String) -> Self {
+        match label.as_str() {
+            "Unset" => VssUnit::None,
+            _ => VssUnit::from_label(label.as_str()),
+        }
+    }
+}
 
-    pub fn to_str(&self) -> &'static str {
+impl VssUnit {
+    // built-in unit or custom one, custom units are checked against unit registry by the parser
+    pub fn from_label(value: &str) -> Self {
+        match VssUnit::from_str(value) {
+            Ok(unit) => unit,
+            Err(_) => VssUnit::Custom(value.to_string()),
+        }
+    }
+
+    pub fn to_str(&self) -> &str {
         match self {
             VssUnit::units => "units",
             VssUnit::mm => "mm",
@@ -194,20 +243,162 @@ impl VssUnit {
             VssUnit::nm_km => "nm/km",
             VssUnit::dBm => "dBm",
             VssUnit::kN => "kN",
+            VssUnit::K => "K",
+            VssUnit::fahrenheit => "fahrenheit",
+            VssUnit::mi => "mi",
+            VssUnit::mph => "mph",
+            VssUnit::psi => "psi",
+            VssUnit::Custom(label) => label.as_str(),
             VssUnit::None => "Unset",
         }
     }
+
+    // conversion to the reference unit of the unit class (SI unit when it exists)
+    pub fn get_scale(&self) -> Option<VssUnitScale> {
+        let scale = match self {
+            // distance: meter
+            VssUnit::mm => VssUnitScale::linear(0.001),
+            VssUnit::cm => VssUnitScale::linear(0.01),
+            VssUnit::m => VssUnitScale::linear(1.0),
+            VssUnit::km => VssUnitScale::linear(1000.0),
+            VssUnit::inch => VssUnitScale::linear(0.0254),
+            VssUnit::mi => VssUnitScale::linear(1609.344),
+            // speed: meter per second
+            VssUnit::km_h => VssUnitScale::linear(1.0 / 3.6),
+            VssUnit::m_s => VssUnitScale::linear(1.0),
+            VssUnit::mph => VssUnitScale::linear(0.44704),
+            // acceleration: meter per second squared
+            VssUnit::m_sx2 => VssUnitScale::linear(1.0),
+            VssUnit::cm_sx2 => VssUnitScale::linear(0.01),
+            // volume: liter
+            VssUnit::ml => VssUnitScale::linear(0.001),
+            VssUnit::l => VssUnitScale::linear(1.0),
+            VssUnit::cmx3 => VssUnitScale::linear(0.001),
+            // temperature: kelvin
+            VssUnit::K => VssUnitScale::linear(1.0),
+            VssUnit::celsius => VssUnitScale::affine(1.0, 273.15),
+            VssUnit::fahrenheit => VssUnitScale::affine(5.0 / 9.0, 273.15 - 32.0 * 5.0 / 9.0),
+            // angle & angular speed: degree
+            VssUnit::degrees => VssUnitScale::linear(1.0),
+            VssUnit::degrees_s => VssUnitScale::linear(1.0),
+            // power: watt
+            VssUnit::W => VssUnitScale::linear(1.0),
+            VssUnit::kW => VssUnitScale::linear(1000.0),
+            VssUnit::PS => VssUnitScale::linear(735.49875),
+            VssUnit::kWh => VssUnitScale::linear(1.0),
+            // mass: kilogram
+            VssUnit::g => VssUnitScale::linear(0.001),
+            VssUnit::kg => VssUnitScale::linear(1.0),
+            VssUnit::lbs => VssUnitScale::linear(0.45359237),
+            VssUnit::V => VssUnitScale::linear(1.0),
+            VssUnit::A => VssUnitScale::linear(1.0),
+            VssUnit::Ah => VssUnitScale::linear(1.0),
+            // time: second (months and years use gregorian calendar average)
+            VssUnit::ms => VssUnitScale::linear(0.001),
+            VssUnit::s => VssUnitScale::linear(1.0),
+            VssUnit::min => VssUnitScale::linear(60.0),
+            VssUnit::h => VssUnitScale::linear(3600.0),
+            VssUnit::day => VssUnitScale::linear(86400.0),
+            VssUnit::weeks => VssUnitScale::linear(604800.0),
+            VssUnit::months => VssUnitScale::linear(2629746.0),
+            VssUnit::years => VssUnitScale::linear(31556952.0),
+            // pressure: pascal
+            VssUnit::mbar => VssUnitScale::linear(100.0),
+            VssUnit::Pa => VssUnitScale::linear(1.0),
+            VssUnit::kPa => VssUnitScale::linear(1000.0),
+            VssUnit::psi => VssUnitScale::linear(6894.757293168),
+            VssUnit::stars => VssUnitScale::linear(1.0),
+            VssUnit::g_s => VssUnitScale::linear(1.0),
+            VssUnit::g_km => VssUnitScale::linear(1.0),
+            VssUnit::kWh_100km => VssUnitScale::linear(1.0),
+            // fuel consumption: liter per 100 kilometers (mpg is an inverse of it)
+            VssUnit::ml_100km => VssUnitScale::linear(0.001),
+            VssUnit::l_100km => VssUnitScale::linear(1.0),
+            VssUnit::mpg => VssUnitScale::inverse(100.0 * 3.785411784 / 1.609344),
+            VssUnit::l_h => VssUnitScale::linear(1.0),
+            // force: newton
+            VssUnit::N => VssUnitScale::linear(1.0),
+            VssUnit::kN => VssUnitScale::linear(1000.0),
+            VssUnit::Nm => VssUnitScale::linear(1.0),
+            VssUnit::rpm => VssUnitScale::linear(1.0),
+            VssUnit::Hz => VssUnitScale::linear(1.0),
+            // relation: ratio
+            VssUnit::ratio => VssUnitScale::linear(1.0),
+            VssUnit::percent => VssUnitScale::linear(0.01),
+
+            // timestamp is a point in time, dBm is logarithmic
+            VssUnit::Timestamp
+            | VssUnit::dBm
+            | VssUnit::nm_km
+            | VssUnit::units
+            | VssUnit::Custom(_)
+            | VssUnit::None => return None,
+        };
+        Some(scale)
+    }
+}
+
+// reference = value * factor + offset, or reference = factor / value for inverse units
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VssUnitScale {
+    pub factor: f64,
+    pub offset: f64,
+    pub inverse: bool,
+}
+
+impl VssUnitScale {
+    pub fn linear(factor: f64) -> Self {
+        VssUnitScale {
+            factor,
+            offset: 0.0,
+            inverse: false,
+        }
+    }
+
+    pub fn affine(factor: f64, offset: f64) -> Self {
+        VssUnitScale {
+            factor,
+            offset,
+            inverse: false,
+        }
+    }
+
+    pub fn inverse(factor: f64) -> Self {
+        VssUnitScale {
+            factor,
+            offset: 0.0,
+            inverse: true,
+        }
+    }
+
+    pub fn to_reference(&self, value: f64) -> f64 {
+        if self.inverse {
+            self.factor / value
+        } else {
+            value * self.factor + self.offset
+        }
+    }
+
+    pub fn from_reference(&self, value: f64) -> f64 {
+        if self.inverse {
+            self.factor / value
+        } else {
+            (value - self.offset) / self.factor
+        }
+    }
 }
 
 pub struct VssUnitInfo {
     pub uid: VssUnit,
-    pub label: &'static str,
-    pub description: &'static str,
+    pub label: String,
+    pub description: String,
     pub domain: VssUnitClass,
+    pub scale: Option<VssUnitScale>,
+    pub datatypes: Vec<String>,
 }
 
 #[allow(non_camel_case_types)]
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum VssUnitClass {
     acceleration,
     angle,
@@ -237,28 +428,128 @@ pub enum VssUnitClass {
     Torque,
     volume,
     volume_distance,
+    Custom(String),
+}
+
+impl VssUnitClass {
+    // accept vss-3 unit domains as well as vss-4 quantity names
+    pub fn from_quantity(value: &str) -> Self {
+        let name = value.to_lowercase().replace(['-', ' '], "_");
+        match name.as_str() {
+            "acceleration" => VssUnitClass::acceleration,
+            "angle" => VssUnitClass::angle,
+            "angular_speed" | "angular_velocity" => VssUnitClass::angular_speed,
+            "distance" | "length" => VssUnitClass::distance,
+            "distance_volume" | "distance_per_volume" => VssUnitClass::distance_volume,
+            "electric_charge" => VssUnitClass::electric_charge,
+            "electric_current" => VssUnitClass::electric_current,
+            "electric_potential" | "voltage" => VssUnitClass::electric_potential,
+            "energu_consumption" | "energy_consumption" => VssUnitClass::energu_consumption,
+            "energy" | "energy_consumption_per_distance" => VssUnitClass::energy,
+            "flow" | "volume_flow_rate" => VssUnitClass::flow,
+            "force" => VssUnitClass::force,
+            "frequency" => VssUnitClass::frequency,
+            "mass" => VssUnitClass::mass,
+            "mass_distance" | "mass_per_distance" => VssUnitClass::mass_distance,
+            "mass_per_time" | "mass_flow_rate" => VssUnitClass::mass_per_time,
+            "power" => VssUnitClass::power,
+            "pressure" => VssUnitClass::pressure,
+            "rating" => VssUnitClass::rating,
+            "relation" => VssUnitClass::relation,
+            "rotational_speed" => VssUnitClass::rotational_speed,
+            "speed" | "velocity" => VssUnitClass::speed,
+            "temperature" => VssUnitClass::temperature,
+            "time" | "duration" | "datetime" => VssUnitClass::time,
+            "torque" => VssUnitClass::Torque,
+            "volume" => VssUnitClass::volume,
+            "volume_distance" | "volume_per_distance" => VssUnitClass::volume_distance,
+            "none" | "" => VssUnitClass::None,
+            _ => VssUnitClass::Custom(value.to_string()),
+        }
+    }
+
+    pub fn to_str(&self) -> &str {
+        match self {
+            VssUnitClass::acceleration => "acceleration",
+            VssUnitClass::angle => "angle",
+            VssUnitClass::angular_speed => "angular_speed",
+            VssUnitClass::distance => "distance",
+            VssUnitClass::distance_volume => "distance_volume",
+            VssUnitClass::electric_charge => "electric_charge",
+            VssUnitClass::electric_current => "electric_current",
+            VssUnitClass::electric_potential => "electric_potential",
+            VssUnitClass::energu_consumption => "energu_consumption",
+            VssUnitClass::energy => "energy",
+            VssUnitClass::flow => "flow",
+            VssUnitClass::force => "force",
+            VssUnitClass::frequency => "frequency",
+            VssUnitClass::mass => "mass",
+            VssUnitClass::mass_distance => "mass_distance",
+            VssUnitClass::mass_per_time => "mass_per_time",
+            VssUnitClass::None => "none",
+            VssUnitClass::power => "power",
+            VssUnitClass::pressure => "pressure",
+            VssUnitClass::rating => "rating",
+            VssUnitClass::relation => "relation",
+            VssUnitClass::rotational_speed => "rotational_speed",
+            VssUnitClass::speed => "speed",
+            VssUnitClass::temperature => "temperature",
+            VssUnitClass::time => "time",
+            VssUnitClass::Torque => "torque",
+            VssUnitClass::volume => "volume",
+            VssUnitClass::volume_distance => "volume_distance",
+            VssUnitClass::Custom(name) => name.as_str(),
+        }
+    }
+
+    // fuel consumption is expressed either as volume per distance or as distance per volume
+    pub fn is_convertible(&self, other: &VssUnitClass) -> bool {
+        match (self, other) {
+            (VssUnitClass::None, _) | (_, VssUnitClass::None) => false,
+            (VssUnitClass::volume_distance, VssUnitClass::distance_volume) => true,
+            (VssUnitClass::distance_volume, VssUnitClass::volume_distance) => true,
+            _ => self == other,
+        }
+    }
 }
 
+pub struct VssQuantity {
+    pub name: String,
+    pub definition: String,
+    pub remark: Option<String>,
+    pub comment: Option<String>,
+}
+
+// runtime unit registry, default is the built-in table returned by VssUnitInfo::get_pool()
 pub struct VssUnitPool {
     units: Vec<VssUnitInfo>,
+    quantities: Vec<VssQuantity>,
 }
 impl VssUnitInfo {
-    pub fn new(
-        uid: VssUnit,
-        label: &'static str,
-        description: &'static str,
-        domain: VssUnitClass,
-    ) -> VssUnitInfo {
+    pub fn new(uid: VssUnit, label: &str, description: &str, domain: VssUnitClass) -> VssUnitInfo {
         VssUnitInfo {
-            uid: uid,
-            label: label,
-            description: description,
-            domain: domain,
+            scale: uid.get_scale(),
+            uid,
+            label: label.to_string(),
+            description: description.to_string(),
+            domain,
+            datatypes: Vec::new(),
+        }
+    }
+
+    // datatypes accepted for this unit, empty means any numeric datatype
+    pub fn accept_datatype(&self, datatype: &VssValueType) -> bool {
+        if self.datatypes.is_empty() {
+            return datatype.is_numeric();
         }
+        self.datatypes.iter().any(|value| {
+            (value == "numeric" && datatype.is_numeric())
+                || value.trim_end_matches("[]") == datatype.to_str()
+        })
     }
 
     pub fn get_pool() -> VssUnitPool {
-        let mut pool = VssUnitPool { units: Vec::new() };
+        let mut pool = VssUnitPool::empty();
 
         pool.units.push(VssUnitInfo::new(
             VssUnit::mm,
@@ -459,11 +750,13 @@ impl VssUnitInfo {
             "Time measured in years",
             VssUnitClass::time,
         ));
-        pool.units.push(VssUnitInfo::new(VssUnit::Timestamp
+        let mut timestamp = VssUnitInfo::new(VssUnit::Timestamp
             , "Timestamp"
             , "Unix time is a system for describing a point in time. It is the number of seconds that have elapsed since the Unix epoch, excluding leap seconds."
             , VssUnitClass::time
-        ));
+        );
+        timestamp.datatypes = vec!["string".to_string(), "uint64".to_string()];
+        pool.units.push(timestamp);
         pool.units.push(VssUnitInfo::new(
             VssUnit::mbar,
             "millibar",
@@ -584,7 +877,273 @@ impl VssUnitInfo {
             "force measured in kilo newton",
             VssUnitClass::force,
         ));
+        pool.units.push(VssUnitInfo::new(
+            VssUnit::K,
+            "kelvin",
+            "Temperature measured in kelvin",
+            VssUnitClass::temperature,
+        ));
+        pool.units.push(VssUnitInfo::new(
+            VssUnit::fahrenheit,
+            "degree fahrenheit",
+            "Temperature measured in degree fahrenheit",
+            VssUnitClass::temperature,
+        ));
+        pool.units.push(VssUnitInfo::new(
+            VssUnit::mi,
+            "mile",
+            "Distance measured in miles",
+            VssUnitClass::distance,
+        ));
+        pool.units.push(VssUnitInfo::new(
+            VssUnit::mph,
+            "miles per hour",
+            "Speed measured in miles per hour",
+            VssUnitClass::speed,
+        ));
+        pool.units.push(VssUnitInfo::new(
+            VssUnit::psi,
+            "pound per square inch",
+            "Pressure measured in pounds per square inch",
+            VssUnitClass::pressure,
+        ));
 
         pool
     }
 }
+
+impl VssUnitPool {
+    // empty registry, use VssUnitInfo::get_pool() for built-in units
+    pub fn empty() -> Self {
+        VssUnitPool {
+            units: Vec::new(),
+            quantities: Vec::new(),
+        }
+    }
+
+    // registry loaded from covesa units.yaml (and optionally quantities.yaml)
+    pub fn from_file(units: &str, quantities: Option<&str>) -> Result<Self, AfbError> {
+        let mut pool = VssUnitPool::empty();
+        if let Some(filename) = quantities {
+            pool.load_quantities(filename)?;
+        }
+        pool.load_units(units)?;
+        Ok(pool)
+    }
+
+    // quantity: [definition, remark, comment]
+    pub fn load_quantities(&mut self, filename: &str) -> Result<(), AfbError> {
+        for node in vss_yaml_from_file(filename)? {
+            let definition = match node.get_str("definition") {
+                Some(value) => value.to_string(),
+                None => {
+                    return Err(AfbError::new(
+                        "vss-quantity-invalid",
+                        format!(
+                            "{}:{} quantity '{}' has no definition",
+                            filename, node.line, node.key
+                        ),
+                    ))
+                }
+            };
+            let quantity = VssQuantity {
+                name: node.key.clone(),
+                definition,
+                remark: node.get_str("remark").map(|value| value.to_string()),
+                comment: node.get_str("comment").map(|value| value.to_string()),
+            };
+            self.quantities.retain(|value| value.name != quantity.name);
+            self.quantities.push(quantity);
+        }
+        Ok(())
+    }
+
+    // vss-3: 'units:' root with [label, description, domain]
+    // vss-4: one root per unit with [unit, definition, quantity]
+    // both accept optional [factor, offset, inverse] to define conversion to class reference unit
+    pub fn load_units(&mut self, filename: &str) -> Result<(), AfbError> {
+        let mut nodes = vss_yaml_from_file(filename)?;
+        if nodes.len() == 1 && nodes[0].key == "units" {
+            nodes = nodes.pop().unwrap().children;
+        }
+
+        for node in nodes {
+            let uid = VssUnit::from_label(node.key.as_str());
+            let quantity = match node.get_str("quantity").or(node.get_str("domain")) {
+                Some(value) => value,
+                None => {
+                    return Err(AfbError::new(
+                        "vss-objunit-invalid",
+                        format!(
+                            "{}:{} unit '{}' has no quantity",
+                            filename, node.line, node.key
+                        ),
+                    ))
+                }
+            };
+            if !self.quantities.is_empty() && self.get_quantity(quantity).is_none() {
+                return Err(AfbError::new(
+                    "vss-quantity-unknown",
+                    format!(
+                        "{}:{} unit '{}' quantity '{}' not defined",
+                        filename, node.line, node.key, quantity
+                    ),
+                ));
+            }
+
+            let mut scale = uid.get_scale();
+            if let Some(value) = node.get_str("factor") {
+                let factor = match value.parse::<f64>() {
+                    Ok(factor) => factor,
+                    Err(_) => {
+                        return Err(AfbError::new(
+                            "vss-objunit-invalid",
+                            format!(
+                                "{}:{} unit '{}' invalid factor:{}",
+                                filename, node.line, node.key, value
+                            ),
+                        ))
+                    }
+                };
+                let offset = match node.get_str("offset").map(|value| value.parse::<f64>()) {
+                    None => 0.0,
+                    Some(Ok(offset)) => offset,
+                    Some(Err(_)) => {
+                        return Err(AfbError::new(
+                            "vss-objunit-invalid",
+                            format!(
+                                "{}:{} unit '{}' invalid offset",
+                                filename, node.line, node.key
+                            ),
+                        ))
+                    }
+                };
+                scale = match node.get_str("inverse") {
+                    Some("true") => Some(VssUnitScale::inverse(factor)),
+                    _ => Some(VssUnitScale::affine(factor, offset)),
+                };
+            }
+
+            let info = VssUnitInfo {
+                label: node
+                    .get_str("label")
+                    .or(node.get_str("unit"))
+                    .unwrap_or(&node.key)
+                    .to_string(),
+                description: node
+                    .get_str("description")
+                    .or(node.get_str("definition"))
+                    .unwrap_or("")
+                    .to_string(),
+                domain: VssUnitClass::from_quantity(quantity),
+                datatypes: match node
+                    .get("allowed-datatypes")
+                    .or(node.get("allowed_datatypes"))
+                {
+                    Some(datatypes) => datatypes.list.clone(),
+                    None => Vec::new(),
+                },
+                scale,
+                uid,
+            };
+            self.units.retain(|value| value.uid != info.uid);
+            self.units.push(info);
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, uid: &VssUnit) -> Option<&VssUnitInfo> {
+        self.units.iter().find(|info| info.uid == *uid)
+    }
+
+    // search unit from its vspec label (ex: 'km/h')
+    pub fn find(&self, label: &str) -> Option<&VssUnitInfo> {
+        self.get(&VssUnit::from_label(label))
+    }
+
+    pub fn get_quantity(&self, name: &str) -> Option<&VssQuantity> {
+        self.quantities
+            .iter()
+            .find(|quantity| quantity.name == name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &VssUnitInfo> {
+        self.units.iter()
+    }
+
+    pub fn quantities(&self) -> impl Iterator<Item = &VssQuantity> {
+        self.quantities.iter()
+    }
+
+    // convert a value between two units of the same class
+    pub fn convert(&self, value: f64, from: &VssUnit, to: &VssUnit) -> Result<f64, AfbError> {
+        if from == to {
+            return Ok(value);
+        }
+        let from_info = match self.get(from) {
+            Some(info) => info,
+            None => {
+                return Err(AfbError::new(
+                    "vss-unit-unknown",
+                    format!("unit:{} not within unit pool", from.to_str()),
+                ))
+            }
+        };
+        let to_info = match self.get(to) {
+            Some(info) => info,
+            None => {
+                return Err(AfbError::new(
+                    "vss-unit-unknown",
+                    format!("unit:{} not within unit pool", to.to_str()),
+                ))
+            }
+        };
+
+        if !from_info.domain.is_convertible(&to_info.domain) {
+            return Err(AfbError::new(
+                "vss-unit-domain",
+                format!(
+                    "cannot convert {}({:?}) into {}({:?})",
+                    from.to_str(),
+                    from_info.domain,
+                    to.to_str(),
+                    to_info.domain
+                ),
+            ));
+        }
+
+        let (from_scale, to_scale) = match (&from_info.scale, &to_info.scale) {
+            (Some(from_scale), Some(to_scale)) => (from_scale, to_scale),
+            _ => {
+                return Err(AfbError::new(
+                    "vss-unit-noscale",
+                    format!(
+                        "no conversion between {} and {}",
+                        from.to_str(),
+                        to.to_str()
+                    ),
+                ))
+            }
+        };
+
+        let result = to_scale.from_reference(from_scale.to_reference(value));
+        if !result.is_finite() {
+            return Err(AfbError::new(
+                "vss-unit-overflow",
+                format!(
+                    "{}{} has no finite value in {}",
+                    value,
+                    from.to_str(),
+                    to.to_str()
+                ),
+            ));
+        }
+        Ok(result)
+    }
+}
+
+impl Default for VssUnitPool {
+    fn default() -> Self {
+        VssUnitInfo::get_pool()
+    }
+}
@@ -7,13 +7,21 @@
  *
  */
 
-use std::fs::File;
-use std::io;
-use std::io::prelude::*;
 use nom::error::{Error, ErrorKind};
+use std::collections::HashMap;
+use std::fs;
+use std::mem;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
 
-use crate::types::*;
+use crate::catalog::*;
+use crate::index::*;
+use crate::lint::*;
 use crate::parser::*;
+use crate::types::*;
+use crate::validate::*;
+use crate::version::*;
 
 pub fn _to_static_str(value: String) -> &'static str {
     Box::leak(value.into_boxed_str())
@@ -22,7 +30,7 @@ pub fn _to_static_str(value: String) -> &'static str {
 // make nom error to leverage ?; try method
 pub fn afb_to_nom_error<'a>(input: &'a str, _error: &AfbError) -> nom::Err<Error<&'a str>> {
     nom::Err::Error(Error {
-        input: input,
+        input,
         code: ErrorKind::Verify,
     })
 }
@@ -31,7 +39,7 @@ pub fn nom_to_afb_error(input: &str, error: nom::Err<Error<&str>>) -> AfbError {
     match error {
         nom::Err::Error(error) => {
             let info = match get_one_line(input) {
-                Ok((_, info)) => format!("{}({})", error.to_string(), info),
+                Ok((_, info)) => format!("{}({})", error, info),
                 Err(_) => error.to_string(),
             };
             AfbError::new("parsing-error", info)
@@ -41,24 +49,17 @@ pub fn nom_to_afb_error(input: &str, error: nom::Err<Error<&str>>) -> AfbError {
     }
 }
 
-// loop on line of buffer until all attributes are parsed
-pub fn vss_parse_rules<'a>(locator: &Locator) -> Result<VssSpec, AfbError> {
-    let mut vss = VssSpec {
-        attributes: Vec::new(),
-        sensors: Vec::new(),
-        branches: Vec::new(),
-    };
+// loop on one file buffer until all objects are parsed, objects are tagged with their offset
+fn vss_parse_source(locator: &Locator, source: &VssSource) -> Result<VssSourceObjects, AfbError> {
+    let context = locator.context(source);
+    let mut objects = Vec::new();
 
-    let mut input = locator.buffer.as_str();
-    while input.len() > 0 {
-        match vss_object(locator, input) {
+    let mut input = source.buffer.as_str();
+    while !input.is_empty() {
+        let offset = (source.buffer.len() - input.len()) as u32;
+        match vss_object(&context, input) {
             Ok((pointer, object)) => {
-                //println!("*** vss obj:{:?}", object);
-                match object {
-                    VssObject::Attribute(obj) => vss.attributes.push(obj),
-                    VssObject::Sensor(obj) => vss.sensors.push(obj),
-                    VssObject::Branch(obj) => vss.branches.push(obj),
-                }
+                objects.push((offset, object));
                 match eof_data(pointer) {
                     Ok(_) => break,
                     Err(_error) => {}
@@ -68,9 +69,7 @@ pub fn vss_parse_rules<'a>(locator: &Locator) -> Result<VssSpec, AfbError> {
             Err(error) => {
                 let afb_error = match error {
                     nom::Err::Error(error) => {
-                        let vss_data = locator.data.try_borrow().unwrap();
-                        let location = location(locator, error.input.len());
-                        let line = &vss_data.lines[location];
+                        let location = context.location(error.input);
                         let info = match get_one_line(error.input) {
                             Ok((_, info)) => info,
                             Err(_) => error.input.to_string(),
@@ -79,7 +78,10 @@ pub fn vss_parse_rules<'a>(locator: &Locator) -> Result<VssSpec, AfbError> {
                             "parsing-error",
                             format!(
                                 "{}{}:{} 'invalid: ({})'",
-                                line.filename.dirname, line.filename.basename, line.line, info
+                                context.filename.dirname,
+                                context.filename.basename,
+                                location.line,
+                                info
                             ),
                         )
                     }
@@ -94,45 +96,607 @@ pub fn vss_parse_rules<'a>(locator: &Locator) -> Result<VssSpec, AfbError> {
             }
         }
     }
-    Ok(vss)
+    Ok((objects, context.diagnostics.take()))
+}
+
+// parsed objects tagged with their buffer offset, plus file parsing diagnostics
+type VssSourceObjects = (Vec<(u32, VssObject)>, Vec<VssDiagnostic>);
+type VssParsed = Option<Result<VssSourceObjects, AfbError>>;
+
+// vpath registry of the spec being merged, duplicates are resolved on the fly
+struct VssMerge<'a> {
+    files: &'a [Filename],
+    policy: VssDuplicates,
+    // vpath => node type, index within its spec vector and location of definition in use
+    seen: HashMap<String, (VssObjectType, usize, VssLocation)>,
+    diagnostics: Vec<VssDiagnostic>,
+}
+
+impl<'a> VssMerge<'a> {
+    fn new(files: &'a [Filename], policy: VssDuplicates) -> Self {
+        VssMerge {
+            files,
+            policy,
+            seen: HashMap::new(),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    fn path(&self, location: VssLocation) -> String {
+        let filename = &self.files[location.file as usize];
+        format!(
+            "{}{}:{}",
+            filename.dirname, filename.basename, location.line
+        )
+    }
+
+    // overlay objects silently replace previous definition, others follow duplicates policy,
+    // an object conflicting with previous definition type is always dropped
+    fn insert(&mut self, vss: &mut VssSpec, object: VssObject, overlay: bool) {
+        let node = VssNode::from(&object);
+        let (vpath, vtype, location) = (node.vpath(), node.vtype().clone(), node.location());
+        let (previous, idx, first) = match self.seen.get(vpath) {
+            Some(found) => found.clone(),
+            None => {
+                let vpath = vpath.to_string();
+                let idx = vss_push_object(vss, object);
+                self.seen.insert(vpath, (vtype, idx, location));
+                return;
+            }
+        };
+
+        if previous != vtype {
+            self.diagnostics.push(VssDiagnostic::new(
+                VssLevel::Error,
+                "vss-duplicate-type",
+                vpath,
+                format!(
+                    "{} conflicts with {} defined at {}, ignored",
+                    vtype.to_str(),
+                    previous.to_str(),
+                    self.path(first)
+                ),
+                location,
+            ));
+            return;
+        }
+        if !overlay {
+            let (level, resolution) = match self.policy {
+                VssDuplicates::Error => (VssLevel::Error, "ignored"),
+                VssDuplicates::FirstWins => (VssLevel::Warning, "ignored"),
+                VssDuplicates::LastWins => (VssLevel::Warning, "replaces it"),
+            };
+            self.diagnostics.push(VssDiagnostic::new(
+                level,
+                "vss-duplicate",
+                vpath,
+                format!("already defined at {}, {}", self.path(first), resolution),
+                location,
+            ));
+            if self.policy != VssDuplicates::LastWins {
+                return;
+            }
+        }
+        let vpath = vpath.to_string();
+        let old = vss_replace_object(vss, idx, object);
+        vss.superseded.push(old);
+        self.seen.insert(vpath, (vtype, idx, location));
+    }
 }
 
+fn vss_push_object(vss: &mut VssSpec, object: VssObject) -> usize {
+    match object {
+        VssObject::Branch(obj) => {
+            vss.branches.push(obj);
+            vss.branches.len() - 1
+        }
+        VssObject::Sensor(obj) => {
+            vss.sensors.push(obj);
+            vss.sensors.len() - 1
+        }
+        VssObject::Actuator(obj) => {
+            vss.actuators.push(obj);
+            vss.actuators.len() - 1
+        }
+        VssObject::Attribute(obj) => {
+            vss.attributes.push(obj);
+            vss.attributes.len() - 1
+        }
+    }
+}
 
-// read a file in RAM and transform its buffer as &str
-pub fn vss_from_file(vss: &VssHandle) -> Result<(), AfbError> {
-    let dbc_buffer = || -> Result<Vec<u8>, io::Error> {
-        let fullname = format!("{}{}", vss.filename.dirname, vss.filename.basename);
-        let mut fd = File::open(fullname)?;
-        let size = fd.metadata().unwrap().len();
-        let mut buffer = Vec::with_capacity(size as usize);
+// object type matches the one of node at idx, replaced node is returned
+fn vss_replace_object(vss: &mut VssSpec, idx: usize, object: VssObject) -> VssObject {
+    match object {
+        VssObject::Branch(obj) => VssObject::Branch(mem::replace(&mut vss.branches[idx], obj)),
+        VssObject::Sensor(obj) => VssObject::Sensor(mem::replace(&mut vss.sensors[idx], obj)),
+        VssObject::Actuator(obj) => VssObject::Actuator(mem::replace(&mut vss.actuators[idx], obj)),
+        VssObject::Attribute(obj) => {
+            VssObject::Attribute(mem::replace(&mut vss.attributes[idx], obj))
+        }
+    }
+}
 
-        fd.read_to_end(&mut buffer)?;
-        Ok(buffer)
+// push file objects into spec, included files objects take place of their #include line
+fn vss_merge_source(
+    merge: &mut VssMerge,
+    vss: &mut VssSpec,
+    sources: &[VssSource],
+    parsed: &mut [VssParsed],
+    file: usize,
+) -> Result<(), AfbError> {
+    let (objects, mut diagnostics) = match parsed[file].take() {
+        Some(result) => result?,
+        None => return Ok(()),
     };
+    merge.diagnostics.append(&mut diagnostics);
+    let source = &sources[file];
+    let mut includes = source.includes.iter().peekable();
+    for (offset, mut object) in objects {
+        while let Some((_, include)) = includes.next_if(|(position, _)| *position <= offset) {
+            vss_merge_source(merge, vss, sources, parsed, *include as usize)?;
+        }
+        let trivia = source.trivia(offset);
+        match &mut object {
+            VssObject::Attribute(obj) => obj.trivia = trivia,
+            VssObject::Sensor(obj) => obj.trivia = trivia,
+            VssObject::Actuator(obj) => obj.trivia = trivia,
+            VssObject::Branch(obj) => obj.trivia = trivia,
+        }
+        merge.insert(vss, object, false);
+    }
+    for (_, include) in includes {
+        vss_merge_source(merge, vss, sources, parsed, *include as usize)?;
+    }
+    if let Some(trailing) = &source.trailing {
+        vss.trailing.push((file as u32, trailing.clone()));
+    }
+    Ok(())
+}
 
-    match dbc_buffer() {
-        Err(error) => {
-            let info = format!(
-                "{}{} ({})",
-                vss.filename.dirname,
-                vss.filename.basename,
-                error.to_string()
-            );
-            Err(AfbError::new("vss-open-fail", info))
-        }
-        Ok(buffer) => {
-            let mut input = std::str::from_utf8(&buffer).unwrap();
-            while input.len() > 0 {
-                match get_line(input, &vss) {
-                    Ok((reste, _)) => {
-                        input = reste;
+// overlay nodes replace main nodes with the same vpath and type,
+// replaced nodes are kept within vss.superseded for provenance
+fn vss_apply_overlay(merge: &mut VssMerge, vss: &mut VssSpec, layer: VssSpec) {
+    for obj in layer.branches {
+        merge.insert(vss, VssObject::Branch(obj), true);
+    }
+    for obj in layer.sensors {
+        merge.insert(vss, VssObject::Sensor(obj), true);
+    }
+    for obj in layer.actuators {
+        merge.insert(vss, VssObject::Actuator(obj), true);
+    }
+    for obj in layer.attributes {
+        merge.insert(vss, VssObject::Attribute(obj), true);
+    }
+    vss.superseded.extend(layer.superseded);
+    vss.trailing.extend(layer.trailing);
+}
+
+// #include prefix is relative to including file prefix, an include without prefix inherits it
+pub fn vss_compose_prefix(outer: Option<&str>, inner: Option<&str>) -> Option<String> {
+    match (outer, inner) {
+        (Some(outer), Some(inner)) => Some(format!("{}.{}", outer, inner)),
+        (Some(prefix), None) | (None, Some(prefix)) => Some(prefix.to_string()),
+        (None, None) => None,
+    }
+}
+
+// workers pick items one by one, a large item does not stall other workers,
+// results follow items order
+fn vss_parallel_map<T: Sync, R: Send>(items: &[T], job: impl Fn(&T) -> R + Sync) -> Vec<R> {
+    let workers = match thread::available_parallelism() {
+        Ok(count) => count.get().min(items.len()),
+        Err(_) => 1,
+    };
+    if workers <= 1 {
+        return items.iter().map(job).collect();
+    }
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<Option<R>> = Vec::new();
+    results.resize_with(items.len(), || None);
+    thread::scope(|scope| {
+        let handles = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let idx = next.fetch_add(1, Ordering::Relaxed);
+                        match items.get(idx) {
+                            Some(item) => done.push((idx, job(item))),
+                            None => break,
+                        }
                     }
-                    Err(error) => return Err(nom_to_afb_error(input, error)),
+                    done
+                })
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            for (idx, result) in handle.join().unwrap() {
+                results[idx] = Some(result);
+            }
+        }
+    });
+    results.into_iter().flatten().collect()
+}
+
+// every file is parsed independently (in parallel), then merged following include order
+pub fn vss_parse_rules(locator: &Locator) -> Result<VssSpec, AfbError> {
+    let mut vss = VssSpec::default();
+
+    let sources = &locator.data.sources;
+    let mut parsed: Vec<VssParsed> = Vec::new();
+    parsed.resize_with(sources.len(), || None);
+
+    let results = vss_parallel_map(sources, |source| vss_parse_source(locator, source));
+    for (source, result) in sources.iter().zip(results) {
+        parsed[source.file as usize] = Some(result);
+    }
+    let files = &locator.data.files;
+    let policy = locator.data.duplicates;
+    let mut merge = VssMerge::new(files, policy);
+    vss_merge_source(&mut merge, &mut vss, sources, &mut parsed, 0)?;
+    for overlay in &locator.data.overlays {
+        let mut layer = VssSpec::default();
+        let mut layer_merge = VssMerge::new(files, policy);
+        vss_merge_source(
+            &mut layer_merge,
+            &mut layer,
+            sources,
+            &mut parsed,
+            *overlay as usize,
+        )?;
+        merge.diagnostics.append(&mut layer_merge.diagnostics);
+        vss_apply_overlay(&mut merge, &mut vss, layer);
+    }
+
+    // semantic checks on the whole tree
+    vss.diagnostics = locator.data.diagnostics.clone();
+    vss.diagnostics.append(&mut merge.diagnostics);
+    vss.diagnostics.extend(vss_check_units(locator, &vss));
+    vss.diagnostics.extend(vss_check_aggregates(&vss));
+    vss.diagnostics.extend(vss_check_aliases(&vss));
+    vss.diagnostics.extend(vss_check_version(locator));
+    vss.files = locator.data.files.clone();
+    Ok(vss)
+}
+
+// read one file in RAM, squeeze comments and empty lines in place and load included files
+// utf-8 file content, BOM is removed and CRLF/CR line breaks become LF (line numbers unchanged)
+pub struct VssText {
+    pub text: String,
+    pub bom: bool,
+    pub crlf: bool,
+    // first line holding an invalid utf-8 sequence, sequences are replaced by U+FFFD
+    pub invalid: Option<u32>,
+}
+
+impl VssText {
+    pub fn decode(mut bytes: Vec<u8>) -> Self {
+        let bom = bytes.starts_with(b"\xEF\xBB\xBF");
+        if bom {
+            bytes.drain(..3);
+        }
+        let crlf = bytes.windows(2).any(|pair| pair == b"\r\n");
+        if bytes.contains(&b'\r') {
+            let mut write = 0;
+            for read in 0..bytes.len() {
+                match bytes[read] {
+                    b'\r' if bytes.get(read + 1) == Some(&b'\n') => continue,
+                    b'\r' => bytes[write] = b'\n',
+                    byte => bytes[write] = byte,
+                }
+                write += 1;
+            }
+            bytes.truncate(write);
+        }
+        let (text, invalid) = match String::from_utf8(bytes) {
+            Ok(text) => (text, None),
+            Err(error) => {
+                let valid = error.utf8_error().valid_up_to();
+                let bytes = error.into_bytes();
+                let line = bytes[..valid].iter().filter(|byte| **byte == b'\n').count() as u32 + 1;
+                (String::from_utf8_lossy(&bytes).into_owned(), Some(line))
+            }
+        };
+        VssText {
+            text,
+            bom,
+            crlf,
+            invalid,
+        }
+    }
+
+    // restore original BOM and line breaks on a LF text
+    pub fn encode(&self, text: &str) -> String {
+        let bom = if self.bom { "\u{FEFF}" } else { "" };
+        match self.crlf {
+            true => format!("{}{}", bom, text.replace('\n', "\r\n")),
+            false => format!("{}{}", bom, text),
+        }
+    }
+
+    // read a file, invalid utf-8 is an error located on its line
+    pub fn from_file(filename: &str) -> Result<Self, AfbError> {
+        let decoded = match fs::read(filename) {
+            Ok(bytes) => VssText::decode(bytes),
+            Err(error) => {
+                return Err(AfbError::new(
+                    "vss-open-fail",
+                    format!("{} ({})", filename, error),
+                ))
+            }
+        };
+        match decoded.invalid {
+            Some(line) => Err(AfbError::new(
+                "vss-utf8-invalid",
+                format!("{}:{} invalid utf-8 sequence", filename, line),
+            )),
+            None => Ok(decoded),
+        }
+    }
+}
+
+// write one line at buffer offset, buffer grows when line does not fit
+fn vss_put_line(bytes: &mut Vec<u8>, write: usize, text: &[u8]) -> usize {
+    let end = write + text.len() + 1;
+    if end > bytes.len() {
+        bytes.resize(end, 0);
+    }
+    bytes[write..end - 1].copy_from_slice(text);
+    bytes[end - 1] = b'\n';
+    end
+}
+
+// read, checksum and decode one file, runs on worker threads for sibling includes
+fn vss_read_source(filename: &Filename) -> Result<(VssText, u32), AfbError> {
+    let fullname = format!("{}{}", filename.dirname, filename.basename);
+    match fs::read(&fullname) {
+        Ok(bytes) => {
+            let checksum = vss_crc32(&bytes);
+            Ok((VssText::decode(bytes), checksum))
+        }
+        Err(error) => Err(AfbError::new(
+            "vss-open-fail",
+            format!("{} ({})", fullname, error),
+        )),
+    }
+}
+
+fn vss_load_source(data: &mut VssData, file: u32) -> Result<(), AfbError> {
+    let read = vss_read_source(&data.files[file as usize]);
+    vss_scan_source(data, file, read)
+}
+
+fn vss_scan_source(
+    data: &mut VssData,
+    file: u32,
+    read: Result<(VssText, u32), AfbError>,
+) -> Result<(), AfbError> {
+    let (decoded, checksum) = read?;
+    let filename = &data.files[file as usize];
+    let fullname = format!("{}{}", filename.dirname, filename.basename);
+    // invalid sequences are replaced, parsing goes on and the line is reported
+    if let Some(line) = decoded.invalid {
+        data.diagnostics.push(VssDiagnostic::new(
+            VssLevel::Error,
+            "vss-utf8-invalid",
+            "",
+            "invalid utf-8 sequence replaced by U+FFFD".to_string(),
+            VssLocation { file, line },
+        ));
+    }
+    let buffer = decoded.text;
+
+    // first pass: keep data lines (start, end, line number) and includes position
+    let mut kept: Vec<(usize, usize, u32)> = Vec::new();
+    // flow map nodes as block lines (kept index, lines), pending holds an open one
+    let mut expanded: Vec<(usize, Vec<String>)> = Vec::new();
+    let mut pending: Option<(String, u32)> = None;
+    let mut includes: Vec<(usize, u32, VssInclude)> = Vec::new();
+    let mut trivia: Vec<(usize, VssTrivia)> = Vec::new();
+    let mut leading = VssTrivia::default();
+    let mut input = buffer.as_str();
+    let mut count = 0;
+    while !input.is_empty() {
+        let (pointer, line) = match get_line(input) {
+            Ok(result) => result,
+            Err(error) => {
+                let error = nom_to_afb_error(input, error);
+                return Err(AfbError::new(
+                    "parsing-error",
+                    format!("{}:{} {}", fullname, count + 1, error.get_info()),
+                ));
+            }
+        };
+        count += 1;
+        match line {
+            // yaml forbids tabs within indentation
+            VssType::Data(text)
+                if !text.trim().is_empty() && text.trim_start_matches(' ').starts_with('\t') =>
+            {
+                return Err(AfbError::new(
+                    "parsing-error",
+                    format!("{}:{} tab character within indentation", fullname, count),
+                ));
+            }
+            // whitespace only lines are blank lines, whatever trivia mode
+            VssType::Data(text) if text.trim().is_empty() => {
+                leading.blank_lines += u32::from(data.trivia);
+            }
+            VssType::Data(text) => {
+                // indented lines continue current node, label lines start a new one
+                if !text.starts_with([' ', '\t']) {
+                    trivia.push((kept.len(), std::mem::take(&mut leading)));
+                } else if let Some((_, last)) = trivia.last_mut() {
+                    last.inner.append(&mut leading.comments);
+                    leading.blank_lines = 0;
+                }
+                let start = buffer.len() - input.len();
+                let flow = match pending.take() {
+                    Some((flow, line)) => Some((format!("{} {}", flow, text.trim()), line)),
+                    None if vss_flow_start(text) => Some((text.to_string(), count)),
+                    None => None,
                 };
+                match flow {
+                    None => kept.push((start, start + text.len(), count)),
+                    Some((flow, line)) => match vss_flow_map(&flow) {
+                        Ok(None) => pending = Some((flow, line)),
+                        Ok(Some(lines)) => {
+                            expanded.push((kept.len(), lines));
+                            kept.push((start, start, line));
+                        }
+                        Err(info) => {
+                            return Err(AfbError::new(
+                                "parsing-error",
+                                format!("{}:{} {}", fullname, line, info),
+                            ))
+                        }
+                    },
+                }
             }
+            VssType::Include(include) => {
+                leading.blank_lines = 0;
+                includes.push((kept.len(), count, include))
+            }
+            // lint directives are kept whatever the trivia mode
+            VssType::Comment(text) if data.trivia || text.contains(LINT_MARKER) => {
+                leading.comments.push(text.to_string())
+            }
+            VssType::Empty() if data.trivia => leading.blank_lines += 1,
+            _ => {}
+        }
+        input = pointer;
+    }
+    if let Some((_, line)) = pending {
+        return Err(AfbError::new(
+            "parsing-error",
+            format!("{}:{} flow map not closed", fullname, line),
+        ));
+    }
 
-            Ok(())
+    // included files are relative to current file directory, then to include directories
+    let dirname = filename.dirname.clone();
+    let outer = filename.prefix.clone();
+    let mut included: Vec<(usize, u32)> = Vec::new();
+    for (position, line, include) in includes {
+        let exists =
+            |file: &Filename| Path::new(&format!("{}{}", file.dirname, file.basename)).exists();
+        let prefix = vss_compose_prefix(outer.as_deref(), include.prefix.as_deref());
+        let mut filename = Filename::new(
+            include.filename.clone(),
+            Some(dirname.clone()),
+            prefix.clone(),
+        );
+        if !exists(&filename) {
+            for dir in &data.include_dirs {
+                let candidate =
+                    Filename::new(include.filename.clone(), Some(dir.clone()), prefix.clone());
+                if exists(&candidate) {
+                    filename = candidate;
+                    break;
+                }
+            }
         }
+        filename.origin = VssFileOrigin::Include {
+            location: VssLocation { file, line },
+            prefix: include.prefix,
+        };
+        data.files.push(filename);
+        included.push((position, data.files.len() as u32 - 1));
     }
+    data.files[file as usize].checksum = checksum;
+
+    // second pass: move kept lines toward buffer head, lines are never longer than original
+    // except expanded flow maps, source is then copied instead of being moved in place
+    let mut bytes = buffer.into_bytes();
+    let original = if expanded.is_empty() {
+        None
+    } else {
+        Some(bytes.clone())
+    };
+    let mut expanded = expanded.into_iter().peekable();
+    let mut firsts: Vec<u32> = Vec::with_capacity(kept.len());
+    let mut source = VssSource {
+        file,
+        buffer: String::new(),
+        starts: Vec::with_capacity(kept.len()),
+        lines: Vec::with_capacity(kept.len()),
+        includes: Vec::with_capacity(included.len()),
+        trivia: Vec::with_capacity(trivia.len()),
+        trailing: None,
+    };
+    let mut pending = included.iter().peekable();
+    let mut write = 0;
+    for (idx, (start, end, line)) in kept.iter().enumerate() {
+        while let Some((_, include)) = pending.next_if(|(position, _)| *position <= idx) {
+            source.includes.push((write as u32, *include));
+        }
+        firsts.push(write as u32);
+        if let Some((_, lines)) = expanded.next_if(|(position, _)| *position == idx) {
+            for text in lines {
+                source.starts.push(write as u32);
+                source.lines.push(*line);
+                write = vss_put_line(&mut bytes, write, text.as_bytes());
+            }
+            continue;
+        }
+        source.starts.push(write as u32);
+        source.lines.push(*line);
+        if let Some(original) = &original {
+            write = vss_put_line(&mut bytes, write, &original[*start..*end]);
+            continue;
+        }
+        bytes.copy_within(*start..*end, write);
+        write += end - start;
+        if write < bytes.len() {
+            bytes[write] = b'\n';
+        } else {
+            bytes.push(b'\n');
+        }
+        write += 1;
+    }
+    for (_, include) in pending {
+        source.includes.push((write as u32, *include));
+    }
+    bytes.truncate(write);
+    for (idx, value) in trivia {
+        if data.trivia || !value.is_empty() {
+            source.trivia.push((firsts[idx], value));
+        }
+    }
+    if data.trivia && !leading.is_empty() {
+        source.trailing = Some(leading);
+    }
+    source.buffer = match String::from_utf8(bytes) {
+        Ok(buffer) => buffer,
+        Err(error) => {
+            return Err(AfbError::new(
+                "vss-utf8-invalid",
+                format!("{} ({})", fullname, error),
+            ))
+        }
+    };
+    data.sources.push(source);
+
+    // sibling includes are read concurrently, then scanned in include order
+    let files = &data.files;
+    let reads = vss_parallel_map(&included, |(_, include)| {
+        vss_read_source(&files[*include as usize])
+    });
+    for ((_, include), read) in included.into_iter().zip(reads) {
+        vss_scan_source(data, include, read)?;
+    }
+    Ok(())
 }
 
+// read root file and its includes in RAM
+pub fn vss_from_file(vss: &mut VssHandle) -> Result<(), AfbError> {
+    vss_load_source(&mut vss.data, 0)?;
+    for overlay in vss.data.overlays.clone() {
+        vss_load_source(&mut vss.data, overlay)?;
+    }
+    // parser expects sources indexed by file
+    vss.data.sources.sort_by_key(|source| source.file);
+    Ok(())
+}
@@ -0,0 +1,433 @@
+/*
+ * Copyright (C) 2015-2023 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
+ * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
+ *
+ */
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::aggregate::*;
+use crate::index::*;
+use crate::types::*;
+use crate::units::*;
+
+fn parent_vpath(vpath: &str) -> &str {
+    match vpath.rsplit_once('.') {
+        Some((parent, _)) => parent,
+        None => "",
+    }
+}
+
+// check unit consistency with datatype, range and sibling signals
+pub fn vss_check_units(locator: &Locator, spec: &VssSpec) -> Vec<VssDiagnostic> {
+    let mut diagnostics = Vec::new();
+    let index = VssIndex::new(spec);
+
+    // parent vpath -> unit class -> first scaled unit seen
+    let mut siblings: BTreeMap<&str, Vec<(&VssUnitClass, &VssUnit)>> = BTreeMap::new();
+
+    for node in index.iter() {
+        let (datatype, unit) = match (node.datatype(), node.unit()) {
+            (Some(datatype), Some(unit)) if *unit != VssUnit::None => (datatype, unit),
+            _ => continue,
+        };
+        // unknown units are rejected while parsing
+        let info = match locator.units.get(unit) {
+            Some(info) => info,
+            None => continue,
+        };
+
+        if *unit == VssUnit::Timestamp {
+            if *datatype != VssValueType::String && *datatype != VssValueType::Uint64 {
+                diagnostics.push(VssDiagnostic::new(
+                    VssLevel::Error,
+                    "vss-unit-datatype",
+                    node.vpath(),
+                    format!(
+                        "unit:Timestamp requires string or uint64 datatype (not {})",
+                        datatype.to_str()
+                    ),
+                    node.location(),
+                ));
+            }
+        } else if !info.accept_datatype(datatype) {
+            diagnostics.push(VssDiagnostic::new(
+                VssLevel::Error,
+                "vss-unit-datatype",
+                node.vpath(),
+                format!(
+                    "unit:{} not allowed on {} datatype",
+                    unit.to_str(),
+                    datatype.to_str()
+                ),
+                node.location(),
+            ));
+        }
+
+        if *unit == VssUnit::percent {
+            let outside =
+                |value: Option<i64>| matches!(value, Some(value) if !(0..=100).contains(&value));
+            if outside(node.min()) || outside(node.max()) {
+                diagnostics.push(VssDiagnostic::new(
+                    VssLevel::Error,
+                    "vss-unit-range",
+                    node.vpath(),
+                    format!(
+                        "unit:percent min:{} max:{} not within 0..100",
+                        node.min()
+                            .map_or("unset".to_string(), |value| value.to_string()),
+                        node.max()
+                            .map_or("unset".to_string(), |value| value.to_string())
+                    ),
+                    node.location(),
+                ));
+            }
+        }
+
+        // only units sharing a linear or affine scale are comparable, this leaves out
+        // point in time (Timestamp) and logarithmic (dBm) units
+        if info.scale.is_none() || info.domain == VssUnitClass::None {
+            continue;
+        }
+        let known = siblings.entry(parent_vpath(node.vpath())).or_default();
+        match known
+            .iter()
+            .find(|(domain, _)| domain.is_convertible(&info.domain))
+        {
+            None => known.push((&info.domain, unit)),
+            Some((_, first)) if *first != unit => {
+                diagnostics.push(VssDiagnostic::new(
+                    VssLevel::Warning,
+                    "vss-unit-sibling",
+                    node.vpath(),
+                    format!(
+                        "unit:{} differs from sibling {} unit:{}",
+                        unit.to_str(),
+                        info.domain.to_str(),
+                        first.to_str()
+                    ),
+                    node.location(),
+                ));
+            }
+            Some(_) => {}
+        }
+    }
+    diagnostics
+}
+
+// former vpaths (fka) resolve to exactly one node and never shadow a current vpath
+pub fn vss_check_aliases(spec: &VssSpec) -> Vec<VssDiagnostic> {
+    let mut diagnostics = Vec::new();
+    let index = VssIndex::new(spec);
+    let mut claimed: BTreeMap<&str, &str> = BTreeMap::new();
+
+    // first definition (file, line) owns a disputed alias
+    let mut nodes = index
+        .iter()
+        .filter(|node| !node.fka().is_empty())
+        .collect::<Vec<VssNode>>();
+    nodes.sort_by_key(|node| (node.location().file, node.location().line));
+    for node in nodes {
+        for alias in node.fka() {
+            if index.get(alias).is_some() {
+                diagnostics.push(VssDiagnostic::new(
+                    VssLevel::Error,
+                    "vss-fka-collision",
+                    node.vpath(),
+                    format!("fka:{} is a current vpath", alias),
+                    node.location(),
+                ));
+                continue;
+            }
+            match claimed.get(alias.as_str()) {
+                None => {
+                    claimed.insert(alias, node.vpath());
+                }
+                Some(owner) => diagnostics.push(VssDiagnostic::new(
+                    VssLevel::Error,
+                    "vss-fka-duplicate",
+                    node.vpath(),
+                    format!("fka:{} already claimed by {}", alias, owner),
+                    node.location(),
+                )),
+            }
+        }
+    }
+    diagnostics
+}
+
+// aggregate branches hold leaves only read/written together: not empty, not nested within another
+// aggregate, and either static (attributes) or runtime (sensors/actuators) fields, never both
+pub fn vss_check_aggregates(spec: &VssSpec) -> Vec<VssDiagnostic> {
+    let mut diagnostics = Vec::new();
+    let index = VssIndex::new(spec);
+    let aggregates = vss_aggregates(&index);
+
+    for aggregate in &aggregates {
+        let vpath = aggregate.vpath();
+        let location = aggregate.branch.location;
+        if let Some(outer) = aggregates.iter().find(|outer| {
+            vpath.starts_with(outer.vpath()) && vpath[outer.vpath().len()..].starts_with('.')
+        }) {
+            diagnostics.push(VssDiagnostic::new(
+                VssLevel::Error,
+                "vss-aggregate-nested",
+                vpath,
+                format!("aggregate within aggregate:{}", outer.vpath()),
+                location,
+            ));
+        }
+
+        let first = match aggregate.fields.first() {
+            Some(field) => field.node,
+            None => {
+                diagnostics.push(VssDiagnostic::new(
+                    VssLevel::Error,
+                    "vss-aggregate-empty",
+                    vpath,
+                    "aggregate holds no sensor, actuator or attribute".to_string(),
+                    location,
+                ));
+                continue;
+            }
+        };
+        let is_static = |node: &VssNode| *node.vtype() == VssObjectType::Attribute;
+        for field in &aggregate.fields {
+            if is_static(&field.node) != is_static(&first) {
+                diagnostics.push(VssDiagnostic::new(
+                    VssLevel::Error,
+                    "vss-aggregate-mixed",
+                    field.node.vpath(),
+                    format!(
+                        "{} within aggregate:{} mixed with {}:{}",
+                        field.node.vtype().to_str(),
+                        vpath,
+                        first.vtype().to_str(),
+                        first.vpath()
+                    ),
+                    field.node.location(),
+                ));
+            }
+        }
+    }
+    diagnostics
+}
+
+// runtime value flattened to scalars, integers are widened to check datatype bounds
+enum VssScalar<'a> {
+    Bool(bool),
+    Str(&'a str),
+    Int(i128),
+    Real(f64),
+}
+
+impl fmt::Display for VssScalar<'_> {
+    fn fmt(&self, format: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VssScalar::Bool(value) => write!(format, "{}", value),
+            VssScalar::Str(value) => write!(format, "'{}'", value),
+            VssScalar::Int(value) => write!(format, "{}", value),
+            VssScalar::Real(value) => write!(format, "{}", value),
+        }
+    }
+}
+
+impl VssScalar<'_> {
+    // allowed values are kept as text within the spec
+    fn is_allowed(&self, allowed: &str) -> bool {
+        match self {
+            VssScalar::Bool(value) => allowed.parse::<bool>() == Ok(*value),
+            VssScalar::Str(value) => allowed == *value,
+            VssScalar::Int(value) => allowed.parse::<i128>() == Ok(*value),
+            VssScalar::Real(value) => allowed.parse::<f64>() == Ok(*value),
+        }
+    }
+}
+
+fn array_scalars<'a, T>(
+    values: &'a [T],
+    scalar: impl Fn(&'a T) -> VssScalar<'a>,
+) -> (Vec<VssScalar<'a>>, bool) {
+    (values.iter().map(scalar).collect(), true)
+}
+
+// return scalars and whether the value is an array
+fn value_scalars(value: &VssDataValue) -> (Vec<VssScalar<'_>>, bool) {
+    match value {
+        VssDataValue::NotAvailable => (Vec::new(), false),
+        VssDataValue::Bool(value) => (vec![VssScalar::Bool(*value)], false),
+        VssDataValue::String(value) => (vec![VssScalar::Str(value)], false),
+        VssDataValue::Int32(value) => (vec![VssScalar::Int(*value as i128)], false),
+        VssDataValue::Int64(value) => (vec![VssScalar::Int(*value as i128)], false),
+        VssDataValue::Uint32(value) => (vec![VssScalar::Int(*value as i128)], false),
+        VssDataValue::Uint64(value) => (vec![VssScalar::Int(*value as i128)], false),
+        VssDataValue::Float(value) => (vec![VssScalar::Real(*value as f64)], false),
+        VssDataValue::Double(value) => (vec![VssScalar::Real(*value)], false),
+        VssDataValue::BoolArray(values) => array_scalars(values, |value| VssScalar::Bool(*value)),
+        VssDataValue::StringArray(values) => array_scalars(values, |value| VssScalar::Str(value)),
+        VssDataValue::Int32Array(values) => {
+            array_scalars(values, |value| VssScalar::Int(*value as i128))
+        }
+        VssDataValue::Int64Array(values) => {
+            array_scalars(values, |value| VssScalar::Int(*value as i128))
+        }
+        VssDataValue::Uint32Array(values) => {
+            array_scalars(values, |value| VssScalar::Int(*value as i128))
+        }
+        VssDataValue::Uint64Array(values) => {
+            array_scalars(values, |value| VssScalar::Int(*value as i128))
+        }
+        VssDataValue::FloatArray(values) => {
+            array_scalars(values, |value| VssScalar::Real(*value as f64))
+        }
+        VssDataValue::DoubleArray(values) => array_scalars(values, |value| VssScalar::Real(*value)),
+    }
+}
+
+fn integer_bounds(datatype: &VssValueType) -> Option<(i128, i128)> {
+    match datatype {
+        VssValueType::Uint8 => Some((0, u8::MAX as i128)),
+        VssValueType::Int8 => Some((i8::MIN as i128, i8::MAX as i128)),
+        VssValueType::Uint16 => Some((0, u16::MAX as i128)),
+        VssValueType::Int16 => Some((i16::MIN as i128, i16::MAX as i128)),
+        VssValueType::Uint32 => Some((0, u32::MAX as i128)),
+        VssValueType::Int32 => Some((i32::MIN as i128, i32::MAX as i128)),
+        VssValueType::Uint64 => Some((0, u64::MAX as i128)),
+        VssValueType::Int64 => Some((i64::MIN as i128, i64::MAX as i128)),
+        _ => None,
+    }
+}
+
+fn check_scalar(
+    node: &VssNode,
+    datatype: &VssValueType,
+    scalar: &VssScalar,
+) -> Result<(), AfbError> {
+    let number = match (datatype, scalar) {
+        (VssValueType::Boolean, VssScalar::Bool(_)) => None,
+        (VssValueType::String, VssScalar::Str(_)) => None,
+        (VssValueType::Float | VssValueType::Double, VssScalar::Real(value)) => Some(*value),
+        (VssValueType::Float | VssValueType::Double, VssScalar::Int(value)) => Some(*value as f64),
+        (_, VssScalar::Int(value)) if integer_bounds(datatype).is_some() => {
+            let (min, max) = integer_bounds(datatype).unwrap();
+            if *value < min || *value > max {
+                return Err(AfbError::new(
+                    "vss-value-range",
+                    format!(
+                        "{} value:{} out of {} bounds",
+                        node.vpath(),
+                        value,
+                        datatype.to_str()
+                    ),
+                ));
+            }
+            Some(*value as f64)
+        }
+        _ => {
+            return Err(AfbError::new(
+                "vss-value-datatype",
+                format!(
+                    "{} value:{} does not match datatype:{}",
+                    node.vpath(),
+                    scalar,
+                    datatype.to_str()
+                ),
+            ))
+        }
+    };
+
+    if let Some(number) = number {
+        let below = matches!(node.min(), Some(min) if number < min as f64);
+        let above = matches!(node.max(), Some(max) if number > max as f64);
+        if below || above {
+            return Err(AfbError::new(
+                "vss-value-range",
+                format!(
+                    "{} value:{} not within min:{} max:{}",
+                    node.vpath(),
+                    scalar,
+                    node.min()
+                        .map_or("unset".to_string(), |value| value.to_string()),
+                    node.max()
+                        .map_or("unset".to_string(), |value| value.to_string())
+                ),
+            ));
+        }
+    }
+
+    let allowed = node.allowed();
+    if !allowed.is_empty() && !allowed.iter().any(|value| scalar.is_allowed(value)) {
+        return Err(AfbError::new(
+            "vss-value-allowed",
+            format!(
+                "{} value:{} not within allowed:[{}]",
+                node.vpath(),
+                scalar,
+                allowed.join(",")
+            ),
+        ));
+    }
+    Ok(())
+}
+
+// check a runtime value against node datatype, arraysize, min/max and allowed
+// NotAvailable is always accepted
+pub fn vss_check_value(node: &VssNode, value: &VssDataValue) -> Result<(), AfbError> {
+    let datatype = match node.datatype() {
+        Some(datatype) => datatype,
+        None => {
+            return Err(AfbError::new(
+                "vss-value-branch",
+                format!("{} is a branch and holds no value", node.vpath()),
+            ))
+        }
+    };
+    if *value == VssDataValue::NotAvailable {
+        return Ok(());
+    }
+
+    let (scalars, is_array) = value_scalars(value);
+    let mismatch = match node.arraysize() {
+        None => is_array,
+        Some(size) => !is_array || (size > 0 && scalars.len() != size),
+    };
+    if mismatch {
+        return Err(AfbError::new(
+            "vss-value-arraysize",
+            format!(
+                "{} {} value with {} item(s) does not match arraysize:{}",
+                node.vpath(),
+                if is_array { "array" } else { "scalar" },
+                scalars.len(),
+                node.arraysize()
+                    .map_or("none".to_string(), |value| value.to_string())
+            ),
+        ));
+    }
+
+    for scalar in &scalars {
+        check_scalar(node, datatype, scalar)?;
+    }
+    Ok(())
+}
+
+// value set by a client, only actuators are writable
+// brokers should call this before forwarding any set request
+pub fn vss_check_write(node: &VssNode, value: &VssDataValue) -> Result<(), AfbError> {
+    if !node.access().is_writable() {
+        return Err(AfbError::new(
+            "vss-value-readonly",
+            format!(
+                "{} type:{} refuses writes (access:{})",
+                node.vpath(),
+                node.vtype().to_str(),
+                node.access().to_str()
+            ),
+        ));
+    }
+    vss_check_value(node, value)
+}
@@ -0,0 +1,163 @@
+/*
+ * Copyright (C) 2015-2023 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
+ * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
+ *
+ * vss version profiles, vss.data.version selects the target specification
+ *   - keys unknown to every version are rejected while parsing, keys introduced after target
+ *     version are parsed and reported as 'vss-version-feature' warnings
+ *   - quantity files (4.x) are reported the same way
+ *
+ * differences between profiles:
+ *   |             | 3.x                                  | 4.x                                     |
+ *   |-------------|--------------------------------------|-----------------------------------------|
+ *   | keys        | all but fka                          | all (fka)                               |
+ *   | units       | built-in and unit files              | quantity files as well                  |
+ *   | datatypes   | primitives (and arrays), struct is   | primitives (and arrays), struct is      |
+ *   |             | a 'vss-datatype-struct' error        | a 'vss-datatype-struct' warning         |
+ *
+ * struct definitions are not resolved, nodes using one keep an unset datatype whatever the target.
+ * other validation rules (units, ranges, allowed, aggregates, fka, lint) do not depend on the target
+ */
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::types::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum VssVersion {
+    V3,
+    V4,
+}
+
+impl VssVersion {
+    pub fn latest() -> Self {
+        VssVersion::V4
+    }
+
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            VssVersion::V3 => "3.x",
+            VssVersion::V4 => "4.x",
+        }
+    }
+}
+
+impl fmt::Display for VssVersion {
+    fn fmt(&self, format: &mut fmt::Formatter) -> fmt::Result {
+        write!(format, "{}", self.to_str())
+    }
+}
+
+// major version only: 3, 3.1, v4, 4.x
+impl FromStr for VssVersion {
+    type Err = AfbError;
+
+    fn from_str(value: &str) -> Result<Self, AfbError> {
+        let text = value.trim_start_matches(['v', 'V']);
+        match text.split('.').next() {
+            Some("3") => Ok(VssVersion::V3),
+            Some("4") => Ok(VssVersion::V4),
+            _ => Err(AfbError::new(
+                "vss-version-invalid",
+                format!("version:{} is not one of 3.x|4.x", value),
+            )),
+        }
+    }
+}
+
+// node keys with the version introducing them, node type restrictions stay within the parser
+const VSS_KEYS: [(&str, VssVersion); 16] = [
+    ("type", VssVersion::V3),
+    ("description", VssVersion::V3),
+    ("comment", VssVersion::V3),
+    ("deprecation", VssVersion::V3),
+    ("datatype", VssVersion::V3),
+    ("arraysize", VssVersion::V3),
+    ("unit", VssVersion::V3),
+    ("min", VssVersion::V3),
+    ("max", VssVersion::V3),
+    ("allowed", VssVersion::V3),
+    ("default", VssVersion::V3),
+    ("aggregate", VssVersion::V3),
+    ("instances", VssVersion::V3),
+    ("name", VssVersion::V3),
+    ("instantiate", VssVersion::V3),
+    ("fka", VssVersion::V4),
+];
+
+// None when no version knows the key
+pub fn vss_key_version(key: &str) -> Option<VssVersion> {
+    VSS_KEYS
+        .iter()
+        .find(|(label, _)| *label == key)
+        .map(|(_, version)| *version)
+}
+
+// struct datatypes are named by the vpath of their struct definition (ex: Types.Position)
+pub fn vss_struct_reference(datatype: &str) -> bool {
+    datatype.contains('.')
+        && datatype
+            .split('.')
+            .all(|name| !name.is_empty() && name.chars().all(|chr| chr.is_ascii_alphanumeric()))
+}
+
+// valid 4.x datatype (definition not resolved), unknown to 3.x
+pub fn vss_check_struct(context: &VssContext, vpath: &str, datatype: &str, input: &str) {
+    if context.version < VssVersion::V4 {
+        context.warning(
+            "vss-version-feature",
+            vpath,
+            format!(
+                "struct datatype:{} requires vss {} (target {})",
+                datatype,
+                VssVersion::V4,
+                context.version
+            ),
+            input,
+        );
+        context.error(
+            "vss-datatype-struct",
+            vpath,
+            format!(
+                "struct datatype:{} is not a vss {} datatype",
+                datatype, context.version
+            ),
+            input,
+        );
+    } else {
+        context.warning(
+            "vss-datatype-struct",
+            vpath,
+            format!(
+                "struct datatype:{} definition is not resolved (datatype left unset)",
+                datatype
+            ),
+            input,
+        );
+    }
+}
+
+// features newer than target version that are not node keys
+pub fn vss_check_version(locator: &Locator) -> Vec<VssDiagnostic> {
+    let mut diagnostics = Vec::new();
+    let target = locator.data.version;
+    if target < VssVersion::V4 && locator.units.quantities().next().is_some() {
+        diagnostics.push(VssDiagnostic::new(
+            VssLevel::Warning,
+            "vss-version-feature",
+            "",
+            format!(
+                "quantity file requires vss {} (target {})",
+                VssVersion::V4,
+                target
+            ),
+            VssLocation { file: 0, line: 0 },
+        ));
+    }
+    diagnostics
+}
@@ -0,0 +1,281 @@
+/*
+ * Copyright (C) 2015-2023 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
+ * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
+ *
+ * minimal yaml reader for side files (units.yaml, quantities.yaml, ...)
+ * support: nested mappings, plain/quoted scalars, flow & block sequences, block scalars (| >)
+ */
+
+use nom::{
+    bytes::complete::take_while,
+    character::complete::{char, space0},
+    IResult,
+};
+
+use crate::scalar::*;
+use crate::types::*;
+use crate::utils::*;
+
+#[derive(Debug)]
+pub struct VssYamlNode {
+    pub key: String,
+    pub value: Option<String>,
+    pub list: Vec<String>,
+    pub line: u32,
+    pub children: Vec<VssYamlNode>,
+}
+
+impl VssYamlNode {
+    pub fn get(&self, key: &str) -> Option<&VssYamlNode> {
+        self.children.iter().find(|child| child.key == key)
+    }
+
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        match self.get(key) {
+            Some(child) => child.value.as_deref(),
+            None => None,
+        }
+    }
+}
+
+struct YamlLine<'a> {
+    line: u32,
+    indent: usize,
+    text: &'a str,
+}
+
+fn is_valid_key(chr: char) -> bool {
+    chr != ':' && chr != '#'
+}
+
+// key: value
+fn yaml_key(input: &str) -> IResult<&str, &str> {
+    let (input, key) = take_while(is_valid_key)(input)?;
+    let (input, _) = char(':')(input)?;
+    let (input, _) = space0(input)?;
+    Ok((input, key.trim_end()))
+}
+
+fn yaml_error(line: u32, info: &str) -> AfbError {
+    AfbError::new("yaml-parsing-error", format!("line:{} {}", line, info))
+}
+
+fn unquote(line: u32, text: &str) -> Result<String, AfbError> {
+    match yaml_unquote(text) {
+        Some(value) => Ok(value),
+        None => Err(yaml_error(
+            line,
+            &format!("invalid quoted scalar {}", text.trim()),
+        )),
+    }
+}
+
+// flow sequence/mapping content split on ',', a trailing ',' is accepted
+fn flow_items(line: u32, text: &str) -> Result<Vec<&str>, AfbError> {
+    let mut items = match flow_split(text, ',') {
+        Some(items) => items,
+        None => return Err(yaml_error(line, "unbalanced brackets or quotes")),
+    };
+    if items.last().is_some_and(|item| item.trim().is_empty()) {
+        items.pop();
+    }
+    Ok(items.into_iter().map(str::trim).collect())
+}
+
+struct YamlParser<'a> {
+    lines: Vec<YamlLine<'a>>,
+    index: usize,
+}
+
+impl<'a> YamlParser<'a> {
+    fn new(buffer: &'a str) -> Result<Self, AfbError> {
+        let mut lines = Vec::new();
+        for (idx, text) in buffer.lines().enumerate() {
+            let content = text.trim_start_matches(' ');
+            if content.starts_with('\t') {
+                return Err(yaml_error(idx as u32 + 1, "tab indentation is not allowed"));
+            }
+            lines.push(YamlLine {
+                line: idx as u32 + 1,
+                indent: text.len() - content.len(),
+                text: content,
+            });
+        }
+        Ok(YamlParser { lines, index: 0 })
+    }
+
+    fn is_blank(line: &YamlLine) -> bool {
+        line.text.is_empty() || line.text.starts_with('#') || line.text == "---"
+    }
+
+    // next significant line without consuming it
+    fn peek(&mut self) -> Option<&YamlLine<'a>> {
+        while self.index < self.lines.len() && Self::is_blank(&self.lines[self.index]) {
+            self.index += 1;
+        }
+        self.lines.get(self.index)
+    }
+
+    // raw lines more indented than 'indent' (block scalar)
+    fn block_scalar(&mut self, indent: usize, folded: bool) -> String {
+        let mut result: Vec<&str> = Vec::new();
+        while self.index < self.lines.len() {
+            let line = &self.lines[self.index];
+            if !line.text.is_empty() && line.indent <= indent {
+                break;
+            }
+            result.push(line.text);
+            self.index += 1;
+        }
+        while let Some(&"") = result.last() {
+            result.pop();
+        }
+        if folded {
+            result.join(" ")
+        } else {
+            result.join("\n")
+        }
+    }
+
+    // plain scalar may continue on more indented lines
+    fn continuation(&mut self, indent: usize, value: &str) -> String {
+        let mut value = value.to_string();
+        while let Some(line) = self.peek() {
+            if line.indent <= indent || line.text.starts_with("- ") || yaml_key(line.text).is_ok() {
+                break;
+            }
+            value.push(' ');
+            value.push_str(strip_comment(line.text));
+            self.index += 1;
+        }
+        value
+    }
+
+    // '- value' items at a given indentation
+    fn sequence(&mut self, indent: usize) -> Result<Vec<String>, AfbError> {
+        let mut result = Vec::new();
+        while let Some(line) = self.peek() {
+            if line.indent != indent || !(line.text.starts_with("- ") || line.text == "-") {
+                break;
+            }
+            let text = strip_comment(line.text[1..].trim_start());
+            result.push(unquote(line.line, text)?);
+            self.index += 1;
+        }
+        Ok(result)
+    }
+
+    fn flow_mapping(line: u32, text: &str) -> Result<Vec<VssYamlNode>, AfbError> {
+        let mut children = Vec::new();
+        for item in flow_items(line, &text[1..text.len() - 1])? {
+            let (value, key) = match yaml_key(item) {
+                Ok(result) => result,
+                Err(_) => return Err(yaml_error(line, "invalid flow mapping")),
+            };
+            children.push(VssYamlNode {
+                key: unquote(line, key)?,
+                value: Some(unquote(line, value)?),
+                list: Vec::new(),
+                line,
+                children: Vec::new(),
+            });
+        }
+        Ok(children)
+    }
+
+    fn mapping(&mut self, indent: usize) -> Result<Vec<VssYamlNode>, AfbError> {
+        let mut result = Vec::new();
+        loop {
+            let (line, text) = match self.peek() {
+                None => break,
+                Some(line) if line.indent < indent => break,
+                Some(line) if line.indent > indent => {
+                    return Err(yaml_error(line.line, "unexpected indentation"))
+                }
+                Some(line) => (line.line, line.text),
+            };
+            let (rest, key) = match yaml_key(text) {
+                Ok(result) => result,
+                Err(_) => return Err(yaml_error(line, "expect 'key: value'")),
+            };
+            self.index += 1;
+
+            let mut node = VssYamlNode {
+                key: unquote(line, key)?,
+                value: None,
+                list: Vec::new(),
+                line,
+                children: Vec::new(),
+            };
+
+            let rest = strip_comment(rest);
+            match rest.chars().next() {
+                Some('|') => node.value = Some(self.block_scalar(indent, false)),
+                Some('>') => node.value = Some(self.block_scalar(indent, true)),
+                Some('[') => {
+                    let mut flow = rest.to_string();
+                    while !flow.ends_with(']') {
+                        match self.peek() {
+                            Some(next) if next.indent > indent => {
+                                flow.push_str(strip_comment(next.text));
+                                self.index += 1;
+                            }
+                            _ => return Err(yaml_error(line, "unterminated flow sequence")),
+                        }
+                    }
+                    node.list = flow_items(line, &flow[1..flow.len() - 1])?
+                        .into_iter()
+                        .map(|item| unquote(line, item))
+                        .collect::<Result<_, _>>()?;
+                }
+                Some('{') if rest.ends_with('}') => {
+                    node.children = Self::flow_mapping(line, rest)?;
+                }
+                Some(_) => {
+                    let value = self.continuation(indent, rest);
+                    node.value = Some(unquote(line, &value)?);
+                }
+                None => {
+                    if let Some(next) = self.peek() {
+                        let next_indent = next.indent;
+                        let is_dash = next.text.starts_with("- ") || next.text == "-";
+                        if is_dash && next_indent >= indent {
+                            node.list = self.sequence(next_indent)?;
+                        } else if next_indent > indent {
+                            node.children = self.mapping(next_indent)?;
+                        }
+                    }
+                }
+            }
+            result.push(node);
+        }
+        Ok(result)
+    }
+}
+
+pub fn vss_yaml_parse(buffer: &str) -> Result<Vec<VssYamlNode>, AfbError> {
+    let mut parser = YamlParser::new(buffer)?;
+    let indent = match parser.peek() {
+        None => return Ok(Vec::new()),
+        Some(line) => line.indent,
+    };
+    let result = parser.mapping(indent)?;
+    if let Some(line) = parser.peek() {
+        return Err(yaml_error(line.line, "unexpected content"));
+    }
+    Ok(result)
+}
+
+pub fn vss_yaml_from_file(filename: &str) -> Result<Vec<VssYamlNode>, AfbError> {
+    let buffer = VssText::from_file(filename)?;
+    match vss_yaml_parse(buffer.text.as_str()) {
+        Ok(nodes) => Ok(nodes),
+        Err(error) => Err(AfbError::new(
+            "yaml-parsing-error",
+            format!("{} {}", filename, error.get_info()),
+        )),
+    }
+}
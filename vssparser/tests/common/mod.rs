@@ -0,0 +1,50 @@
+/*
+ * Copyright (C) 2015-2023 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
+ * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
+ *
+ * shared by integration tests, every test binary only uses part of it
+ */
+#![allow(dead_code)]
+
+use vssparser::prelude::*;
+
+pub fn spec_dir() -> String {
+    format!("{}/tests/spec/", env!("CARGO_MANIFEST_DIR"))
+}
+
+// handle options (trivia, version, duplicates) are set by setup before loading
+pub fn load_with(
+    dirname: Option<String>,
+    vspec: &str,
+    setup: impl FnOnce(&mut VssHandle),
+) -> Locator {
+    let mut vss = VssHandle::new(vspec.to_string(), dirname, None);
+    setup(&mut vss);
+    vss_from_file(&mut vss).expect("fail to load vspec");
+    Locator::new(vss).expect("fail to build locator")
+}
+
+pub fn load(vspec: &str) -> Locator {
+    load_with(Some(spec_dir()), vspec, |_| {})
+}
+
+pub fn parse_with(vspec: &str, setup: impl FnOnce(&mut VssHandle)) -> VssSpec {
+    let locator = load_with(Some(spec_dir()), vspec, setup);
+    vss_parse_rules(&locator).expect("fail to parse vspec")
+}
+
+// vspec is relative to tests/spec
+pub fn parse(vspec: &str) -> VssSpec {
+    parse_with(vspec, |_| {})
+}
+
+// loader and parser errors are returned to the test
+pub fn try_parse(vspec: &str) -> Result<VssSpec, AfbError> {
+    let mut vss = VssHandle::new(vspec.to_string(), Some(spec_dir()), None);
+    vss_from_file(&mut vss)?;
+    let locator = Locator::new(vss)?;
+    vss_parse_rules(&locator)
+}
@@ -0,0 +1,101 @@
+/*
+ * Copyright (C) 2015-2023 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
+ * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
+ *
+ */
+
+extern crate vssparser;
+
+mod common;
+
+use common::parse;
+use vssparser::prelude::*;
+
+#[test]
+fn aggregate_flag() {
+    let spec = parse("aggregate/aggregate.vspec");
+    let aggregate = |vpath: &str| {
+        spec.branches
+            .iter()
+            .find(|obj| obj.vpath == vpath)
+            .map(|obj| obj.aggregate)
+    };
+    assert_eq!(aggregate("Vehicle"), Some(false));
+    assert_eq!(aggregate("Vehicle.Seat"), Some(true));
+    assert_eq!(aggregate("Vehicle.Hvac"), Some(true));
+    assert_eq!(aggregate("Vehicle.Mirror"), Some(false));
+}
+
+#[test]
+fn aggregate_record() {
+    let spec = parse("aggregate/aggregate.vspec");
+    let index = VssIndex::new(&spec);
+    assert!(VssAggregate::new(&index, "Vehicle.Mirror").is_none());
+
+    let seat = VssAggregate::new(&index, "Vehicle.Seat").expect("not an aggregate");
+    let names = seat
+        .fields
+        .iter()
+        .map(|field| field.name)
+        .collect::<Vec<&str>>();
+    assert_eq!(names, vec!["Backrest.Recline", "Position"]);
+
+    let mut record = seat.record();
+    assert!(seat.check_write(&record).is_ok());
+    record
+        .values
+        .insert("Position".to_string(), VssDataValue::Uint32(500));
+    record
+        .values
+        .insert("Backrest.Recline".to_string(), VssDataValue::Float(12.5));
+    assert!(seat.check_write(&record).is_ok());
+
+    record
+        .values
+        .insert("Position".to_string(), VssDataValue::Uint32(5000));
+    assert!(seat
+        .check_record(&record)
+        .unwrap_err()
+        .to_str()
+        .starts_with("uid:vss-value-range "));
+
+    record.values.remove("Position");
+    assert!(seat
+        .check_record(&record)
+        .unwrap_err()
+        .to_str()
+        .starts_with("uid:vss-record-missing "));
+
+    let hvac = VssAggregate::new(&index, "Vehicle.Hvac").expect("not an aggregate");
+    let record = hvac.record();
+    assert!(hvac.check_record(&record).is_ok());
+    assert!(hvac
+        .check_write(&record)
+        .unwrap_err()
+        .to_str()
+        .starts_with("uid:vss-value-readonly "));
+}
+
+#[test]
+fn aggregate_validation() {
+    let spec = parse("aggregate/aggregate.vspec");
+    let found = spec
+        .diagnostics
+        .iter()
+        .map(|diagnostic| (diagnostic.uid.as_ref(), diagnostic.vpath.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        found,
+        vec![
+            ("vss-aggregate-empty", "Vehicle.Empty"),
+            ("vss-aggregate-mixed", "Vehicle.Mixed.Inner.Value"),
+            ("vss-aggregate-mixed", "Vehicle.Mixed.Level"),
+            ("vss-aggregate-nested", "Vehicle.Mixed.Inner"),
+        ]
+    );
+    let index = VssIndex::new(&spec);
+    assert_eq!(vss_aggregates(&index).len(), 5);
+}
@@ -0,0 +1,192 @@
+/*
+ * Copyright (C) 2015-2023 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
+ * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
+ *
+ */
+
+extern crate vssparser;
+
+mod common;
+
+use std::fs;
+
+use common::{load_with, parse};
+use vssparser::prelude::*;
+
+fn parse_file(filename: &str) -> VssSpec {
+    vss_parse_rules(&load_with(None, filename, |_| {})).expect("fail to parse vspec")
+}
+
+fn default<'a>(node: &VssNode<'a>) -> &'a [String] {
+    match node {
+        VssNode::Branch(_) => &[],
+        VssNode::Sensor(sensor) => &sensor.default,
+        VssNode::Actuator(actuator) => &actuator.default,
+        VssNode::Attribute(attribute) => &attribute.default,
+    }
+}
+
+fn refused(bytes: &[u8]) -> String {
+    match VssCatalog::new(bytes) {
+        Ok(_) => panic!("invalid catalog accepted"),
+        Err(error) => error.to_str().to_string(),
+    }
+}
+
+#[test]
+fn catalog_matches_index() {
+    let spec = parse("vehicle.vspec");
+    let bytes = vss_catalog_build(&spec);
+    let catalog = VssCatalog::new(&bytes).expect("fail to open catalog");
+    assert_eq!(catalog.version(), CATALOG_VERSION);
+
+    // catalog holds expanded instances
+    let expanded = vss_expand_instances(&spec);
+    let index = VssIndex::new(&expanded);
+    assert_eq!(catalog.len(), index.len());
+    assert!(catalog
+        .get("Vehicle.Cabin.Door.Row2.PassengerSide.IsOpen")
+        .is_some());
+
+    for (node, entry) in index.iter().zip(catalog.iter()) {
+        let vpath = node.vpath();
+        assert_eq!(entry.vpath(), vpath);
+        assert_eq!(
+            catalog.get(vpath).map(|entry| entry.index()),
+            Some(entry.index())
+        );
+        assert_eq!(entry.vtype(), *node.vtype(), "{}", vpath);
+        assert_eq!(entry.datatype().as_ref(), node.datatype(), "{}", vpath);
+        assert_eq!(entry.description(), node.description().map(String::as_str));
+        assert_eq!(entry.comment(), node.comment().map(String::as_str));
+        let unit = node
+            .unit()
+            .filter(|unit| **unit != VssUnit::None)
+            .map(|unit| unit.to_str());
+        assert_eq!(entry.unit(), unit, "{}", vpath);
+        assert_eq!(entry.allowed().collect::<Vec<_>>(), node.allowed());
+        assert_eq!(entry.default().collect::<Vec<_>>(), default(&node));
+        assert_eq!(entry.min(), node.min(), "{}", vpath);
+        assert_eq!(entry.max(), node.max(), "{}", vpath);
+        assert_eq!(entry.arraysize(), node.arraysize(), "{}", vpath);
+        let aggregate = matches!(node, VssNode::Branch(branch) if branch.aggregate);
+        assert_eq!(entry.aggregate(), aggregate);
+        assert_eq!(entry.uuid(), vss_uuid(vpath));
+        // undeclared intermediate branches (ex: include prefix) have no record
+        let parent = vpath
+            .rsplit_once('.')
+            .and_then(|(parent, _)| index.get(parent))
+            .map(|parent| parent.vpath());
+        assert_eq!(entry.parent().map(|parent| parent.vpath()), parent);
+        let children = index
+            .children(vpath)
+            .iter()
+            .map(|child| child.vpath())
+            .filter(|child| child.rsplit_once('.').map(|(parent, _)| parent) == Some(vpath))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            entry
+                .children()
+                .map(|child| child.vpath())
+                .collect::<Vec<_>>(),
+            children
+        );
+    }
+
+    let speed = catalog.get("Vehicle.Speed").expect("vpath not found");
+    assert_eq!(speed.unit(), Some("km/h"));
+    assert_eq!((speed.min(), speed.max()), (Some(0), Some(250)));
+    assert!(catalog.get("Vehicle.Unknown").is_none());
+    assert!(catalog.get("").is_none());
+    assert!(catalog.node(catalog.len()).is_none());
+}
+
+#[test]
+fn corrupted_catalog_is_refused() {
+    let spec = parse("vehicle.vspec");
+    let bytes = vss_catalog_build(&spec);
+
+    let mut flipped = bytes.clone();
+    let last = flipped.len() - 1;
+    flipped[last] ^= 0x01;
+    assert!(refused(&flipped).starts_with("uid:vss-catalog-checksum "));
+
+    let mut version = bytes.clone();
+    version[4] += 1;
+    assert!(refused(&version).starts_with("uid:vss-catalog-version "));
+
+    let truncated = &bytes[..bytes.len() - 10];
+    let error = refused(truncated);
+    assert!(error.starts_with("uid:vss-catalog-invalid "));
+    assert!(error.contains("truncated or inconsistent catalog"));
+
+    let error = refused(&bytes[..16]);
+    assert!(error.contains("invalid magic"));
+}
+
+// rewrite one u32 within a copy of catalog, checksum is updated to reach reference checks
+fn patched(bytes: &[u8], offset: usize, value: u32) -> Vec<u8> {
+    let mut patched = bytes.to_vec();
+    patched[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    let checksum = vss_crc32(&patched[36..]);
+    patched[28..32].copy_from_slice(&checksum.to_le_bytes());
+    patched
+}
+
+#[test]
+fn oversized_references_are_refused() {
+    let spec = parse("vehicle.vspec");
+    let bytes = vss_catalog_build(&spec);
+    let inconsistent = |offset: usize| refused(&patched(&bytes, offset, u32::MAX));
+
+    // node count, data offset, data and payload sizes
+    for offset in [8, 16, 20, 24] {
+        assert!(
+            inconsistent(offset).contains("truncated or inconsistent catalog"),
+            "header offset:{}",
+            offset
+        );
+    }
+
+    // first node record: vpath reference (offset, len), allowed list (offset, count)
+    let record = 36;
+    let error = refused(&patched(&bytes, record + 4, u32::MAX));
+    assert!(error.contains("invalid string reference"), "{}", error);
+    let error = refused(&patched(&bytes, record, u32::MAX - 1));
+    assert!(error.contains("invalid string reference"), "{}", error);
+    let error = refused(&patched(&bytes, record + 32 + 4, u32::MAX));
+    assert!(error.contains("invalid list reference"), "{}", error);
+    let error = refused(&patched(&bytes, record + 32, u32::MAX));
+    assert!(error.contains("invalid list reference"), "{}", error);
+}
+
+#[test]
+fn stale_sources_are_refused() {
+    let dirname = std::env::temp_dir().join(format!("vss-catalog-{}", std::process::id()));
+    fs::create_dir_all(&dirname).unwrap();
+    let filename = dirname.join("stale.vspec");
+    let filename = filename.to_str().unwrap();
+    let source = "Vehicle:\n  type: branch\n  description: Vehicle.\n";
+
+    fs::write(filename, source).unwrap();
+    let spec = parse_file(filename);
+    let bytes = vss_catalog_build(&spec);
+    let catalog = VssCatalog::with_sources(&bytes, &spec.files).expect("fresh catalog refused");
+    assert_eq!(catalog.sources(), vss_catalog_sources(&spec.files));
+
+    // same nodes, different source content
+    fs::write(filename, source.replace("Vehicle.\n", "Edited.\n")).unwrap();
+    let edited = parse_file(filename);
+    let error = VssCatalog::with_sources(&bytes, &edited.files)
+        .err()
+        .expect("stale catalog accepted");
+    assert!(error.to_str().starts_with("uid:vss-catalog-stale "));
+    assert!(catalog.check_sources(&edited.files).is_err());
+    // target side has no sources and only checks catalog integrity
+    assert!(VssCatalog::new(&bytes).is_ok());
+
+    fs::remove_dir_all(&dirname).unwrap();
+}
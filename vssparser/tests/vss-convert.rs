@@ -0,0 +1,92 @@
+/*
+ * Copyright (C) 2015-2023 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
+ * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
+ *
+ */
+
+extern crate vssparser;
+
+use vssparser::prelude::*;
+
+fn assert_near(value: f64, expected: f64) {
+    assert!((value - expected).abs() < 1e-9, "{} != {}", value, expected);
+}
+
+#[test]
+fn affine_conversion_and_inverse() {
+    let pool = VssUnitInfo::get_pool();
+    let convert = |value: f64, from: VssUnit, to: VssUnit| {
+        pool.convert(value, &from, &to).expect("conversion refused")
+    };
+
+    assert_near(convert(100.0, VssUnit::celsius, VssUnit::fahrenheit), 212.0);
+    assert_near(convert(-40.0, VssUnit::celsius, VssUnit::fahrenheit), -40.0);
+    assert_near(convert(32.0, VssUnit::fahrenheit, VssUnit::celsius), 0.0);
+    assert_near(convert(0.0, VssUnit::celsius, VssUnit::K), 273.15);
+
+    // back and forth returns the original value
+    let fahrenheit = convert(21.5, VssUnit::celsius, VssUnit::fahrenheit);
+    assert_near(
+        convert(fahrenheit, VssUnit::fahrenheit, VssUnit::celsius),
+        21.5,
+    );
+
+    // affine scale offset applies on reference side only
+    let scale = VssUnit::celsius.get_scale().expect("celsius has no scale");
+    assert_near(scale.to_reference(25.0), 298.15);
+    assert_near(scale.from_reference(298.15), 25.0);
+}
+
+#[test]
+fn linear_inverse_and_identity() {
+    let pool = VssUnitInfo::get_pool();
+    let convert = |value: f64, from: VssUnit, to: VssUnit| {
+        pool.convert(value, &from, &to).expect("conversion refused")
+    };
+
+    assert_near(convert(36.0, VssUnit::km_h, VssUnit::m_s), 10.0);
+    assert_near(convert(10.0, VssUnit::m_s, VssUnit::km_h), 36.0);
+
+    // mpg is the inverse of l/100km
+    let mpg = convert(5.0, VssUnit::l_100km, VssUnit::mpg);
+    assert_near(mpg, 100.0 * 3.785411784 / 1.609344 / 5.0);
+    assert_near(convert(mpg, VssUnit::mpg, VssUnit::l_100km), 5.0);
+
+    // same unit leaves value untouched, even without rounding through reference
+    assert_eq!(convert(0.1, VssUnit::km_h, VssUnit::km_h), 0.1);
+    assert_eq!(convert(37.2, VssUnit::celsius, VssUnit::celsius), 37.2);
+
+    // same unit is accepted even when it has no scale
+    assert_eq!(
+        convert(1700000000.0, VssUnit::Timestamp, VssUnit::Timestamp),
+        1700000000.0
+    );
+}
+
+#[test]
+fn conversion_errors() {
+    let pool = VssUnitInfo::get_pool();
+
+    let error = pool
+        .convert(50.0, &VssUnit::km_h, &VssUnit::celsius)
+        .expect_err("cross domain conversion accepted");
+    assert!(error.to_str().starts_with("uid:vss-unit-domain "));
+    assert!(error.to_str().contains("km/h"));
+
+    let error = pool
+        .convert(1.0, &VssUnit::from_label("furlong"), &VssUnit::m)
+        .expect_err("unknown unit accepted");
+    assert!(error.to_str().starts_with("uid:vss-unit-unknown "));
+    assert!(error.to_str().contains("unit:furlong"));
+
+    let error = pool
+        .convert(1.0, &VssUnit::m, &VssUnit::from_label("furlong"))
+        .expect_err("unknown unit accepted");
+    assert!(error.to_str().starts_with("uid:vss-unit-unknown "));
+
+    // unit without scale cannot be converted
+    assert_eq!(VssUnit::Timestamp.get_scale(), None);
+}
@@ -0,0 +1,148 @@
+/*
+ * Copyright (C) 2015-2023 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
+ * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
+ *
+ */
+
+extern crate vssparser;
+
+mod common;
+
+use common::load;
+use vssparser::prelude::*;
+
+#[test]
+fn multiline_texts_stay_within_table_rows() {
+    let locator = load("vehicle.vspec");
+    let mut spec = vss_parse_rules(&locator).expect("fail to parse vspec");
+    let vehicle = spec
+        .branches
+        .iter_mut()
+        .find(|branch| branch.vpath == "Vehicle")
+        .expect("no Vehicle branch");
+    vehicle.description = Some("High-level vehicle data.\nSecond line.".to_string());
+
+    let doc = vss_doc_build(&spec, &locator.units, VssDocFormat::Markdown, None);
+    assert!(doc.contains("| description | High-level vehicle data.<br>Second line. |\n"));
+
+    // every table line is a complete row
+    let mut rows = 0;
+    for line in doc.lines().filter(|line| line.starts_with('|')) {
+        assert!(line.ends_with(" |") || line.ends_with("---|"), "{}", line);
+        rows += 1;
+    }
+    assert!(rows > 20);
+    let table = doc
+        .split("## Vehicle\n")
+        .nth(1)
+        .and_then(|section| section.split("\n\n").nth(1))
+        .expect("no Vehicle table");
+    assert_eq!(table.lines().count(), 6);
+}
+
+// rows of one node section, up to its children table
+fn section<'a>(doc: &'a str, title: &str) -> Vec<&'a str> {
+    doc.split(&format!("{}\n\n", title))
+        .nth(1)
+        .and_then(|section| section.split("\n\n").next())
+        .expect("no section")
+        .lines()
+        .collect()
+}
+
+#[test]
+fn unit_rows_and_signal_tree() {
+    let locator = load("vehicle.vspec");
+    let spec = vss_parse_rules(&locator).expect("fail to parse vspec");
+    let doc = vss_doc_build(&spec, &locator.units, VssDocFormat::Markdown, None);
+
+    // unit symbol is followed by registry label and description
+    let speed = section(&doc, "### Vehicle.Speed");
+    assert!(speed
+        .contains(&"| unit | km/h (kilometer per hour: Speed measured in kilometers per hours) |"));
+    assert!(speed.contains(&"| range | 0 .. 250 |"));
+    let width = section(&doc, "### Vehicle.Width");
+    assert!(width.contains(&"| unit | mm (millimeter: Distance measured in millimeters) |"));
+    let door = section(&doc, "### Vehicle.Cabin.Door.IsOpen");
+    assert!(door.iter().all(|row| !row.starts_with("| unit ")));
+
+    // branches nest by depth, undeclared intermediate branches are skipped
+    let tree = doc
+        .split("## Signal tree\n\n")
+        .nth(1)
+        .and_then(|tree| tree.split("\n\n").next())
+        .expect("no signal tree");
+    assert_eq!(
+        tree,
+        "- [Vehicle](#vehicle)\n  \
+           - [Body](#vehicle-body)\n    \
+             - [Hood](#vehicle-body-hood)\n    \
+             - [Beam](#vehicle-body-lights-beam)\n  \
+           - [Cabin](#vehicle-cabin)\n    \
+             - [Door](#vehicle-cabin-door)"
+    );
+}
+
+#[test]
+fn html_output() {
+    let locator = load("vehicle.vspec");
+    let mut spec = vss_parse_rules(&locator).expect("fail to parse vspec");
+    let speed = spec
+        .sensors
+        .iter_mut()
+        .find(|sensor| sensor.vpath == "Vehicle.Speed")
+        .expect("no Vehicle.Speed sensor");
+    speed.description = Some("Speed <km/h> & \"rounded\".".to_string());
+
+    let html = vss_doc_build(&spec, &locator.units, VssDocFormat::Html, None);
+    assert!(html.starts_with("<!DOCTYPE html>\n<html>\n"));
+    assert!(html.ends_with("</body>\n</html>\n"));
+    assert_eq!(
+        html.matches("<section id=").count(),
+        VssIndex::new(&spec).len()
+    );
+    assert_eq!(
+        html.matches("<section").count(),
+        html.matches("</section>").count()
+    );
+
+    // texts are escaped, within field table and parent children table
+    let escaped = "Speed &lt;km/h&gt; &amp; &quot;rounded&quot;.";
+    assert!(html.contains(&format!(
+        "<tr><th>description</th><td>{}</td></tr>",
+        escaped
+    )));
+    assert!(html.contains(&format!(
+        "<tr><td><a href=\"#vehicle-speed\">Speed</a></td><td>sensor</td><td>{}</td></tr>",
+        escaped
+    )));
+    assert!(html.contains(
+        "<tr><th>unit</th><td>km/h (kilometer per hour: Speed measured in kilometers per hours)</td></tr>"
+    ));
+
+    // navigation sub-lists stay within their parent item
+    let nav = html
+        .split("<nav>\n")
+        .nth(1)
+        .and_then(|nav| nav.split("</nav>").next())
+        .expect("no navigation");
+    assert_eq!(
+        nav,
+        concat!(
+            "<ul>\n",
+            "<li><a href=\"#vehicle\">Vehicle</a><ul>\n",
+            "<li><a href=\"#vehicle-body\">Body</a><ul>\n",
+            "<li><a href=\"#vehicle-body-hood\">Hood</a></li>\n",
+            "<li><a href=\"#vehicle-body-lights-beam\">Beam</a></li>\n",
+            "</ul>\n</li>\n",
+            "<li><a href=\"#vehicle-cabin\">Cabin</a><ul>\n",
+            "<li><a href=\"#vehicle-cabin-door\">Door</a></li>\n",
+            "</ul>\n</li>\n",
+            "</ul>\n</li>\n",
+            "</ul>\n",
+        )
+    );
+}
@@ -0,0 +1,101 @@
+/*
+ * Copyright (C) 2015-2023 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
+ * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
+ *
+ */
+
+extern crate vssparser;
+
+mod common;
+
+use std::fs;
+
+use common::{parse, spec_dir};
+use vssparser::prelude::*;
+
+#[test]
+fn decode_text() {
+    let decoded = VssText::decode(b"\xEF\xBB\xBFa: 1\r\nb: 2\rc: 3\n".to_vec());
+    assert_eq!(decoded.text, "a: 1\nb: 2\nc: 3\n");
+    assert!(decoded.bom && decoded.crlf);
+    assert_eq!(decoded.invalid, None);
+    assert_eq!(decoded.encode("a\n"), "\u{FEFF}a\r\n");
+
+    let decoded = VssText::decode(b"a: 1\nb: caf\xE9\n".to_vec());
+    assert_eq!(decoded.text, "a: 1\nb: caf\u{FFFD}\n");
+    assert_eq!(decoded.invalid, Some(2));
+}
+
+#[test]
+fn utf8_bom_crlf() {
+    let spec = parse("encoding/utf8.vspec");
+    assert!(spec.diagnostics.is_empty());
+    let index = VssIndex::new(&spec);
+
+    let vehicle = index.get("Vehicle").expect("vpath not found");
+    assert_eq!(vehicle.location().line, 2);
+    assert_eq!(
+        vehicle.description_l10n(Some("fr")),
+        Some("Données de haut niveau du véhicule.")
+    );
+
+    let lights = index.get("Vehicle.Lights").expect("vpath not found");
+    assert_eq!(lights.allowed(), ["Éteint", "Allumé", "Automatique"]);
+    assert_eq!(
+        lights.description().map(String::as_str),
+        Some("Mode d’éclairage « auto »")
+    );
+    assert_eq!(
+        lights.comment().map(String::as_str),
+        Some("Température en °C, valeur ≥ 0.")
+    );
+}
+
+#[test]
+fn invalid_utf8_is_located() {
+    let spec = parse("encoding/latin1.vspec");
+    let found = spec
+        .diagnostics
+        .iter()
+        .map(|diagnostic| {
+            (
+                diagnostic.level.clone(),
+                diagnostic.uid.as_ref(),
+                diagnostic.location.line,
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(found, vec![(VssLevel::Error, "vss-utf8-invalid", 8)]);
+    assert_eq!(
+        spec.filename(spec.diagnostics[0].location).basename,
+        "latin1.vspec"
+    );
+
+    // formatter never rewrites a file it cannot decode
+    let error = vss_format_file(
+        &format!("{}encoding/latin1.vspec", spec_dir()),
+        &VssFormatOptions::default(),
+        true,
+    )
+    .expect_err("invalid utf-8 formatted");
+    assert!(error.to_str().starts_with("uid:vss-utf8-invalid "));
+}
+
+#[test]
+fn format_keeps_bom_and_crlf() {
+    let filename = std::env::temp_dir().join(format!("vss-encoding-{}.vspec", std::process::id()));
+    fs::copy(format!("{}encoding/utf8.vspec", spec_dir()), &filename).expect("fail to copy");
+    let filename = filename.to_str().expect("invalid temp path").to_string();
+
+    let formatted = vss_format_file(&filename, &VssFormatOptions::default(), false);
+    let bytes = fs::read(&filename).expect("fail to read");
+    fs::remove_file(&filename).expect("fail to remove");
+    assert_eq!(formatted.ok(), Some(false));
+    assert!(bytes.starts_with(b"\xEF\xBB\xBF"));
+    assert!(!bytes
+        .windows(2)
+        .any(|pair| pair[1] == b'\n' && pair[0] != b'\r'));
+}
@@ -0,0 +1,75 @@
+/*
+ * Copyright (C) 2015-2023 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
+ * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
+ *
+ */
+
+extern crate vssparser;
+
+mod common;
+
+use common::parse;
+use vssparser::prelude::*;
+
+#[test]
+fn fka_parsed_on_every_node_type() {
+    let spec = parse("fka/fka.vspec");
+    let index = VssIndex::new(&spec);
+    let fka = |vpath: &str| index.get(vpath).expect("vpath not found").fka().to_vec();
+    assert_eq!(fka("Vehicle.Cabin.Seat"), vec!["Vehicle.Cabin.Chair"]);
+    assert_eq!(
+        fka("Vehicle.Cabin.Seat.Position"),
+        vec!["Vehicle.Cabin.Chair.Pos", "Vehicle.Cabin.SeatPos"]
+    );
+    assert_eq!(fka("Vehicle.Speed"), vec!["Vehicle.OBD.Speed"]);
+    assert_eq!(fka("Vehicle.Width"), vec!["Vehicle.Size.Width"]);
+    assert!(fka("Vehicle").is_empty());
+}
+
+#[test]
+fn resolve_former_vpath() {
+    let spec = parse("fka/fka.vspec");
+    let index = VssIndex::new(&spec);
+
+    let resolved = index.resolve("Vehicle.Speed").expect("vpath not found");
+    assert_eq!(resolved.alias, None);
+    assert_eq!(resolved.notice(), None);
+
+    let resolved = index
+        .resolve("Vehicle.Cabin.SeatPos")
+        .expect("alias not found");
+    assert_eq!(resolved.node.vpath(), "Vehicle.Cabin.Seat.Position");
+    assert_eq!(resolved.alias, Some("Vehicle.Cabin.SeatPos"));
+    assert!(resolved
+        .notice()
+        .expect("missing notice")
+        .contains("use Vehicle.Cabin.Seat.Position"));
+
+    // a former vpath shadowing a current one never wins
+    let resolved = index
+        .resolve("Vehicle.Cabin.Seat")
+        .expect("vpath not found");
+    assert_eq!(resolved.node.vpath(), "Vehicle.Cabin.Seat");
+    assert!(index.resolve("Vehicle.Cabin.Chair.Unknown").is_none());
+}
+
+#[test]
+fn fka_collisions() {
+    let spec = parse("fka/fka.vspec");
+    let found = spec
+        .diagnostics
+        .iter()
+        .map(|diagnostic| (diagnostic.uid.as_ref(), diagnostic.vpath.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        found,
+        vec![
+            ("vss-fka-collision", "Vehicle.Cabin.Door"),
+            ("vss-fka-duplicate", "Vehicle.Cabin.Door.IsOpen"),
+        ]
+    );
+    assert!(spec.diagnostics[1].info.contains("Vehicle.Speed"));
+}
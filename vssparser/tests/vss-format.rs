@@ -0,0 +1,134 @@
+/*
+ * Copyright (C) 2015-2023 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
+ * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
+ *
+ */
+
+extern crate vssparser;
+
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use common::load_with;
+use vssparser::prelude::*;
+
+// fixtures refused by the loader on purpose
+const INVALID: &[&str] = &["tab.vspec", "duplicate-key.vspec", "latin1.vspec"];
+
+const ROOTS: &[&str] = &[
+    "vehicle.vspec",
+    "diagnostic.vspec",
+    "include/root.vspec",
+    "duplicate/root.vspec",
+    "aggregate/aggregate.vspec",
+    "fka/fka.vspec",
+    "yaml/yaml.vspec",
+    "encoding/utf8.vspec",
+    "units/check.vspec",
+    "format/escape.vspec",
+];
+
+fn fixtures() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/spec")
+}
+
+// copy fixture tree, return copied vspec files
+fn copy_tree(source: &Path, target: &Path, vspecs: &mut Vec<PathBuf>) {
+    fs::create_dir_all(target).unwrap();
+    for entry in fs::read_dir(source).unwrap() {
+        let path = entry.unwrap().path();
+        let copy = target.join(path.file_name().unwrap());
+        if path.is_dir() {
+            copy_tree(&path, &copy, vspecs);
+        } else {
+            fs::copy(&path, &copy).unwrap();
+            let name = path.file_name().unwrap().to_str().unwrap();
+            if name.ends_with(".vspec") && !INVALID.contains(&name) {
+                vspecs.push(copy);
+            }
+        }
+    }
+}
+
+fn parse(dirname: &Path, vspec: &str) -> VssSpec {
+    let dirname = format!("{}/", dirname.to_str().unwrap());
+    vss_parse_rules(&load_with(Some(dirname), vspec, |_| {})).expect("fail to parse vspec")
+}
+
+fn tmpdir(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("vss-format-{}-{}", name, std::process::id()))
+}
+
+#[test]
+fn format_is_stable_and_keeps_spec() {
+    let dirname = tmpdir("fixtures");
+    let mut vspecs = Vec::new();
+    copy_tree(&fixtures(), &dirname, &mut vspecs);
+    assert!(vspecs.len() >= ROOTS.len());
+
+    let options = VssFormatOptions::default();
+    for vspec in &vspecs {
+        let filename = vspec.to_str().unwrap();
+        vss_format_file(filename, &options, false).expect("fail to format");
+        // second pass finds nothing to change
+        assert!(
+            vss_format_file(filename, &options, true).expect("fail to check"),
+            "{} not stable",
+            filename
+        );
+        let text = VssText::from_file(filename).expect("fail to read").text;
+        assert_eq!(vss_format(&text, &options), text, "{}", filename);
+    }
+
+    for root in ROOTS {
+        let original = parse(&fixtures(), root);
+        let formatted = parse(&dirname, root);
+        assert_eq!(
+            vss_export_build(&formatted, VssExportFormat::Json, None),
+            vss_export_build(&original, VssExportFormat::Json, None),
+            "{}",
+            root
+        );
+        let original_index = VssIndex::new(&original);
+        for node in VssIndex::new(&formatted).iter() {
+            let expected = original_index.get(node.vpath()).expect("vpath lost");
+            assert_eq!(node.l10n(), expected.l10n(), "{}", node.vpath());
+            assert_eq!(node.vtype(), expected.vtype(), "{}", node.vpath());
+        }
+        let uids = |spec: &VssSpec| {
+            spec.diagnostics
+                .iter()
+                .map(|diagnostic| (diagnostic.uid.to_string(), diagnostic.vpath.clone()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(uids(&formatted), uids(&original), "{}", root);
+    }
+    fs::remove_dir_all(&dirname).unwrap();
+}
+
+#[test]
+fn check_mode_never_writes() {
+    let dirname = tmpdir("check");
+    fs::create_dir_all(&dirname).unwrap();
+    let filename = dirname.join("unformatted.vspec");
+    let filename = filename.to_str().unwrap();
+    let source = "Vehicle:\n    description: \"Vehicle.\"\n    type: branch\n";
+    fs::write(filename, source).unwrap();
+
+    let options = VssFormatOptions::default();
+    assert!(!vss_format_file(filename, &options, true).expect("fail to check"));
+    assert_eq!(fs::read_to_string(filename).unwrap(), source);
+
+    assert!(!vss_format_file(filename, &options, false).expect("fail to format"));
+    assert_eq!(
+        fs::read_to_string(filename).unwrap(),
+        "Vehicle:\n  type: branch\n  description: \"Vehicle.\"\n"
+    );
+    assert!(vss_format_file(filename, &options, true).expect("fail to check"));
+    fs::remove_dir_all(&dirname).unwrap();
+}
@@ -0,0 +1,156 @@
+/*
+ * Copyright (C) 2015-2023 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
+ * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
+ *
+ */
+
+extern crate vssparser;
+
+mod common;
+
+use common::parse;
+use vssparser::prelude::*;
+
+fn graph(format: VssGraphFormat, options: &VssGraphOptions) -> String {
+    vss_graph_build(&parse("graph/graph.vspec"), format, options).expect("fail to build graph")
+}
+
+fn cabin() -> VssGraphOptions {
+    VssGraphOptions {
+        root: Some("Vehicle.Cabin".to_string()),
+        ..VssGraphOptions::default()
+    }
+}
+
+#[test]
+fn dot_subtree() {
+    let expected = "digraph vss {
+  rankdir=LR;
+  node [shape=box, style=\"rounded,filled\", fillcolor=\"#ffffff\"];
+  n0 [label=\"Cabin\", fillcolor=\"#d9d9d9\"];
+  n1 [label=\"Door\\nRow1,Row2\\nLeft,Right\", fillcolor=\"#d9d9d9\"];
+  n2 [label=\"IsOpen\", fillcolor=\"#fdae61\"];
+  n0 -> n1;
+  n1 -> n2;
+}
+";
+    assert_eq!(graph(VssGraphFormat::Dot, &cabin()), expected);
+}
+
+#[test]
+fn mermaid_subtree() {
+    let expected = "graph LR
+  n0[\"Cabin\"]:::branch
+  n1[\"Door<br/>Row1,Row2<br/>Left,Right\"]:::branch
+  n2[\"IsOpen\"]:::actuator
+  n0 --> n1
+  n1 --> n2
+  classDef branch fill:#d9d9d9
+  classDef sensor fill:#a6d96a
+  classDef actuator fill:#fdae61
+  classDef attribute fill:#abd9e9
+  classDef instance fill:#f0f0f0
+";
+    assert_eq!(graph(VssGraphFormat::Mermaid, &cabin()), expected);
+}
+
+#[test]
+fn depth_limit_and_no_color() {
+    let options = VssGraphOptions {
+        depth: Some(1),
+        colors: false,
+        ..VssGraphOptions::default()
+    };
+    let expected = "graph LR
+  n0[\"Vehicle\"]
+  n1[\"Cabin\"]
+  n2[\"Speed\"]
+  n3[\"Width\"]
+  n0 --> n1
+  n0 --> n2
+  n0 --> n3
+";
+    assert_eq!(graph(VssGraphFormat::Mermaid, &options), expected);
+
+    let dot = graph(VssGraphFormat::Dot, &options);
+    assert!(!dot.contains("fillcolor=\"#d9d9d9\""));
+    assert!(dot.contains("  n0 [label=\"Vehicle\"];\n"));
+
+    // depth 0 only draws root
+    let options = VssGraphOptions {
+        depth: Some(0),
+        ..cabin()
+    };
+    let mermaid = graph(VssGraphFormat::Mermaid, &options);
+    assert!(mermaid.starts_with("graph LR\n  n0[\"Cabin\"]:::branch\n  classDef "));
+}
+
+#[test]
+fn expanded_instances() {
+    let options = VssGraphOptions {
+        collapse_instances: false,
+        colors: false,
+        ..cabin()
+    };
+    let mermaid = graph(VssGraphFormat::Mermaid, &options);
+    let labels = mermaid
+        .lines()
+        .filter_map(|line| line.split_once("[\""))
+        .map(|(_, label)| label.trim_end_matches("\"]"))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        labels,
+        [
+            "Cabin", "Door", "Row1", "Left", "IsOpen", "Right", "IsOpen", "Row2", "Left", "IsOpen",
+            "Right", "IsOpen"
+        ]
+    );
+    assert!(mermaid.contains("  n1 --> n7\n  n7 --> n8\n  n8 --> n9\n"));
+
+    // instance levels are dashed
+    let options = VssGraphOptions {
+        collapse_instances: false,
+        ..cabin()
+    };
+    let dot = graph(VssGraphFormat::Dot, &options);
+    assert!(dot.contains(
+        "  n2 [label=\"Row1\", fillcolor=\"#f0f0f0\", style=\"rounded,filled,dashed\"];\n"
+    ));
+    assert_eq!(dot.matches("dashed").count(), 6);
+}
+
+#[test]
+fn root_selection() {
+    let options = VssGraphOptions {
+        root: Some("Vehicle.Speed".to_string()),
+        ..VssGraphOptions::default()
+    };
+    assert_eq!(
+        graph(VssGraphFormat::Dot, &options)
+            .lines()
+            .filter(|line| line.contains("label="))
+            .collect::<Vec<_>>(),
+        ["  n0 [label=\"Speed\", fillcolor=\"#a6d96a\"];"]
+    );
+
+    let options = VssGraphOptions {
+        root: Some("Vehicle.Unknown".to_string()),
+        ..VssGraphOptions::default()
+    };
+    let error = vss_graph_build(&parse("graph/graph.vspec"), VssGraphFormat::Dot, &options)
+        .expect_err("unknown root accepted");
+    assert!(error.to_str().starts_with("uid:vss-graph-root "));
+
+    assert_eq!(
+        "mmd".parse::<VssGraphFormat>().ok(),
+        Some(VssGraphFormat::Mermaid)
+    );
+    assert_eq!(
+        "Graphviz".parse::<VssGraphFormat>().ok(),
+        Some(VssGraphFormat::Dot)
+    );
+    assert!("svg".parse::<VssGraphFormat>().is_err());
+}
@@ -0,0 +1,232 @@
+/*
+ * Copyright (C) 2015-2023 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
+ * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
+ *
+ */
+
+extern crate vssparser;
+
+mod common;
+
+use common::{parse, parse_with};
+use vssparser::prelude::*;
+
+fn parse_policy(vspec: &str, policy: VssDuplicates) -> VssSpec {
+    parse_with(vspec, |vss| vss.data.duplicates = policy)
+}
+
+fn vpaths<'a>(vpaths: impl Iterator<Item = &'a String>) -> Vec<&'a str> {
+    let mut vpaths = vpaths.map(|vpath| vpath.as_str()).collect::<Vec<&str>>();
+    vpaths.sort();
+    vpaths
+}
+
+#[test]
+fn nested_prefix_composition() {
+    let spec = parse("include/root.vspec");
+    assert_eq!(
+        vpaths(spec.branches.iter().map(|obj| &obj.vpath)),
+        vec![
+            "Vehicle",
+            "Vehicle.Body",
+            "Vehicle.Body.Hood",
+            "Vehicle.Body.Lights",
+            "Vehicle.Body.Lights.Beam",
+            "Vehicle.Body.Lights.Fog",
+            "Vehicle.Cabin",
+            "Vehicle.Cabin.Door",
+            "Vehicle.Cabin.Seat",
+        ]
+    );
+    assert_eq!(
+        vpaths(spec.sensors.iter().map(|obj| &obj.vpath)),
+        vec![
+            "Vehicle.Body.Hood.IsOpen",
+            "Vehicle.Body.Lights.Beam.IsOn",
+            "Vehicle.Body.Lights.Fog.IsOn",
+            "Vehicle.Cabin.Door.IsLocked",
+            "Vehicle.Cabin.Seat.IsLocked",
+            "Vehicle.Speed",
+        ]
+    );
+    assert_eq!(
+        vpaths(spec.actuators.iter().map(|obj| &obj.vpath)),
+        vec![
+            "Vehicle.Body.Hood.Position",
+            "Vehicle.Body.Lights.Beam.Level",
+            "Vehicle.Cabin.Door.IsOpen",
+            "Vehicle.Cabin.Seat.IsOpen",
+        ]
+    );
+    assert_eq!(
+        vpaths(spec.attributes.iter().map(|obj| &obj.vpath)),
+        vec![
+            "Vehicle.Body.Hood.Material",
+            "Vehicle.Body.Lights.Beam.Count",
+            "Vehicle.Cabin.Door.Width",
+            "Vehicle.Cabin.Seat.Width",
+        ]
+    );
+}
+
+#[test]
+fn prefix_applied_once_for_every_node_type() {
+    let spec = parse("include/root.vspec");
+    let index = VssIndex::new(&spec);
+    for node in index.iter() {
+        let filename = spec.filename(node.location());
+        if let Some(prefix) = &filename.prefix {
+            let label = node.vpath().strip_prefix(&format!("{}.", prefix));
+            assert!(label.is_some(), "{} misses prefix {}", node.vpath(), prefix);
+            assert!(
+                !label.unwrap().starts_with("Vehicle."),
+                "{} prefix applied twice",
+                node.vpath()
+            );
+        }
+    }
+}
+
+#[test]
+fn included_files_hold_composed_prefix() {
+    let spec = parse("include/root.vspec");
+    let prefix = |basename: &str| -> Vec<Option<&str>> {
+        spec.files
+            .iter()
+            .filter(|file| file.basename == basename)
+            .map(|file| file.prefix.as_deref())
+            .collect()
+    };
+    assert_eq!(prefix("root.vspec"), vec![None]);
+    assert_eq!(prefix("Body.vspec"), vec![Some("Vehicle.Body")]);
+    assert_eq!(prefix("Lights.vspec"), vec![Some("Vehicle.Body.Lights")]);
+    assert_eq!(prefix("Fog.vspec"), vec![Some("Vehicle.Body.Lights")]);
+    assert_eq!(
+        prefix("Door.vspec"),
+        vec![Some("Vehicle.Cabin.Door"), Some("Vehicle.Cabin.Seat")]
+    );
+}
+
+#[test]
+fn compose_prefix() {
+    assert_eq!(vss_compose_prefix(None, None), None);
+    assert_eq!(
+        vss_compose_prefix(Some("A.B"), None),
+        Some("A.B".to_string())
+    );
+    assert_eq!(vss_compose_prefix(None, Some("C")), Some("C".to_string()));
+    assert_eq!(
+        vss_compose_prefix(Some("A.B"), Some("C.D")),
+        Some("A.B.C.D".to_string())
+    );
+}
+
+#[test]
+fn blame_lists_include_chain() {
+    let spec = parse("include/root.vspec");
+    let blame = VssBlame::new(&spec, "Vehicle.Body.Lights.Fog.IsOn").expect("vpath not found");
+    assert_eq!(blame.definitions.len(), 1);
+    let definition = &blame.definitions[0];
+    assert_eq!(definition.overlay, None);
+    let chain = definition
+        .chain
+        .iter()
+        .map(|step| {
+            let filename = spec.filename(step.location);
+            (
+                filename.basename.as_str(),
+                step.location.line,
+                step.prefix.as_deref(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        chain,
+        vec![
+            ("Lights.vspec", 21, None),
+            ("Body.vspec", 25, Some("Lights")),
+            ("root.vspec", 16, Some("Vehicle.Body")),
+        ]
+    );
+
+    // nested include paths hold a single '/' between directories
+    let text = blame.to_text(&spec);
+    let dirname = format!("{}/tests/spec/include", env!("CARGO_MANIFEST_DIR"));
+    assert!(!text.contains("//"), "{}", text);
+    for path in [
+        "/body/lights/Fog.vspec:5 sensor",
+        "/body/lights/Lights.vspec:21 prefix:(none)",
+        "/body/Body.vspec:25 prefix:Lights",
+        "/root.vspec:16 prefix:Vehicle.Body",
+    ] {
+        assert!(text.contains(&format!("{}{}", dirname, path)), "{}", text);
+    }
+}
+
+fn speed_description(spec: &VssSpec) -> Vec<&str> {
+    spec.sensors
+        .iter()
+        .filter(|obj| obj.vpath == "Vehicle.Speed")
+        .map(|obj| obj.description.as_deref().unwrap_or(""))
+        .collect()
+}
+
+// (level, uid, basename:line of duplicate) for every diagnostic
+fn duplicates(spec: &VssSpec) -> Vec<(VssLevel, &str, String)> {
+    spec.diagnostics
+        .iter()
+        .map(|diagnostic| {
+            let filename = spec.filename(diagnostic.location);
+            (
+                diagnostic.level.clone(),
+                diagnostic.uid.as_ref(),
+                format!("{}:{}", filename.basename, diagnostic.location.line),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn duplicate_policy() {
+    let spec = parse_policy("duplicate/root.vspec", VssDuplicates::Error);
+    assert_eq!(speed_description(&spec), vec!["First speed."]);
+    assert_eq!(duplicates(&spec)[0].0, VssLevel::Error);
+    assert!(spec.diagnostics[0].info.contains("first.vspec:1"));
+    assert!(spec.superseded.is_empty());
+
+    let spec = parse_policy("duplicate/root.vspec", VssDuplicates::FirstWins);
+    assert_eq!(speed_description(&spec), vec!["First speed."]);
+    assert_eq!(duplicates(&spec)[0].0, VssLevel::Warning);
+
+    let spec = parse_policy("duplicate/root.vspec", VssDuplicates::LastWins);
+    assert_eq!(speed_description(&spec), vec!["Second speed."]);
+    assert_eq!(duplicates(&spec)[0].0, VssLevel::Warning);
+    assert_eq!(spec.superseded.len(), 1);
+    let blame = VssBlame::new(&spec, "Vehicle.Speed").expect("vpath not found");
+    assert_eq!(blame.definitions.len(), 2);
+}
+
+#[test]
+fn duplicate_type_conflict_is_an_error() {
+    for policy in [
+        VssDuplicates::Error,
+        VssDuplicates::FirstWins,
+        VssDuplicates::LastWins,
+    ] {
+        let spec = parse_policy("duplicate/root.vspec", policy);
+        assert_eq!(
+            duplicates(&spec)[1],
+            (
+                VssLevel::Error,
+                "vss-duplicate-type",
+                "second.vspec:7".to_string()
+            )
+        );
+        assert!(spec.diagnostics[1].info.contains("first.vspec:7"));
+        assert!(spec.branches.iter().any(|obj| obj.vpath == "Vehicle.Door"));
+        assert!(!spec.sensors.iter().any(|obj| obj.vpath == "Vehicle.Door"));
+    }
+}
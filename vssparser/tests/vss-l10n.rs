@@ -0,0 +1,118 @@
+/*
+ * Copyright (C) 2015-2023 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
+ * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
+ *
+ */
+
+extern crate vssparser;
+
+mod common;
+
+use common::parse;
+use vssparser::prelude::*;
+
+fn fixture(name: &str) -> String {
+    format!("{}/tests/spec/l10n/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+#[test]
+fn side_file_and_locale_fallback() {
+    let mut spec = parse("vehicle.vspec");
+    let count = vss_l10n_from_file(&mut spec, fixture("fr.vspec.l10n").as_str(), None)
+        .expect("fail to load l10n");
+    assert_eq!(count, 3);
+
+    let index = VssIndex::new(&spec);
+    let node = |vpath: &str| index.get(vpath).expect("vpath not found");
+
+    // side file overloads inline translation, locale is taken from basename
+    let vehicle = node("Vehicle");
+    assert_eq!(
+        vehicle.description_l10n(Some("fr")),
+        Some("Données du véhicule.")
+    );
+    assert_eq!(vehicle.name_l10n(Some("fr")), "Véhicule");
+
+    // region falls back on language, unknown locale on english
+    let speed = node("Vehicle.Speed");
+    assert_eq!(speed.name_l10n(Some("fr_FR")), "Vitesse");
+    assert_eq!(
+        speed.description_l10n(Some("fr-CA")),
+        Some("Vitesse du véhicule.")
+    );
+    assert_eq!(speed.name_l10n(Some("it")), "Speed");
+    assert_eq!(speed.description_l10n(None), Some("Vehicle speed."));
+
+    // language never falls back on a region
+    assert_eq!(
+        speed.comment_l10n(Some("de-de")),
+        Some("an den Rädern gemessen")
+    );
+    assert_eq!(speed.comment_l10n(Some("de")), Some("measured at wheels"));
+    assert_eq!(speed.comment_l10n(Some("fr")), Some("measured at wheels"));
+}
+
+#[test]
+fn explicit_locale() {
+    let mut spec = parse("vehicle.vspec");
+    vss_l10n_from_file(
+        &mut spec,
+        fixture("cabin.vspec.l10n").as_str(),
+        Some("de_DE"),
+    )
+    .expect("fail to load l10n");
+    let index = VssIndex::new(&spec);
+    let door = index.get("Vehicle.Cabin.Door").expect("vpath not found");
+    assert_eq!(door.description_l10n(Some("de_DE")), Some("Alle Türen."));
+    assert_eq!(door.description_l10n(Some("de")), Some("All doors."));
+    assert!(door.l10n().iter().all(|l10n| l10n.locale != "cabin"));
+}
+
+#[test]
+fn export_within_locale() {
+    let spec = parse("vehicle.vspec");
+    let french = vss_export_build(&spec, VssExportFormat::Json, Some("fr"));
+    assert!(french.contains("\"description\": \"Données générales du véhicule.\""));
+    let english = vss_export_build(&spec, VssExportFormat::Json, None);
+    assert!(!english.contains("Données"));
+
+    // region specific comment, description falls back on english
+    let csv = vss_export_build(&spec, VssExportFormat::Csv, Some("de_DE"));
+    assert!(csv.contains("\"an den Rädern gemessen\""));
+    assert!(csv.contains("\"Vehicle speed.\""));
+}
+
+#[test]
+fn missing_translations() {
+    let mut spec = parse("vehicle.vspec");
+    let documented = VssIndex::new(&spec).len();
+    let missing = vss_l10n_missing(&spec, "fr");
+    assert_eq!(missing.len(), documented - 1);
+    assert!(!missing.contains(&"Vehicle"));
+
+    vss_l10n_from_file(&mut spec, fixture("fr.vspec.l10n").as_str(), None)
+        .expect("fail to load l10n");
+    let missing = vss_l10n_missing(&spec, "fr_BE");
+    assert_eq!(missing.len(), documented - 3);
+    for vpath in ["Vehicle", "Vehicle.Speed", "Vehicle.Cabin"] {
+        assert!(!missing.contains(&vpath), "{}", vpath);
+    }
+    assert!(missing.contains(&"Vehicle.Cabin.Door"));
+
+    // inline comment.de_DE is not a description translation
+    assert_eq!(vss_l10n_missing(&spec, "de").len(), documented);
+}
+
+#[test]
+fn unknown_vpath_is_located() {
+    let mut spec = parse("vehicle.vspec");
+    let error = vss_l10n_from_file(&mut spec, fixture("unknown.vspec.l10n").as_str(), None)
+        .expect_err("unknown vpath accepted");
+    assert!(error.to_str().starts_with("uid:vss-l10n-vpath "));
+    assert!(error
+        .to_str()
+        .contains("unknown.vspec.l10n:4 vpath:Vehicle.Unknown not found"));
+}
@@ -0,0 +1,150 @@
+/*
+ * Copyright (C) 2015-2023 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
+ * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
+ *
+ */
+
+extern crate vssparser;
+
+mod common;
+
+use common::{load_with, parse_with};
+use vssparser::prelude::*;
+
+fn refused(name: &str) -> String {
+    match VssLintConfig::from_file(&fixture(name)) {
+        Ok(_) => panic!("{} accepted", name),
+        Err(error) => error.to_str().to_string(),
+    }
+}
+
+fn fixture(name: &str) -> String {
+    format!("{}/tests/spec/lint/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+fn parse(trivia: bool) -> VssSpec {
+    parse_with("lint/lint.vspec", |vss| vss.data.trivia = trivia)
+}
+
+fn findings(spec: &VssSpec, config: &VssLintConfig) -> Vec<(VssLevel, String, String, u32)> {
+    vss_lint(spec, config)
+        .into_iter()
+        .map(|diagnostic| {
+            (
+                diagnostic.level,
+                diagnostic.uid.to_string(),
+                diagnostic.vpath,
+                diagnostic.location.line,
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn default_rules() {
+    let spec = parse(false);
+    let expected = [
+        ("abbreviation", "Vehicle.CabinTemp", 23),
+        ("boolean-prefix", "Vehicle.Moving", 12),
+        ("unit-in-name", "Vehicle.SpeedKmh", 17),
+        ("name-case", "Vehicle.engineSpeed", 6),
+    ]
+    .map(|(uid, vpath, line)| (VssLevel::Warning, uid.to_string(), vpath.to_string(), line));
+    assert_eq!(findings(&spec, &VssLintConfig::default()), expected);
+
+    // description-missing is off by default
+    let mut config = VssLintConfig::default();
+    assert!(config.set_level("description-missing", Some(VssLevel::Error)));
+    assert!(!config.set_level("camel-case", None));
+    let missing = findings(&spec, &config)
+        .into_iter()
+        .filter(|(_, uid, _, _)| uid == "description-missing")
+        .collect::<Vec<_>>();
+    assert_eq!(
+        missing,
+        [(
+            VssLevel::Error,
+            "description-missing".to_string(),
+            "Vehicle.Width".to_string(),
+            29
+        )]
+    );
+}
+
+#[test]
+fn config_from_file() {
+    let spec = parse(false);
+    let config = VssLintConfig::from_file(&fixture("lint.yaml")).expect("fail to load config");
+    assert_eq!(config.boolean_prefixes, ["Is", "Has", "Can"]);
+    // lists replace default ones, Kmh is still a default unit word
+    assert_eq!(config.abbreviations, ["Temp", "Lvl"]);
+    let expected = [
+        (VssLevel::Error, "abbreviation", "Vehicle.CabinTemp"),
+        (VssLevel::Warning, "boolean-prefix", "Vehicle.Moving"),
+        (VssLevel::Warning, "unit-in-name", "Vehicle.SpeedKmh"),
+        (VssLevel::Warning, "description-missing", "Vehicle.Width"),
+    ];
+    assert_eq!(
+        findings(&spec, &config)
+            .iter()
+            .map(|(level, uid, vpath, _)| (level.clone(), uid.as_str(), vpath.as_str()))
+            .collect::<Vec<_>>(),
+        expected
+    );
+
+    let error = refused("invalid.yaml");
+    assert!(error.starts_with("uid:vss-lint-config "));
+    assert!(error.contains("invalid.yaml:2 rule:abbreviation invalid severity:fatal"));
+    let error = refused("unknown.yaml");
+    assert!(error.contains("unknown.yaml:3 unknown rule:camel-case"));
+}
+
+#[test]
+fn suppression_with_and_without_trivia() {
+    let mut config = VssLintConfig::default();
+    config.set_level("description-missing", Some(VssLevel::Warning));
+    let plain = findings(&parse(false), &config);
+    assert_eq!(plain, findings(&parse(true), &config));
+
+    let vpaths = plain
+        .iter()
+        .map(|(_, _, vpath, _)| vpath.as_str())
+        .collect::<Vec<_>>();
+    // fuelLvl: every rule disabled, Parked: disabled within block, OilTempCelsius: two rules
+    for vpath in [
+        "Vehicle.fuelLvl",
+        "Vehicle.Parked",
+        "Vehicle.OilTempCelsius",
+    ] {
+        assert!(!vpaths.contains(&vpath), "{} not suppressed", vpath);
+    }
+    assert_eq!(plain.len(), 5);
+
+    // same file without directives reports suppressed nodes
+    let dirname = std::env::temp_dir().join(format!("vss-lint-{}", std::process::id()));
+    std::fs::create_dir_all(&dirname).unwrap();
+    let filename = dirname.join("lint.vspec");
+    let source = std::fs::read_to_string(fixture("lint.vspec")).unwrap();
+    std::fs::write(&filename, source.replace("vss-lint:", "lint:")).unwrap();
+    let locator = load_with(None, filename.to_str().unwrap(), |_| {});
+    let spec = vss_parse_rules(&locator).expect("fail to parse vspec");
+    let unsuppressed = findings(&spec, &config)
+        .into_iter()
+        .filter(|finding| !plain.contains(finding))
+        .map(|(_, uid, vpath, _)| (uid, vpath))
+        .collect::<Vec<_>>();
+    let expected = [
+        ("abbreviation", "Vehicle.OilTempCelsius"),
+        ("unit-in-name", "Vehicle.OilTempCelsius"),
+        ("boolean-prefix", "Vehicle.Parked"),
+        ("name-case", "Vehicle.fuelLvl"),
+        ("abbreviation", "Vehicle.fuelLvl"),
+        ("description-missing", "Vehicle.fuelLvl"),
+    ]
+    .map(|(uid, vpath)| (uid.to_string(), vpath.to_string()));
+    assert_eq!(unsuppressed, expected);
+    std::fs::remove_dir_all(&dirname).unwrap();
+}
@@ -0,0 +1,147 @@
+/*
+ * Copyright (C) 2015-2023 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
+ * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
+ *
+ */
+
+extern crate vssparser;
+
+mod common;
+
+use common::parse;
+use std::sync::Arc;
+use std::thread;
+use vssparser::prelude::*;
+
+fn vpaths<'a>(nodes: Vec<VssNode<'a>>) -> Vec<&'a str> {
+    nodes.iter().map(|node| node.vpath()).collect()
+}
+
+#[test]
+fn query_exact_and_single_segment() {
+    let spec = parse("vehicle.vspec");
+    let index = VssIndex::new(&spec);
+
+    // pattern without wildcard is an exact lookup
+    assert_eq!(vpaths(index.query("Vehicle.Speed")), vec!["Vehicle.Speed"]);
+    assert!(index.query("Vehicle.Unknown").is_empty());
+
+    // '*' stays within one segment
+    assert_eq!(
+        vpaths(index.query("Vehicle.*")),
+        vec![
+            "Vehicle.Body",
+            "Vehicle.Cabin",
+            "Vehicle.Speed",
+            "Vehicle.Width"
+        ]
+    );
+    assert_eq!(
+        vpaths(index.query("Vehicle.Cabin.*")),
+        vec!["Vehicle.Cabin.Door", "Vehicle.Cabin.Language"]
+    );
+    assert_eq!(
+        vpaths(index.query("Vehicle.Body.*.Position")),
+        vec!["Vehicle.Body.Hood.Position"]
+    );
+    assert_eq!(vpaths(index.query("Vehicle.*idth")), vec!["Vehicle.Width"]);
+}
+
+#[test]
+fn query_any_depth() {
+    let spec = parse("vehicle.vspec");
+    let index = VssIndex::new(&spec);
+
+    // '**' matches any depth, including zero segment
+    assert_eq!(
+        vpaths(index.query("Vehicle.Body.**")),
+        vec![
+            "Vehicle.Body",
+            "Vehicle.Body.Hood",
+            "Vehicle.Body.Hood.Position",
+            "Vehicle.Body.Lights.Beam",
+            "Vehicle.Body.Lights.Beam.IsOn",
+        ]
+    );
+    assert_eq!(
+        vpaths(index.query("Vehicle.**.IsO*")),
+        vec!["Vehicle.Body.Lights.Beam.IsOn", "Vehicle.Cabin.Door.IsOpen"]
+    );
+    assert_eq!(
+        vpaths(index.query("**.Beam")),
+        vec!["Vehicle.Body.Lights.Beam"]
+    );
+
+    // trailing segments must all be consumed
+    assert!(index.query("Vehicle.*.Door.IsOpen.*").is_empty());
+    assert!(index.query("Vehicle.**.Beam.IsOn.Unknown").is_empty());
+}
+
+#[test]
+fn filter_by_type_and_datatype() {
+    let spec = parse("vehicle.vspec");
+    let index = VssIndex::new(&spec);
+
+    let mut filter = VssFilter::new();
+    filter.vtype = Some(VssObjectType::Actuator);
+    assert_eq!(
+        vpaths(index.filter("Vehicle.**", &filter)),
+        vec![
+            "Vehicle.Body.Hood.Position",
+            "Vehicle.Cabin.Door.IsOpen",
+            "Vehicle.Cabin.Language",
+        ]
+    );
+
+    filter.datatype = Some(VssValueType::Boolean);
+    assert_eq!(
+        vpaths(index.filter("Vehicle.**", &filter)),
+        vec!["Vehicle.Cabin.Door.IsOpen"]
+    );
+
+    let mut filter = VssFilter::new();
+    filter.vtype = Some(VssObjectType::Sensor);
+    assert_eq!(
+        vpaths(index.filter("Vehicle.*", &filter)),
+        vec!["Vehicle.Speed"]
+    );
+
+    filter.access = Some(VssAccess::ReadWrite);
+    assert!(index.filter("Vehicle.**", &filter).is_empty());
+
+    // actuators share sensor layout, only vtype tells them apart
+    assert_eq!(spec.actuators().count(), 3);
+    assert!(spec
+        .actuators()
+        .all(|actuator| actuator.vtype.access() == VssAccess::ReadWrite));
+    assert!(spec
+        .sensors()
+        .all(|sensor| sensor.vtype == VssObjectType::Sensor));
+}
+
+#[test]
+fn query_shared_spec_from_threads() {
+    let patterns = ["Vehicle.Cabin.**", "Vehicle.*"];
+    let spec = Arc::new(parse("vehicle.vspec"));
+    let workers = patterns.map(|pattern| {
+        let spec = Arc::clone(&spec);
+        thread::spawn(move || {
+            let index = VssIndex::new(&spec);
+            index
+                .query(pattern)
+                .iter()
+                .map(|node| node.vpath().to_string())
+                .collect::<Vec<String>>()
+        })
+    });
+
+    let index = VssIndex::new(&spec);
+    for (worker, pattern) in workers.into_iter().zip(patterns) {
+        let found = worker.join().expect("query thread panicked");
+        assert!(!found.is_empty());
+        assert_eq!(found, vpaths(index.query(pattern)));
+    }
+}
@@ -0,0 +1,60 @@
+/*
+ * Copyright (C) 2015-2023 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
+ * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
+ *
+ */
+
+extern crate vssparser;
+
+mod common;
+
+use std::borrow::Cow;
+
+use common::parse;
+use vssparser::prelude::*;
+
+fn round_trip(vspec: &str) -> VssSpec {
+    let spec = parse(vspec);
+    let json = serde_json::to_string(&spec).expect("fail to serialize spec");
+    let reloaded: VssSpec = serde_json::from_str(&json).expect("fail to deserialize spec");
+    assert_eq!(reloaded, parse(vspec));
+    reloaded
+}
+
+#[test]
+fn spec_round_trip() {
+    let spec = round_trip("vehicle.vspec");
+    assert!(!spec.branches.is_empty());
+    assert!(!spec.sensors.is_empty());
+    assert!(!spec.actuators.is_empty());
+    assert!(!spec.attributes.is_empty());
+    assert!(spec.files.len() > 2);
+}
+
+#[test]
+fn diagnostic_round_trip() {
+    let spec = round_trip("diagnostic.vspec");
+    assert!(!spec.diagnostics.is_empty());
+
+    // reloaded uids are owned strings, nothing is leaked to get a 'static lifetime
+    let diagnostic = &spec.diagnostics[0];
+    assert!(matches!(diagnostic.uid, Cow::Owned(_)));
+    assert_eq!(diagnostic.uid, "vss-unit-datatype");
+}
+
+#[test]
+fn unit_round_trip() {
+    for info in VssUnitInfo::get_pool().iter() {
+        let json = serde_json::to_string(&info.uid).expect("fail to serialize unit");
+        let unit: VssUnit = serde_json::from_str(&json).expect("fail to deserialize unit");
+        assert_eq!(unit, info.uid);
+    }
+    for unit in [VssUnit::None, VssUnit::Custom("furlong".to_string())] {
+        let json = serde_json::to_string(&unit).expect("fail to serialize unit");
+        let reloaded: VssUnit = serde_json::from_str(&json).expect("fail to deserialize unit");
+        assert_eq!(reloaded, unit);
+    }
+}
@@ -0,0 +1,120 @@
+/*
+ * Copyright (C) 2015-2023 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
+ * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
+ *
+ */
+
+extern crate vssparser;
+
+mod common;
+
+use common::load;
+use serde_json::{json, Value};
+use vssparser::prelude::*;
+
+fn stats(vspec: &str) -> VssStats {
+    let locator = load(vspec);
+    let spec = vss_parse_rules(&locator).expect("fail to parse vspec");
+    VssStats::new(&spec, &locator.units)
+}
+
+#[test]
+fn stats_json() {
+    let stats = stats("vehicle.vspec");
+    let json: Value = serde_json::from_str(&stats.to_json()).expect("invalid json");
+
+    assert_eq!(json["nodes"], 12);
+    assert_eq!(
+        json["vtypes"],
+        json!({"actuator": 3, "attribute": 1, "branch": 6, "sensor": 2})
+    );
+    assert_eq!(
+        json["datatypes"],
+        json!({"boolean": 2, "float": 1, "string[]": 1, "uint16": 1, "uint8": 1})
+    );
+    assert_eq!(
+        json["units"],
+        json!({"distance": {"mm": 1}, "relation": {"percent": 1}, "speed": {"km/h": 1}})
+    );
+    assert_eq!(json["max_depth"], 5);
+    assert_eq!(json["avg_depth"], 2.92);
+    assert_eq!(json["deprecated"], 1);
+    assert_eq!(json["undocumented"], json!([]));
+    assert_eq!(
+        (json["errors"].clone(), json["warnings"].clone()),
+        (json!(0), json!(0))
+    );
+    let total: usize = stats.files.iter().map(|(_, count)| count).sum();
+    assert_eq!(total, stats.nodes);
+}
+
+#[test]
+fn include_stats_json() {
+    let stats = stats("include/root.vspec");
+    let json: Value = serde_json::from_str(&stats.to_json()).expect("invalid json");
+
+    assert_eq!(json["nodes"], 23);
+    assert_eq!(
+        json["vtypes"],
+        json!({"actuator": 4, "attribute": 4, "branch": 9, "sensor": 6})
+    );
+    assert_eq!(
+        json["datatypes"],
+        json!({"boolean": 7, "float": 1, "string": 1, "uint16": 2, "uint8": 3})
+    );
+    assert_eq!(
+        json["units"],
+        json!({"distance": {"mm": 2}, "relation": {"percent": 1}, "speed": {"km/h": 1}})
+    );
+    assert_eq!(json["max_depth"], 5);
+    assert_eq!(json["avg_depth"], 3.61);
+    assert_eq!(json["instance_branches"], 0);
+    assert_eq!(json["instantiated_nodes"], 0);
+    assert_eq!(json["undocumented"], json!([]));
+    assert_eq!(
+        (json["errors"].clone(), json["warnings"].clone()),
+        (json!(0), json!(0))
+    );
+
+    // Door.vspec is included twice, every inclusion holds its own entry
+    let files = json["files"]
+        .as_array()
+        .expect("files is not an array")
+        .iter()
+        .map(|file| {
+            let name = file["file"].as_str().expect("no file name");
+            (
+                name.rsplit('/').next().unwrap(),
+                file["nodes"].as_u64().unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        files,
+        [
+            ("root.vspec", 6),
+            ("Body.vspec", 5),
+            ("Door.vspec", 3),
+            ("Door.vspec", 3),
+            ("Lights.vspec", 4),
+            ("Fog.vspec", 2)
+        ]
+    );
+    let total: usize = stats.files.iter().map(|(_, count)| count).sum();
+    assert_eq!(total, stats.nodes);
+}
+
+#[test]
+fn instance_stats() {
+    let stats = stats("vehicle.vspec");
+    assert_eq!(stats.instance_branches, 1);
+    assert_eq!(stats.template_nodes, 1);
+    // Row1,Row2 x DriverSide,PassengerSide branches plus one IsOpen under each pair
+    assert_eq!(stats.instantiated_nodes, 10);
+    assert!(stats
+        .to_text()
+        .contains("instances: 1 branch(es), 1 template node(s), 10 instantiated node(s)\n"));
+}
@@ -0,0 +1,74 @@
+/*
+ * Copyright (C) 2015-2023 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
+ * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
+ *
+ */
+
+extern crate vssparser;
+
+mod common;
+
+use common::parse_with;
+use vssparser::prelude::*;
+
+fn parse(trivia: bool) -> VssSpec {
+    parse_with("trivia/trivia.vspec", |vss| vss.data.trivia = trivia)
+}
+
+#[test]
+fn trivia_mode_keeps_spec() {
+    let plain = parse(false);
+    let trivia = parse(true);
+    assert_eq!(
+        vss_export_build(&trivia, VssExportFormat::Json, None),
+        vss_export_build(&plain, VssExportFormat::Json, None)
+    );
+    let locations = |spec: &VssSpec| {
+        VssIndex::new(spec)
+            .iter()
+            .map(|node| (node.vpath().to_string(), node.location().line))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(locations(&trivia), locations(&plain));
+    // whitespace only line within folded text is a blank line in both modes
+    let description = |spec: &VssSpec| spec.sensors[0].description.clone();
+    assert_eq!(description(&plain), description(&trivia));
+    assert_eq!(
+        description(&plain).as_deref(),
+        Some("Vehicle speed, in km/h.")
+    );
+    assert_eq!(
+        locations(&plain),
+        [
+            ("Vehicle".to_string(), 2),
+            ("Vehicle.Speed".to_string(), 11)
+        ]
+    );
+
+    // plain mode records nothing
+    assert!(plain.branches.iter().all(|branch| branch.trivia.is_none()));
+    assert!(plain.sensors.iter().all(|sensor| sensor.trivia.is_none()));
+    assert!(plain.trailing.is_empty());
+}
+
+#[test]
+fn trivia_mode_captures_comments_and_blank_lines() {
+    let spec = parse(true);
+    let vehicle = spec.branches[0].trivia.clone().expect("no trivia");
+    assert_eq!(vehicle.comments, [" Vehicle root"]);
+    assert_eq!(vehicle.inner, [" inner comment"]);
+    assert_eq!(vehicle.blank_lines, 0);
+
+    // empty and whitespace only lines count the same
+    let speed = spec.sensors[0].trivia.clone().expect("no trivia");
+    assert_eq!(speed.comments, [" speed comes next", " with two comments"]);
+    assert!(speed.inner.is_empty());
+    assert_eq!(speed.blank_lines, 2);
+
+    let (_, trailing) = &spec.trailing[0];
+    assert_eq!(trailing.comments, [" end of file"]);
+    assert_eq!(trailing.blank_lines, 1);
+}
@@ -0,0 +1,110 @@
+/*
+ * Copyright (C) 2015-2023 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
+ * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
+ *
+ */
+
+extern crate vssparser;
+
+use vssparser::prelude::*;
+
+fn fixture(name: &str) -> String {
+    format!("{}/tests/spec/units/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+#[test]
+fn load_quantities_and_units() {
+    let pool = VssUnitPool::from_file(
+        fixture("units.yaml").as_str(),
+        Some(fixture("quantities.yaml").as_str()),
+    )
+    .expect("fail to load units");
+
+    let names = pool
+        .quantities()
+        .map(|quantity| quantity.name.as_str())
+        .collect::<Vec<&str>>();
+    assert_eq!(names, vec!["velocity", "temperature", "length"]);
+    let velocity = pool.get_quantity("velocity").expect("quantity not found");
+    assert_eq!(
+        velocity.definition,
+        "Rate of change of the position of an object"
+    );
+    assert_eq!(
+        velocity.remark.as_deref(),
+        Some("speed is the magnitude of velocity")
+    );
+    let temperature = pool
+        .get_quantity("temperature")
+        .expect("quantity not found");
+    assert_eq!(temperature.definition, "Measure of \"hotness\"");
+    assert_eq!(temperature.comment, None);
+
+    // built-in unit keeps its scale, registry label and datatypes come from yaml
+    let speed = pool.find("km/h").expect("unit not found");
+    assert_eq!(speed.uid, VssUnit::km_h);
+    assert_eq!(speed.label, "kilometer per hour");
+    assert_eq!(speed.domain, VssUnitClass::speed);
+    assert_eq!(speed.datatypes, vec!["numeric"]);
+    assert_eq!(speed.scale, VssUnit::km_h.get_scale());
+
+    // custom units get their scale from factor/offset
+    let furlong = pool.find("furlong").expect("unit not found");
+    assert_eq!(furlong.uid, VssUnit::Custom("furlong".to_string()));
+    assert_eq!(furlong.domain, VssUnitClass::distance);
+    assert_eq!(
+        furlong.description,
+        "Distance measured in furlongs, mostly used for horse races"
+    );
+    assert_eq!(furlong.scale, Some(VssUnitScale::affine(201.168, 0.0)));
+    let fahrenheit = pool.find("degF").expect("unit not found");
+    assert_eq!(fahrenheit.domain, VssUnitClass::temperature);
+    assert_eq!(pool.iter().count(), 3);
+
+    let scale = fahrenheit.scale.expect("degF has no scale");
+    assert!((scale.to_reference(32.0) - 273.15).abs() < 1e-9);
+}
+
+#[test]
+fn load_vss3_units() {
+    let pool =
+        VssUnitPool::from_file(fixture("units3.yaml").as_str(), None).expect("fail to load units");
+    let labels = pool
+        .iter()
+        .map(|info| (info.uid.to_str(), info.label.as_str(), &info.domain))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        labels,
+        vec![
+            ("km/h", "kilometer per hour", &VssUnitClass::speed),
+            ("mm", "millimeter", &VssUnitClass::distance),
+        ]
+    );
+    assert_eq!(pool.quantities().count(), 0);
+}
+
+#[test]
+fn unknown_quantity_is_located() {
+    let error = VssUnitPool::from_file(
+        fixture("unknown.yaml").as_str(),
+        Some(fixture("quantities.yaml").as_str()),
+    )
+    .err()
+    .expect("unknown quantity accepted");
+    assert!(error.to_str().starts_with("uid:vss-quantity-unknown "));
+    assert!(error
+        .to_str()
+        .contains("unknown.yaml:6 unit 'lux' quantity 'illuminance' not defined"));
+}
+
+#[test]
+fn malformed_line_is_located() {
+    let error = VssUnitPool::from_file(fixture("malformed.yaml").as_str(), None)
+        .err()
+        .expect("malformed yaml accepted");
+    assert!(error.to_str().starts_with("uid:yaml-parsing-error "));
+    assert!(error.to_str().contains("malformed.yaml line:3 "));
+}
@@ -0,0 +1,98 @@
+/*
+ * Copyright (C) 2015-2023 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
+ * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
+ *
+ */
+
+extern crate vssparser;
+
+mod common;
+
+use common::parse;
+
+#[test]
+fn unit_diagnostics_are_located() {
+    let spec = parse("units/check.vspec");
+    let found = spec
+        .diagnostics
+        .iter()
+        .map(|diagnostic| {
+            (
+                diagnostic.level.to_str(),
+                diagnostic.uid.as_ref(),
+                diagnostic.vpath.as_str(),
+                spec.filename(diagnostic.location).basename.as_str(),
+                diagnostic.location.line,
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        found,
+        vec![
+            (
+                "warning",
+                "vss-unit-sibling",
+                "Vehicle.Cabin.OutsideTemperature",
+                "check.vspec",
+                36
+            ),
+            (
+                "error",
+                "vss-unit-range",
+                "Vehicle.FuelLevel",
+                "check.vspec",
+                18
+            ),
+            (
+                "error",
+                "vss-unit-datatype",
+                "Vehicle.IsMoving",
+                "check.vspec",
+                6
+            ),
+            (
+                "error",
+                "vss-unit-datatype",
+                "Vehicle.StartTime",
+                "check.vspec",
+                12
+            ),
+        ]
+    );
+
+    let info = |vpath: &str| {
+        spec.diagnostics
+            .iter()
+            .find(|diagnostic| diagnostic.vpath == vpath)
+            .map(|diagnostic| diagnostic.info.as_str())
+            .expect("diagnostic not found")
+    };
+    assert_eq!(
+        info("Vehicle.IsMoving"),
+        "unit:km/h not allowed on boolean datatype"
+    );
+    assert_eq!(
+        info("Vehicle.StartTime"),
+        "unit:Timestamp requires string or uint64 datatype (not float)"
+    );
+    assert_eq!(
+        info("Vehicle.FuelLevel"),
+        "unit:percent min:0 max:120 not within 0..100"
+    );
+    assert_eq!(
+        info("Vehicle.Cabin.OutsideTemperature"),
+        "unit:fahrenheit differs from sibling temperature unit:celsius"
+    );
+}
+
+#[test]
+fn clean_spec_has_no_unit_diagnostic() {
+    let spec = parse("vehicle.vspec");
+    assert!(spec
+        .diagnostics
+        .iter()
+        .all(|diagnostic| !diagnostic.uid.starts_with("vss-unit-")));
+}
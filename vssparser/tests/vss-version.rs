@@ -0,0 +1,184 @@
+/*
+ * Copyright (C) 2015-2023 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
+ * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
+ *
+ */
+
+extern crate vssparser;
+
+mod common;
+
+use common::parse_with;
+use vssparser::prelude::*;
+
+fn parse(vspec: &str, version: VssVersion) -> VssSpec {
+    parse_with(vspec, |vss| vss.data.version = version)
+}
+
+fn version_features(spec: &VssSpec) -> Vec<(&str, u32)> {
+    spec.diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.uid == "vss-version-feature")
+        .map(|diagnostic| (diagnostic.vpath.as_str(), diagnostic.location.line))
+        .collect()
+}
+
+#[test]
+fn parse_version() {
+    for value in ["3", "3.x", "3.1", "v3"] {
+        assert_eq!(value.parse::<VssVersion>().ok(), Some(VssVersion::V3));
+    }
+    for value in ["4", "4.0", "V4.x"] {
+        assert_eq!(value.parse::<VssVersion>().ok(), Some(VssVersion::V4));
+    }
+    assert!("5.0".parse::<VssVersion>().is_err());
+    assert!(VssVersion::V3 < VssVersion::latest());
+}
+
+#[test]
+fn key_profiles() {
+    assert_eq!(vss_key_version("datatype"), Some(VssVersion::V3));
+    assert_eq!(vss_key_version("fka"), Some(VssVersion::V4));
+    assert_eq!(vss_key_version("instantiate"), Some(VssVersion::V3));
+    assert_eq!(vss_key_version("struct"), None);
+}
+
+#[test]
+fn newer_keys_are_warnings() {
+    let spec = parse("fka/fka.vspec", VssVersion::latest());
+    assert!(version_features(&spec).is_empty());
+
+    let spec = parse("fka/fka.vspec", VssVersion::V3);
+    assert_eq!(
+        version_features(&spec),
+        vec![
+            ("Vehicle.Cabin.Seat", 12),
+            ("Vehicle.Cabin.Seat.Position", 18),
+            ("Vehicle.Speed", 25),
+            ("Vehicle.Width", 32),
+            ("Vehicle.Cabin.Door", 37),
+            ("Vehicle.Cabin.Door.IsOpen", 43),
+        ]
+    );
+    // keys are still parsed
+    let index = VssIndex::new(&spec);
+    assert!(index.resolve("Vehicle.OBD.Speed").is_some());
+
+    // specs without newer features are silent
+    let spec = parse("include/root.vspec", VssVersion::V3);
+    assert!(version_features(&spec).is_empty());
+}
+
+fn diagnostics(spec: &VssSpec) -> Vec<(VssLevel, &str, &str, u32)> {
+    spec.diagnostics
+        .iter()
+        .map(|diagnostic| {
+            (
+                diagnostic.level.clone(),
+                diagnostic.uid.as_ref(),
+                diagnostic.vpath.as_str(),
+                diagnostic.location.line,
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn spec_against_both_targets() {
+    // struct datatypes are valid 4.x datatypes, only left unresolved
+    let latest = parse("version/version.vspec", VssVersion::V4);
+    assert_eq!(
+        diagnostics(&latest),
+        [(
+            VssLevel::Warning,
+            "vss-datatype-struct",
+            "Vehicle.Position",
+            33
+        )]
+    );
+
+    let legacy = parse("version/version.vspec", VssVersion::V3);
+    assert_eq!(
+        diagnostics(&legacy),
+        [
+            (
+                VssLevel::Warning,
+                "vss-version-feature",
+                "Vehicle.Speed",
+                30
+            ),
+            (
+                VssLevel::Warning,
+                "vss-version-feature",
+                "Vehicle.Position",
+                33
+            ),
+            (
+                VssLevel::Error,
+                "vss-datatype-struct",
+                "Vehicle.Position",
+                33
+            ),
+        ]
+    );
+    let info = |spec: &VssSpec| {
+        spec.diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.uid == "vss-datatype-struct")
+            .map(|diagnostic| diagnostic.info.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        info(&latest),
+        ["struct datatype:Types.Position definition is not resolved (datatype left unset)"]
+    );
+    assert_eq!(
+        info(&legacy),
+        ["struct datatype:Types.Position is not a vss 3.x datatype"]
+    );
+    // only diagnostics depend on target version
+    assert_eq!(
+        vss_export_build(&legacy, VssExportFormat::Json, None),
+        vss_export_build(&latest, VssExportFormat::Json, None)
+    );
+
+    let index = VssIndex::new(&latest);
+    let position = index.get("Vehicle.Position").expect("vpath not found");
+    assert_eq!(position.datatype(), Some(&VssValueType::Unset));
+    let count = index
+        .get("Vehicle.Cabin.Door.Count")
+        .expect("vpath not found");
+    assert!(!count.instantiate());
+    assert!(index
+        .get("Vehicle.Cabin.Door.IsOpen")
+        .expect("vpath not found")
+        .instantiate());
+}
+
+#[test]
+fn instantiate_false_is_not_expanded() {
+    for version in [VssVersion::V3, VssVersion::V4] {
+        let expanded = vss_expand_instances(&parse("version/version.vspec", version));
+        let index = VssIndex::new(&expanded);
+        let vpaths = index
+            .iter()
+            .map(|node| node.vpath())
+            .filter(|vpath| vpath.starts_with("Vehicle.Cabin.Door."))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vpaths,
+            [
+                "Vehicle.Cabin.Door.Count",
+                "Vehicle.Cabin.Door.Left",
+                "Vehicle.Cabin.Door.Left.IsOpen",
+                "Vehicle.Cabin.Door.Right",
+                "Vehicle.Cabin.Door.Right.IsOpen",
+            ],
+            "{}",
+            version
+        );
+    }
+}
@@ -0,0 +1,111 @@
+/*
+ * Copyright (C) 2015-2023 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
+ * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
+ *
+ */
+
+extern crate vssparser;
+
+mod common;
+
+use common::parse;
+use vssparser::prelude::*;
+
+fn refused(index: &VssIndex, vpath: &str, value: VssDataValue) -> String {
+    let node = index.get(vpath).expect("vpath not found");
+    match vss_check_write(&node, &value) {
+        Ok(()) => panic!("{} accepted {:?}", vpath, value),
+        Err(error) => error.to_str().to_string(),
+    }
+}
+
+fn accepted(index: &VssIndex, vpath: &str, value: VssDataValue) {
+    let node = index.get(vpath).expect("vpath not found");
+    if let Err(error) = vss_check_write(&node, &value) {
+        panic!("{} refused {:?} ({})", vpath, value, error.to_str());
+    }
+}
+
+#[test]
+fn sensor_attribute_and_branch_are_read_only() {
+    let spec = parse("vehicle.vspec");
+    let index = VssIndex::new(&spec);
+
+    // value is valid for the sensor, only access refuses it
+    let error = refused(&index, "Vehicle.Speed", VssDataValue::Float(50.0));
+    assert!(error.starts_with("uid:vss-value-readonly "));
+    assert!(error.contains("Vehicle.Speed type:sensor"));
+    let speed = index.get("Vehicle.Speed").expect("vpath not found");
+    assert!(vss_check_value(&speed, &VssDataValue::Float(50.0)).is_ok());
+
+    let error = refused(&index, "Vehicle.Width", VssDataValue::Uint32(1800));
+    assert!(error.starts_with("uid:vss-value-readonly "));
+    assert!(error.contains("Vehicle.Width type:attribute"));
+
+    let error = refused(&index, "Vehicle.Cabin", VssDataValue::Bool(true));
+    assert!(error.starts_with("uid:vss-value-readonly "));
+}
+
+#[test]
+fn actuator_value_is_checked() {
+    let spec = parse("vehicle.vspec");
+    let index = VssIndex::new(&spec);
+
+    // uint8 percent, min:0 max:100
+    let position = "Vehicle.Body.Hood.Position";
+    let error = refused(&index, position, VssDataValue::Uint32(101));
+    assert!(error.starts_with("uid:vss-value-range "));
+    assert!(error.contains("not within min:0 max:100"));
+    let error = refused(&index, position, VssDataValue::Int32(-1));
+    assert!(error.starts_with("uid:vss-value-range "));
+    let error = refused(&index, position, VssDataValue::Uint32(300));
+    assert!(error.contains("out of uint8 bounds"));
+    let error = refused(&index, position, VssDataValue::Bool(true));
+    assert!(error.starts_with("uid:vss-value-datatype "));
+    let error = refused(&index, position, VssDataValue::Uint32Array(vec![1, 2]));
+    assert!(error.starts_with("uid:vss-value-arraysize "));
+
+    // string[] with allowed values
+    let language = "Vehicle.Cabin.Language";
+    let value = VssDataValue::StringArray(vec!["fr".to_string(), "it".to_string()]);
+    let error = refused(&index, language, value);
+    assert!(error.starts_with("uid:vss-value-allowed "));
+    assert!(error.contains("value:'it' not within allowed:[en,fr,de]"));
+    let error = refused(&index, language, VssDataValue::String("fr".to_string()));
+    assert!(error.starts_with("uid:vss-value-arraysize "));
+}
+
+#[test]
+fn valid_actuator_writes() {
+    let spec = parse("vehicle.vspec");
+    let index = VssIndex::new(&spec);
+
+    accepted(
+        &index,
+        "Vehicle.Body.Hood.Position",
+        VssDataValue::Uint32(0),
+    );
+    accepted(
+        &index,
+        "Vehicle.Body.Hood.Position",
+        VssDataValue::Int64(100),
+    );
+    accepted(
+        &index,
+        "Vehicle.Cabin.Door.IsOpen",
+        VssDataValue::Bool(true),
+    );
+    accepted(
+        &index,
+        "Vehicle.Cabin.Language",
+        VssDataValue::StringArray(vec!["en".to_string(), "de".to_string()]),
+    );
+    accepted(
+        &index,
+        "Vehicle.Cabin.Door.IsOpen",
+        VssDataValue::NotAvailable,
+    );
+}
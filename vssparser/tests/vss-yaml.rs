@@ -0,0 +1,144 @@
+/*
+ * Copyright (C) 2015-2023 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
+ * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
+ *
+ */
+
+extern crate vssparser;
+
+mod common;
+
+use common::try_parse;
+use vssparser::prelude::*;
+
+#[test]
+fn yaml_scalars() {
+    let spec = try_parse("yaml/yaml.vspec").expect("fail to parse vspec");
+    let index = VssIndex::new(&spec);
+    let node = |vpath: &str| index.get(vpath).expect("vpath not found");
+    let description = |vpath: &str| node(vpath).description().expect("no description").as_str();
+
+    assert_eq!(
+        description("Vehicle"),
+        "High-level vehicle data.\nSecond line."
+    );
+    assert_eq!(description("Vehicle.Cabin"), "All in-cabin components.");
+    assert_eq!(
+        node("Vehicle.Cabin").comment().map(String::as_str),
+        Some("Quoted \"escaped\" text, it's fine")
+    );
+    assert_eq!(
+        description("Vehicle.Cabin.Door"),
+        "Door, it's open or closed."
+    );
+    assert_eq!(
+        description("Vehicle.Cabin.Light.Mode"),
+        "Light mode, continued on a misindented line."
+    );
+
+    // hexadecimal and exponent numbers are normalized, quoted ones are kept as text
+    let speed = node("Vehicle.Speed");
+    assert_eq!((speed.min(), speed.max()), (Some(-16), Some(1000)));
+    assert_eq!(speed.allowed(), ["16", "100", "5", "12", "0x20"]);
+}
+
+#[test]
+fn yaml_sequences() {
+    let spec = try_parse("yaml/yaml.vspec").expect("fail to parse vspec");
+    let index = VssIndex::new(&spec);
+    let node = |vpath: &str| index.get(vpath).expect("vpath not found");
+
+    let mode = node("Vehicle.Cabin.Light.Mode");
+    assert_eq!(mode.allowed(), ["OFF", "AUTO", "ON"]);
+    assert_eq!(mode.default(), ["AUTO"]);
+
+    let instances = |vpath: &str| match node(vpath) {
+        VssNode::Branch(branch) => branch
+            .instances
+            .iter()
+            .map(|instance| instance.names())
+            .collect::<Vec<_>>(),
+        _ => panic!("{} is not a branch", vpath),
+    };
+    assert_eq!(
+        instances("Vehicle.Cabin.Door"),
+        vec![vec!["Row1", "Row2"], vec!["DriverSide", "PassengerSide"]]
+    );
+    assert_eq!(
+        instances("Vehicle.Cabin.Light"),
+        vec![vec!["Front", "Rear"]]
+    );
+
+    // block and flow map nodes are located on their label line
+    let labels = [
+        ("Vehicle", 2),
+        ("Vehicle.Cabin", 8),
+        ("Vehicle.Cabin.Door", 15),
+        ("Vehicle.Cabin.Light.Mode", 28),
+        ("Vehicle.Speed", 39),
+    ];
+    for (vpath, line) in labels {
+        assert_eq!(node(vpath).location().line, line, "{}", vpath);
+    }
+    let width = node("Vehicle.Width");
+    assert_eq!(width.vtype(), &VssObjectType::Attribute);
+    assert_eq!(width.default(), ["0"]);
+    assert_eq!(
+        width.description().map(String::as_str),
+        Some("Width, in mm.")
+    );
+    assert_eq!(width.location().line, 48);
+}
+
+#[test]
+fn yaml_errors_are_located() {
+    let error = try_parse("yaml/tab.vspec").expect_err("tab accepted");
+    assert!(error.to_str().starts_with("uid:parsing-error "));
+    assert!(error.to_str().contains("tab.vspec:3 "));
+
+    let error = try_parse("yaml/duplicate-key.vspec").expect_err("duplicated key accepted");
+    assert!(error.to_str().contains("duplicate-key.vspec:4 "));
+}
+
+#[test]
+fn side_file_scalars_and_flows() {
+    let buffer = concat!(
+        "units:\n",
+        "  degree:\n",
+        "    label: \"\\u00b0 \\x41\\tB\" # comment\n",
+        "    description: 'it''s \"raw\\n\"'\n",
+        "    allowed: [\"a, b\", 'c]', \"d\\\"e\", f,]\n",
+        "    range: {min: \"0\", 'max': 360}\n",
+    );
+    let nodes = vss_yaml_parse(buffer).expect("fail to parse yaml");
+    let degree = nodes[0].get("degree").expect("no degree node");
+    assert_eq!(degree.get_str("label"), Some("\u{b0} A\tB"));
+    assert_eq!(degree.get_str("description"), Some("it's \"raw\\n\""));
+    assert_eq!(
+        degree.get("allowed").expect("no allowed").list,
+        ["a, b", "c]", "d\"e", "f"]
+    );
+    let range = degree.get("range").expect("no range");
+    assert_eq!(
+        (range.get_str("min"), range.get_str("max")),
+        (Some("0"), Some("360"))
+    );
+
+    for (buffer, line) in [
+        ("units:\n  list: [a, [b, c]]]\n", 2),
+        ("units:\n  list: [a, \"b]\n", 2),
+        ("units:\n  map: {a: [b}\n", 2),
+        ("units:\n  label: \"bad \\q escape\"\n", 2),
+    ] {
+        let error = vss_yaml_parse(buffer).expect_err(buffer);
+        assert!(error.to_str().starts_with("uid:yaml-parsing-error "));
+        assert!(
+            error.to_str().contains(&format!("line:{} ", line)),
+            "{}",
+            buffer
+        );
+    }
+}